@@ -1,17 +1,19 @@
 use std::collections::HashMap;
-use std::rc::Rc;
 
 use serde::Deserialize;
-use web_sys::{CanvasRenderingContext2d, HtmlCanvasElement, HtmlImageElement};
 
 use crate::assets::sprites::{Rectangle, Sprite};
 use crate::physics::Direction;
-use crate::utils::create_image_buffer;
+use crate::video::{Bitmap, Renderer};
 
 #[derive(Deserialize, Hash, Clone, Copy, Debug, PartialEq, Eq)]
 pub enum AnimationName {
     #[serde(alias = "run")]
     Run,
+    #[serde(alias = "run-big")]
+    RunBig,
+    #[serde(alias = "run-fire")]
+    RunFire,
     #[serde(alias = "walk")]
     Walk,
     #[serde(alias = "wake")]
@@ -22,6 +24,12 @@ pub enum AnimationName {
     Bullet,
     #[serde(alias = "coin")]
     Coin,
+    #[serde(alias = "debris")]
+    Debris,
+    #[serde(alias = "sparkle")]
+    Sparkle,
+    #[serde(alias = "puff")]
+    Puff,
 }
 
 #[derive(Deserialize)]
@@ -43,18 +51,14 @@ impl AnimationDefinition {
 
 pub struct Animation {
     name: AnimationName,
-    image: Rc<HtmlImageElement>,
-    frames: HashMap<(Sprite, Direction), HtmlCanvasElement>,
+    image: Bitmap,
+    frames: HashMap<(Sprite, Direction), Bitmap>,
     frame_len: f64,
     key_frames: Vec<Sprite>,
 }
 
 impl Animation {
-    pub fn build(
-        name: AnimationName,
-        animation_def: &AnimationDefinition,
-        image: Rc<HtmlImageElement>,
-    ) -> Self {
+    pub fn build(name: AnimationName, animation_def: &AnimationDefinition, image: Bitmap) -> Self {
         let frames = HashMap::default();
         let frame_len = animation_def.frame_len;
         let key_frames = animation_def.frames.clone();
@@ -71,13 +75,13 @@ impl Animation {
     pub fn name(&self) -> AnimationName {
         self.name
     }
-    pub fn frames(&self) -> &HashMap<(Sprite, Direction), HtmlCanvasElement> {
+    pub fn frames(&self) -> &HashMap<(Sprite, Direction), Bitmap> {
         &self.frames
     }
 
     pub fn define(&mut self, frame: Sprite, direction: Direction, rect: &Rectangle) {
         let mirror = direction == Direction::Left;
-        let buffer = create_image_buffer(self.image.clone(), rect, mirror);
+        let buffer = self.image.crop(rect, mirror);
         self.frames.insert((frame, direction), buffer);
     }
 
@@ -89,7 +93,7 @@ impl Animation {
 
     pub fn draw_frame(
         &self,
-        context: &CanvasRenderingContext2d,
+        renderer: &dyn Renderer,
         x: f64,
         y: f64,
         frame: Sprite,
@@ -107,8 +111,6 @@ impl Animation {
                 self.name, frame, direction, found
             )
         });
-        context
-            .draw_image_with_html_canvas_element(buffer, x.floor(), y.floor())
-            .unwrap();
+        renderer.blit(buffer, x, y);
     }
 }
@@ -1,20 +1,43 @@
 use std::rc::Rc;
 
-use wasm_bindgen::prelude::*;
-use wasm_bindgen::JsCast;
-use web_sys::{AddEventListenerOptions, Event};
-
-use crate::assets::audio::musics::{MusicPlayer, Track};
+use crate::assets::audio::musics::Track;
+use crate::assets::audio::sounds::Fx;
 
 pub mod musics;
 pub mod sounds;
 
+/// Play the sound effects of an entity
+pub trait AudioBoard {
+    fn play(&self, fx: Fx);
+}
+
+/// Play the music tracks of a level
+pub trait MusicPlayer {
+    fn pause(&self);
+    /// Continue the paused track where it stopped
+    fn resume(&self);
+    fn play(&self, track: Track, speed: f64);
+    /// Play the track once, then continue with the next one
+    fn play_then(&self, track: Track, next: Track, next_speed: f64);
+}
+
+/// A music player without any sound, used by headless simulations
+#[derive(Default)]
+pub struct Silence;
+
+impl MusicPlayer for Silence {
+    fn pause(&self) {}
+    fn resume(&self) {}
+    fn play(&self, _track: Track, _speed: f64) {}
+    fn play_then(&self, _track: Track, _next: Track, _next_speed: f64) {}
+}
+
 pub struct MusicController {
-    music_player: Rc<MusicPlayer>,
+    music_player: Rc<dyn MusicPlayer>,
 }
 
 impl MusicController {
-    pub fn new(music_player: Rc<MusicPlayer>) -> Self {
+    pub fn new(music_player: Rc<dyn MusicPlayer>) -> Self {
         Self { music_player }
     }
 
@@ -22,38 +45,15 @@ impl MusicController {
         self.music_player.pause();
     }
 
+    pub fn resume(&self) {
+        self.music_player.resume();
+    }
+
     pub fn play_theme(&self) {
-        let _ = self.music_player.play(Track::Main, 1.);
+        self.music_player.play(Track::Main, 1.);
     }
 
     pub fn play_hurry(&self) {
-        if let Some(audio) = self.music_player.clone().play(Track::Hurry, 1.) {
-            let player = self.music_player.clone();
-            let closure = Closure::wrap(Box::new(move |_: Event| {
-                player.play(Track::Main, 1.3);
-            }) as Box<dyn FnMut(_)>);
-
-            let mut options = AddEventListenerOptions::new();
-            options.once(true);
-
-            audio
-                .add_event_listener_with_callback_and_add_event_listener_options(
-                    "ended",
-                    closure.as_ref().unchecked_ref(),
-                    &options,
-                )
-                .unwrap();
-
-            // The instance of `Closure` that we created will invalidate its
-            // corresponding JS callback whenever it is dropped, so if we were to
-            // normally return from `setup_clock` then our registered closure will
-            // raise an exception when invoked.
-            //
-            // Normally we'd store the handle to later get dropped at an appropriate
-            // time but for now we want it to be a global handler so we use the
-            // `forget` method to drop it without invalidating the closure. Note that
-            // this is leaking memory in Rust, so this should be done judiciously!
-            closure.forget();
-        }
+        self.music_player.play_then(Track::Hurry, Track::Main, 1.3);
     }
 }
@@ -1,11 +1,15 @@
+use std::cell::Cell;
 use std::collections::HashMap;
+use std::rc::Rc;
 
 use serde::Deserialize;
-use wasm_bindgen::{JsCast, JsValue};
-use wasm_bindgen_futures::JsFuture;
-use web_sys::{HtmlAudioElement, Request, Response};
+use wasm_bindgen::prelude::*;
+use wasm_bindgen::JsCast;
+use web_sys::{AddEventListenerOptions, Event, HtmlAudioElement};
 
-use crate::utils::{log, window};
+use crate::assets::audio::MusicPlayer;
+use crate::assets::load_json;
+use crate::utils::log;
 
 #[derive(Hash, Copy, Clone, Debug, Eq, PartialEq)]
 pub enum Track {
@@ -28,31 +32,22 @@ impl MusicDescription {
     pub async fn load(name: &str) -> Result<MusicDescription, JsValue> {
         log(&format!("Loading music sheet '{}'", name));
         let url = format!("assets/musics/{}.json", name);
-        let request = Request::new_with_str(&url)?;
-
-        let resp_value = JsFuture::from(window().fetch_with_request(&request)).await?;
-        let resp: Response = resp_value.dyn_into().unwrap();
-        let json = JsFuture::from(resp.json()?).await?;
-
-        let music_description = json
-            .into_serde::<MusicDescription>()
-            .expect("Error during music loading");
-
-        Ok(music_description)
+        load_json(url.as_str()).await
     }
 }
 
 #[derive(Default)]
-pub struct MusicPlayer {
+pub struct HtmlMusicPlayer {
     tracks: HashMap<Track, HtmlAudioElement>,
     volume: f64,
+    current: Rc<Cell<Option<Track>>>,
 }
 
-impl MusicPlayer {
-    pub async fn load_music(name: &str, volume: f64) -> Result<MusicPlayer, JsValue> {
+impl HtmlMusicPlayer {
+    pub async fn load_music(name: &str, volume: f64) -> Result<HtmlMusicPlayer, JsValue> {
         let desc = MusicDescription::load(name).await?;
 
-        let mut result = MusicPlayer {
+        let mut result = HtmlMusicPlayer {
             volume,
             ..Default::default()
         };
@@ -73,20 +68,75 @@ impl MusicPlayer {
         self.tracks.insert(track, audio);
     }
 
-    pub fn pause(&self) {
+    fn start(&self, track: Track, speed: f64) -> Option<&HtmlAudioElement> {
+        self.pause();
+        self.current.set(Some(track));
+
+        self.tracks.get(&track).inspect(|audio| {
+            let _ = audio.play().unwrap();
+            audio.set_volume(self.volume);
+            audio.set_playback_rate(speed);
+        })
+    }
+}
+
+impl MusicPlayer for HtmlMusicPlayer {
+    fn pause(&self) {
         for audio in self.tracks.values() {
             audio.pause().unwrap();
         }
     }
 
-    pub fn play(&self, track: Track, speed: f64) -> Option<&HtmlAudioElement> {
-        self.pause();
-
-        self.tracks.get(&track).map(|audio| {
+    fn resume(&self) {
+        let current = self.current.get();
+        if let Some(audio) = current.and_then(|track| self.tracks.get(&track)) {
             let _ = audio.play().unwrap();
-            audio.set_volume(self.volume);
-            audio.set_playback_rate(speed);
+        }
+    }
+
+    fn play(&self, track: Track, speed: f64) {
+        let _ = self.start(track, speed);
+    }
+
+    fn play_then(&self, track: Track, next: Track, next_speed: f64) {
+        if let Some(audio) = self.start(track, 1.) {
+            let next_audio = self.tracks.get(&next).cloned();
+            let others: Vec<HtmlAudioElement> = self.tracks.values().cloned().collect();
+            let volume = self.volume;
+            let current = self.current.clone();
+            let closure = Closure::wrap(Box::new(move |_: Event| {
+                for other in others.iter() {
+                    other.pause().unwrap();
+                }
+                current.set(Some(next));
+                if let Some(audio) = &next_audio {
+                    let _ = audio.play().unwrap();
+                    audio.set_volume(volume);
+                    audio.set_playback_rate(next_speed);
+                }
+            }) as Box<dyn FnMut(_)>);
+
+            let options = AddEventListenerOptions::new();
+            options.set_once(true);
+
             audio
-        })
+                .add_event_listener_with_callback_and_add_event_listener_options(
+                    "ended",
+                    closure.as_ref().unchecked_ref(),
+                    &options,
+                )
+                .unwrap();
+
+            // The instance of `Closure` that we created will invalidate its
+            // corresponding JS callback whenever it is dropped, so if we were to
+            // normally return from `setup_clock` then our registered closure will
+            // raise an exception when invoked.
+            //
+            // Normally we'd store the handle to later get dropped at an appropriate
+            // time but for now we want it to be a global handler so we use the
+            // `forget` method to drop it without invalidating the closure. Note that
+            // this is leaking memory in Rust, so this should be done judiciously!
+            closure.forget();
+        }
     }
 }
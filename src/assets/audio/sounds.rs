@@ -1,4 +1,5 @@
 use std::collections::HashMap;
+use std::rc::Rc;
 
 use js_sys::ArrayBuffer;
 use serde::Deserialize;
@@ -6,6 +7,8 @@ use wasm_bindgen::{JsCast, JsValue};
 use wasm_bindgen_futures::JsFuture;
 use web_sys::{AudioBuffer, AudioContext, Request, Response};
 
+use crate::assets::audio::AudioBoard;
+use crate::assets::load_json;
 use crate::utils::{log, window};
 
 #[derive(Deserialize)]
@@ -54,30 +57,23 @@ impl SoundAudioDescription {
     async fn load(name: &str) -> Result<SoundAudioDescription, JsValue> {
         log(&format!("Loading sound sheet '{}'", name));
         let url = format!("assets/sounds/{}.json", name);
-        let request = Request::new_with_str(&url)?;
-
-        let resp_value = JsFuture::from(window().fetch_with_request(&request)).await?;
-        let resp: Response = resp_value.dyn_into().unwrap();
-        let json = JsFuture::from(resp.json()?).await?;
-
-        let audio_description = json
-            .into_serde::<SoundAudioDescription>()
-            .expect("Error during sounds loading");
-
-        Ok(audio_description)
+        load_json(url.as_str()).await
     }
 }
 
-#[derive(Default)]
-pub struct AudioBoard {
+pub struct WebAudioBoard {
+    audio_context: Rc<AudioContext>,
     map: HashMap<Fx, AudioBuffer>,
     volume: f32,
 }
 
-impl AudioBoard {
-    pub async fn load_sounds(name: &str, volume: f32) -> Result<AudioBoard, JsValue> {
+impl WebAudioBoard {
+    pub async fn load_sounds(
+        name: &str,
+        audio_context: Rc<AudioContext>,
+        volume: f32,
+    ) -> Result<WebAudioBoard, JsValue> {
         let desc = SoundAudioDescription::load(name).await?;
-        let audio_context = AudioContext::new().unwrap();
 
         let mut map = HashMap::new();
         for (fx, desc) in desc.fx {
@@ -85,11 +81,18 @@ impl AudioBoard {
             map.insert(fx, audio);
         }
 
-        let result = Self { map, volume };
+        let result = Self {
+            audio_context,
+            map,
+            volume,
+        };
         Ok(result)
     }
+}
 
-    pub fn play(&self, audio_context: &AudioContext, fx: Fx) {
+impl AudioBoard for WebAudioBoard {
+    fn play(&self, fx: Fx) {
+        let audio_context = &self.audio_context;
         let audio_buffer = self
             .map
             .get(&fx)
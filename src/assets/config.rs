@@ -1,13 +1,20 @@
 use std::collections::HashMap;
+#[cfg(not(target_arch = "wasm32"))]
+use std::path::Path;
 
 use serde::Deserialize;
 use wasm_bindgen::prelude::*;
 
 use crate::assets::load_json;
+#[cfg(not(target_arch = "wasm32"))]
+use crate::assets::read_json;
 use crate::assets::sprites::Rectangle;
+use crate::entity::traits::power::PowerUp;
 use crate::input::Action;
 use crate::physics::{Direction, Position, Size};
 
+const CONFIG_URL: &str = "assets/config.json";
+
 #[derive(Deserialize, Copy, Clone, Debug)]
 pub struct JumpingDefault {
     pub duration: f64,
@@ -38,17 +45,100 @@ pub struct PlayerDefault {
     pub stomp: f64,
 }
 
-#[derive(Deserialize, Copy, Clone, Debug)]
+/// A trait of a mob, with its parameters
+#[derive(Deserialize, Clone, Debug)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum TraitDefinition {
+    Solid,
+    Walk,
+    Velocity,
+    Physics,
+    Stomper,
+    Killable {
+        #[serde(default)]
+        ddx: f64,
+        #[serde(default)]
+        ddy: f64,
+    },
+    // Behaviors when stomped
+    Goomba,
+    Koopa {
+        #[serde(alias = "hideDuration")]
+        hide_duration: f64,
+        #[serde(alias = "panicSpeed")]
+        panic_speed: f64,
+    },
+    Bullet,
+    Emitter {
+        interval: f64,
+        mob: String,
+        speed: f64,
+        #[serde(alias = "holdFire")]
+        hold_fire: f64,
+    },
+    // Items
+    Item {
+        power: PowerUp,
+    },
+    Fireball,
+    // Level end
+    Flagpole {
+        castle: f64,
+    },
+    // Solid and moving
+    Platform {
+        #[serde(default)]
+        path: Vec<Position>,
+        #[serde(default)]
+        looping: bool,
+        #[serde(default)]
+        falls: bool,
+    },
+}
+
+#[derive(Deserialize, Clone, Debug)]
 pub struct MobsDefault {
     pub speed: f64,
     pub size: Size,
     pub bbox: Option<Rectangle>,
+    /// The sprite sheet, the mob name by default
+    pub sprites: Option<String>,
+    pub traits: Vec<TraitDefinition>,
+}
+
+impl MobsDefault {
+    /// The traits built on another one are declared after it
+    fn check(&self) -> Result<(), String> {
+        let mut solid = false;
+        let mut walk = false;
+        let mut physics = false;
+        for definition in self.traits.iter() {
+            match definition {
+                TraitDefinition::Solid => solid = true,
+                TraitDefinition::Walk => walk = true,
+                TraitDefinition::Physics if physics => {
+                    return Err(String::from("Physics declared twice"));
+                }
+                TraitDefinition::Physics => physics = true,
+                TraitDefinition::Killable { .. } if !solid => {
+                    return Err(String::from("Missing solid trait before killable"));
+                }
+                TraitDefinition::Koopa { .. } if !walk => {
+                    return Err(String::from("Missing walk trait before koopa"));
+                }
+                _ => {}
+            }
+        }
+        Ok(())
+    }
 }
 
 #[derive(Deserialize, Copy, Clone, Debug)]
 pub struct DevConfiguration {
     #[serde(alias = "showCollision")]
     pub(crate) show_collision: bool,
+    #[serde(alias = "recordInputs")]
+    pub(crate) record_inputs: bool,
 }
 
 #[derive(Deserialize, Copy, Clone, Debug)]
@@ -57,6 +147,33 @@ pub struct SoundsConfiguration {
     pub music: f64,
 }
 
+/// Players in the same level, each one with its keymap
+#[derive(Deserialize, Clone, Debug)]
+pub struct CoopConfiguration {
+    /// A player falling on another one bounces
+    pub stomp: bool,
+    /// Players walking into each other are pushed apart,
+    /// and a block bumped under a partner knocks it
+    pub bump: bool,
+    keymaps: Vec<HashMap<String, Action>>,
+}
+
+impl CoopConfiguration {
+    /// A keymap by player
+    pub fn keymaps(&self) -> Vec<HashMap<String, Action>> {
+        self.keymaps.clone()
+    }
+}
+
+/// The actions of the gamepad buttons, by index in the standard mapping
+#[derive(Deserialize, Clone, Debug)]
+pub struct GamepadConfiguration {
+    /// The stick moves below are ignored, from 0 to 1
+    #[serde(alias = "deadZone")]
+    pub dead_zone: f64,
+    pub buttons: HashMap<usize, Action>,
+}
+
 #[derive(Deserialize, Clone, Debug)]
 pub struct Configuration {
     pub dev: DevConfiguration,
@@ -66,16 +183,40 @@ pub struct Configuration {
     pub gravity: f64,
     pub player: PlayerDefault,
     pub mobs: HashMap<String, MobsDefault>,
+    pub coop: CoopConfiguration,
+    pub gamepad: GamepadConfiguration,
 }
 
 impl Configuration {
     pub async fn load() -> Result<Configuration, JsValue> {
-        let config = load_json("assets/config.json")
-            .await?
-            .into_serde::<Configuration>()
-            .expect("Error during level loading");
+        let configuration: Configuration = load_json(CONFIG_URL).await?;
+        configuration
+            .check()
+            .map_err(|err| JsValue::from_str(&err))?;
+        Ok(configuration)
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn load_file(dir: &Path) -> Result<Configuration, String> {
+        let configuration: Configuration = read_json(dir, CONFIG_URL)?;
+        configuration.check()?;
+        Ok(configuration)
+    }
 
-        Ok(config)
+    /// The mobs are checked once loaded, instead of failing when they first spawn
+    fn check(&self) -> Result<(), String> {
+        for (name, mob) in self.mobs.iter() {
+            mob.check()
+                .map_err(|err| format!("Error in mob {}: {}", name, err))?;
+            for definition in mob.traits.iter() {
+                if let TraitDefinition::Emitter { mob: emitted, .. } = definition {
+                    if !self.mobs.contains_key(emitted) {
+                        return Err(format!("Error in mob {}: unknown mob {}", name, emitted));
+                    }
+                }
+            }
+        }
+        Ok(())
     }
 
     pub fn keymap(&self) -> HashMap<String, Action> {
@@ -86,3 +227,64 @@ impl Configuration {
         self.keymap.get(&key_code).copied()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use serde_json::Value;
+
+    use super::*;
+
+    fn config_with_traits(mob: &str, traits: &str) -> Configuration {
+        let mut json: Value =
+            serde_json::from_str(include_str!("../../www/assets/config.json")).unwrap();
+        json["mobs"][mob]["traits"] = serde_json::from_str(traits).unwrap();
+        serde_json::from_value(json).unwrap()
+    }
+
+    #[test]
+    fn should_check_mobs_traits() {
+        let config: Configuration =
+            serde_json::from_str(include_str!("../../www/assets/config.json")).unwrap();
+        assert_eq!(config.check(), Ok(()));
+
+        let config =
+            config_with_traits("goomba", r#"[{ "type": "killable" }, { "type": "solid" }]"#);
+        assert_eq!(
+            config.check(),
+            Err(String::from(
+                "Error in mob goomba: Missing solid trait before killable"
+            ))
+        );
+        let config = config_with_traits(
+            "goomba",
+            r#"[{ "type": "physics" }, { "type": "physics" }]"#,
+        );
+        assert!(config.check().is_err());
+
+        let emitter = r#"[{ "type": "emitter", "interval": 4, "mob": "bowser", "speed": 80, "holdFire": 60 }]"#;
+        let config = config_with_traits("cannon", emitter);
+        assert!(config.check().is_err());
+    }
+
+    #[test]
+    fn should_not_load_bad_config() {
+        let mut json: Value =
+            serde_json::from_str(include_str!("../../www/assets/config.json")).unwrap();
+        json["mobs"]["koopa"]["traits"] =
+            serde_json::from_str(r#"[{ "type": "koopa", "hideDuration": 5, "panicSpeed": 300 }]"#)
+                .unwrap();
+        let name = format!("plop-mario-bad-config-{}", std::process::id());
+        let dir = std::env::temp_dir().join(name);
+        std::fs::create_dir_all(dir.join("assets")).unwrap();
+        std::fs::write(dir.join(CONFIG_URL), json.to_string()).unwrap();
+
+        let result = Configuration::load_file(dir.as_path());
+        std::fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(
+            result.err(),
+            Some(String::from(
+                "Error in mob koopa: Missing walk trait before koopa"
+            ))
+        );
+    }
+}
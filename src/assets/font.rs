@@ -1,24 +1,39 @@
 use std::collections::HashMap;
-use std::rc::Rc;
+#[cfg(not(target_arch = "wasm32"))]
+use std::path::Path;
 
 use wasm_bindgen::JsValue;
-use web_sys::{CanvasRenderingContext2d, HtmlImageElement};
 
 use crate::assets::load_image;
+#[cfg(not(target_arch = "wasm32"))]
+use crate::assets::read_image;
+use crate::assets::sprites::Rectangle;
+use crate::video::{Bitmap, Renderer};
 
+const FONT_URL: &str = "assets/images/font.png";
 const CHARS: &str = " !\"#$%&\'()*+,-./0123456789:;<=>?@ABCDEFGHIJKLMNOPQRSTUVWXYZ[\\]^_`abcdefghijklmnopqrstuvwxyz{|}~";
 
 #[derive(Clone)]
 pub struct Font {
-    image: Rc<HtmlImageElement>,
+    image: Bitmap,
     map: HashMap<char, (usize, usize)>,
     size: u32,
 }
 
 impl Font {
     pub async fn load() -> Result<Font, JsValue> {
-        let image = load_image("assets/images/font.png").await?;
-        let row_len = image.width() as usize;
+        let image = load_image(FONT_URL).await?;
+        Ok(Font::new(Bitmap::from_image(&image)))
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn load_file(dir: &Path) -> Result<Font, String> {
+        let image = read_image(dir, FONT_URL)?;
+        Ok(Font::new(image))
+    }
+
+    fn new(image: Bitmap) -> Self {
+        let row_len = image.size().width as usize;
         let mut map = HashMap::default();
 
         let size = 8;
@@ -29,43 +44,33 @@ impl Font {
             map.insert(ch, (x, y));
         }
 
-        let result = Self {
+        Self {
             image,
             map,
             size: size as u32,
-        };
-        Ok(result)
+        }
     }
 
     pub fn size(&self) -> u32 {
         self.size
     }
 
-    pub fn print(&self, context: Rc<CanvasRenderingContext2d>, text: &str, x: f64, y: f64) {
+    pub fn print(&self, renderer: &dyn Renderer, text: &str, x: f64, y: f64) {
         let size = self.size as f64;
         for (index, ch) in text.char_indices() {
             let (sx, sy) = self
                 .map
                 .get(&ch)
                 .unwrap_or_else(|| panic!("Char '{}' not registered!", ch));
-            let sx = *sx as f64;
-            let sy = *sy as f64;
+            let source = Rectangle {
+                x: *sx as u32,
+                y: *sy as u32,
+                width: self.size,
+                height: self.size,
+            };
 
-            let dx = x + (index as f64 * size) as f64;
-            let dy = y;
-            context
-                .draw_image_with_html_image_element_and_sw_and_sh_and_dx_and_dy_and_dw_and_dh(
-                    &self.image.clone(),
-                    sx,
-                    sy,
-                    size,
-                    size,
-                    dx,
-                    dy,
-                    size,
-                    size,
-                )
-                .unwrap();
+            let dx = x + index as f64 * size;
+            renderer.blit_part(&self.image, &source, dx, y);
         }
     }
 }
@@ -1,15 +1,22 @@
 use core::cell::RefCell;
+use core::ops::RangeInclusive;
 use std::collections::HashMap;
+#[cfg(not(target_arch = "wasm32"))]
+use std::path::Path;
 use std::rc::Rc;
 
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use wasm_bindgen::prelude::*;
 
 use crate::assets::animations::AnimationName;
-use crate::assets::patterns::{load_patterns, PatternDefinition};
-use crate::assets::sprites::{Sprite, SpriteSheet};
+use crate::assets::config::MobsDefault;
+use crate::assets::patterns::PatternDefinition;
+#[cfg(not(target_arch = "wasm32"))]
+use crate::assets::read_json;
+use crate::assets::sprites::Sprite;
 use crate::assets::tiles::TilesDefinition;
 use crate::assets::{load_json, TILE_SIZE};
+use crate::camera::CameraZone;
 use crate::physics::bounding_box::BBox;
 use crate::physics::matrix::Matrix;
 use crate::physics::{Position, Size};
@@ -25,6 +32,34 @@ pub enum TileType {
     BrickBroken,
     #[serde(alias = "coin")]
     Coin,
+    #[serde(alias = "chance")]
+    Chance,
+}
+
+/// What a chance tile releases when hit from below
+#[derive(Deserialize, Copy, Clone, Debug, Default, Eq, PartialEq)]
+#[serde(rename_all = "kebab-case")]
+pub enum ChanceContent {
+    #[default]
+    Coin,
+    MultiCoin,
+    Mushroom,
+    FireFlower,
+    Star,
+    OneUp,
+}
+
+impl ChanceContent {
+    /// The released mob, coins are directly collected
+    pub fn mob(&self) -> Option<&str> {
+        match self {
+            ChanceContent::Coin | ChanceContent::MultiCoin => None,
+            ChanceContent::Mushroom => Some("mushroom"),
+            ChanceContent::FireFlower => Some("fire-flower"),
+            ChanceContent::Star => Some("star"),
+            ChanceContent::OneUp => Some("one-up"),
+        }
+    }
 }
 
 //
@@ -35,6 +70,8 @@ pub struct TileData {
     tile: Option<TileType>,
     animation: Option<AnimationName>,
     rectangle: BBox,
+    content: Option<ChanceContent>,
+    hits: u32,
 }
 
 impl TileData {
@@ -44,24 +81,34 @@ impl TileData {
         tile: Option<TileType>,
         animation: Option<AnimationName>,
         rectangle: BBox,
+        content: Option<ChanceContent>,
     ) -> Self {
+        let hits = 0;
         Self {
             sprite,
             position,
             tile,
             animation,
             rectangle,
+            content,
+            hits,
         }
     }
 
+    /// The same tile, no more animated, with another sprite
     pub fn replace_sprite(&self, sprite: Sprite) -> TileData {
-        TileData::new(
+        let animation = None;
+        TileData {
             sprite,
-            self.position,
-            self.tile,
-            self.animation,
-            self.rectangle,
-        )
+            animation,
+            ..*self
+        }
+    }
+
+    /// The same tile, hit once more from below
+    pub fn hit(&self) -> TileData {
+        let hits = self.hits + 1;
+        TileData { hits, ..*self }
     }
 
     pub fn sprite(&self) -> Sprite {
@@ -81,6 +128,12 @@ impl TileData {
     pub fn rectangle(&self) -> BBox {
         self.rectangle
     }
+    pub fn content(&self) -> ChanceContent {
+        self.content.unwrap_or_default()
+    }
+    pub fn hits(&self) -> u32 {
+        self.hits
+    }
 
     pub fn offset(&mut self, x: u32, y: u32) {
         let (x0, y0) = self.position;
@@ -93,30 +146,113 @@ impl TileData {
 
 type XYTileData = (u32, u32, TileData);
 
+/// A tile changed since the level creation, removed when there is no sprite
+#[derive(Serialize, Deserialize, Copy, Clone, Debug, PartialEq, Eq)]
+pub struct TileChange {
+    layer: usize,
+    x: usize,
+    y: usize,
+    sprite: Option<Sprite>,
+}
+
+impl TileChange {
+    pub fn layer(&self) -> usize {
+        self.layer
+    }
+
+    /// The same change on another layer
+    pub fn on_layer(self, layer: usize) -> Self {
+        Self { layer, ..self }
+    }
+}
+
 #[derive(Deserialize)]
 struct LayerDefinition {
+    scroll: Option<f64>,
+    repeat: Option<u32>,
     tiles: Vec<TilesDefinition>,
 }
 
-#[derive(Deserialize, Clone)]
+/// How a layer scrolls with the camera, a slower layer looks farther away.
+/// The tiles of a backdrop repeat every `repeat` columns
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Parallax {
+    scroll: f64,
+    repeat: Option<u32>,
+}
+
+impl Default for Parallax {
+    fn default() -> Self {
+        Self::new(1., None)
+    }
+}
+
+impl Parallax {
+    pub fn new(scroll: f64, repeat: Option<u32>) -> Self {
+        Self { scroll, repeat }
+    }
+
+    /// The position of the camera seen by the layer
+    pub fn camera(&self, position: (f64, f64)) -> (f64, f64) {
+        (position.0 * self.scroll, position.1 * self.scroll)
+    }
+
+    /// The columns of the window showing the column x of the layer
+    pub fn columns(&self, x: usize, window: &RangeInclusive<usize>) -> Vec<usize> {
+        match self.repeat {
+            Some(repeat) if x < repeat as usize => {
+                let repeat = repeat as usize;
+                let first = window.start() - window.start() % repeat + x;
+                (first..=*window.end())
+                    .step_by(repeat)
+                    .filter(|column| window.contains(column))
+                    .collect()
+            }
+            Some(_) => vec![],
+            None if window.contains(&x) => vec![x],
+            None => vec![],
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct EntityDefinition {
     name: String,
     pos: Position,
+    /// Where the mob was created, when it moved since, e.g. the start of a platform path
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    origin: Option<Position>,
 }
 
 impl EntityDefinition {
+    pub fn new(name: &str, pos: Position) -> Self {
+        let name = String::from(name);
+        let origin = None;
+        Self { name, pos, origin }
+    }
+
+    pub fn with_origin(self, origin: Position) -> Self {
+        let origin = Some(origin).filter(|origin| *origin != self.pos);
+        Self { origin, ..self }
+    }
+
     pub fn name(&self) -> &str {
         self.name.as_str()
     }
     pub fn position(&self) -> Position {
         self.pos
     }
+    pub fn origin(&self) -> Position {
+        self.origin.unwrap_or(self.pos)
+    }
 }
 
 #[derive(Deserialize, Copy, Clone, Debug)]
 pub enum TriggerKind {
     #[serde(alias = "goto")]
     Goto,
+    #[serde(alias = "pipe")]
+    Pipe,
 }
 
 #[derive(Deserialize, Clone, Debug)]
@@ -125,6 +261,8 @@ pub struct TriggerDefinition {
     #[serde(alias = "type")]
     kind: TriggerKind,
     pos: (u32, u32),
+    #[serde(default)]
+    spawn: Option<String>,
 }
 
 impl TriggerDefinition {
@@ -137,15 +275,56 @@ impl TriggerDefinition {
     pub fn position(&self) -> (u32, u32) {
         self.pos
     }
+    /// The name of the spawn point in the target level
+    pub fn spawn(&self) -> Option<String> {
+        self.spawn.clone()
+    }
+}
+
+#[derive(Deserialize, Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum SpawnKind {
+    #[default]
+    #[serde(alias = "start")]
+    Start,
+    #[serde(alias = "pipe")]
+    Pipe,
+    #[serde(alias = "checkpoint")]
+    Checkpoint,
+}
+
+/// A named place where the player appears, a pipe spawn is the top left corner of the pipe
+#[derive(Deserialize, Clone, Debug)]
+pub struct SpawnDefinition {
+    name: String,
+    #[serde(alias = "type", default)]
+    kind: SpawnKind,
+    pos: Position,
+}
+
+impl SpawnDefinition {
+    pub fn name(&self) -> &str {
+        self.name.as_str()
+    }
+    pub fn kind(&self) -> SpawnKind {
+        self.kind
+    }
+    pub fn position(&self) -> Position {
+        self.pos
+    }
 }
 
 pub struct LevelSpec {
     tiles: Vec<Rc<RefCell<Matrix<TileData>>>>,
+    original_tiles: Vec<Matrix<TileData>>,
+    parallax: Vec<Parallax>,
     music: String,
-    bg_sprites: Rc<SpriteSheet>,
+    sprite_sheet: String,
     gravity: Option<f64>,
     entities: Vec<EntityDefinition>,
     triggers: Vec<TriggerDefinition>,
+    spawns: Vec<SpawnDefinition>,
+    camera: Vec<CameraZone>,
+    next: Option<String>,
 }
 
 impl LevelSpec {
@@ -155,9 +334,112 @@ impl LevelSpec {
     pub fn tiles(&self) -> Vec<Rc<RefCell<Matrix<TileData>>>> {
         self.tiles.clone()
     }
-    pub fn bg_sprites(&self) -> Rc<SpriteSheet> {
-        self.bg_sprites.clone()
+    pub fn sprite_sheet(&self) -> &str {
+        self.sprite_sheet.as_str()
+    }
+    pub fn parallax(&self, layer: usize) -> Parallax {
+        self.parallax.get(layer).copied().unwrap_or_default()
+    }
+
+    /// The indices of the layers scrolling with the camera, the backdrops are left out
+    pub fn camera_layers(&self) -> Vec<usize> {
+        (0..self.tiles.len())
+            .filter(|layer| self.parallax(*layer) == Parallax::default())
+            .collect()
+    }
+
+    /// The ground tiles side by side from the column x on the row y, e.g. the top of a pipe
+    pub fn ground_span(&self, x: usize, y: usize) -> usize {
+        let layers = self.camera_layers();
+        (x..)
+            .take_while(|column| {
+                layers.iter().any(|layer| {
+                    self.tiles[*layer]
+                        .borrow()
+                        .get(*column, y)
+                        .is_some_and(|data| data.tile() == Some(TileType::Ground))
+                })
+            })
+            .count()
+    }
+
+    /// The mobs of the level and of its chance tiles are checked once loaded,
+    /// instead of failing when they first spawn
+    pub fn check(&self, mobs: &HashMap<String, MobsDefault>) -> Result<(), String> {
+        for entity in self.entities.iter() {
+            if !mobs.contains_key(entity.name()) {
+                return Err(format!("Unknown mob {}", entity.name()));
+            }
+        }
+        for tiles in self.original_tiles.iter() {
+            for (x, y, data) in tiles.iter() {
+                if let Some(mob) = data.content().mob() {
+                    if !mobs.contains_key(mob) {
+                        return Err(format!(
+                            "Unknown mob {} in the chance tile {},{}",
+                            mob, x, y
+                        ));
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// The tiles broken or collected since the level was built
+    pub fn tile_changes(&self) -> Vec<TileChange> {
+        let mut result = vec![];
+        for (layer, (tiles, original)) in self.tiles.iter().zip(&self.original_tiles).enumerate() {
+            let tiles = tiles.borrow();
+            let Size { width, height } = tiles.size();
+            for x in 0..width as usize {
+                for y in 0..height as usize {
+                    let sprite = tiles.get(x, y).map(|data| data.sprite());
+                    if sprite != original.get(x, y).map(|data| data.sprite()) {
+                        result.push(TileChange {
+                            layer,
+                            x,
+                            y,
+                            sprite,
+                        });
+                    }
+                }
+            }
+        }
+        result
+    }
+
+    /// True when the change is on a tile of the level
+    pub fn contains(&self, change: &TileChange) -> bool {
+        self.tiles.get(change.layer).is_some_and(|tiles| {
+            let Size { width, height } = tiles.borrow().size();
+            change.x < width as usize && change.y < height as usize
+        })
     }
+
+    /// Reset the tiles as built, then apply the changes
+    pub fn restore_tiles(&self, changes: &[TileChange]) {
+        for (tiles, original) in self.tiles.iter().zip(&self.original_tiles) {
+            tiles.borrow_mut().restore(original);
+        }
+        for change in changes {
+            if !self.contains(change) {
+                log(&format!("Cannot restore tile {:?}", change));
+                continue;
+            }
+            let original = self.original_tiles.get(change.layer);
+            let tile = original.and_then(|tiles| tiles.get(change.x, change.y));
+            let mut tiles = self.tiles[change.layer].borrow_mut();
+            match (change.sprite, tile) {
+                (Some(sprite), Some(tile)) => {
+                    tiles.set(change.x, change.y, tile.replace_sprite(sprite))
+                }
+                (None, _) => tiles.remove(change.x, change.y),
+                (Some(_), None) => log(&format!("Cannot restore tile {:?}", change)),
+            }
+        }
+    }
+
     pub fn gravity(&self) -> Option<f64> {
         self.gravity
     }
@@ -167,6 +449,28 @@ impl LevelSpec {
     pub fn triggers(&self) -> Vec<TriggerDefinition> {
         self.triggers.clone()
     }
+    /// The camera modes of the level, then of its regions
+    pub fn camera(&self) -> Vec<CameraZone> {
+        self.camera.clone()
+    }
+    /// The level after the castle
+    pub fn next(&self) -> Option<String> {
+        self.next.clone()
+    }
+    pub fn spawn(&self, name: &str) -> Option<SpawnDefinition> {
+        self.spawns
+            .iter()
+            .find(|spawn| spawn.name() == name)
+            .cloned()
+    }
+    /// The last checkpoint on the left of x
+    pub fn checkpoint(&self, x: f64) -> Option<SpawnDefinition> {
+        self.spawns
+            .iter()
+            .filter(|spawn| spawn.kind() == SpawnKind::Checkpoint && spawn.position().x() <= x)
+            .max_by(|a, b| a.position().x().total_cmp(&b.position().x()))
+            .cloned()
+    }
 }
 
 #[derive(Deserialize)]
@@ -182,24 +486,38 @@ pub struct LevelDefinition {
     layers: Vec<LayerDefinition>,
     entities: Vec<EntityDefinition>,
     triggers: Vec<TriggerDefinition>,
+    #[serde(default)]
+    spawns: Vec<SpawnDefinition>,
+    #[serde(default)]
+    camera: Vec<CameraZone>,
+    next: Option<String>,
 }
 
 impl LevelDefinition {
     pub async fn load(name: &str) -> Result<LevelDefinition, JsValue> {
         log(&format!("Loading level {}", name));
         let url = format!("assets/levels/{}.json", name);
-        let level = load_json(url.as_str())
-            .await?
-            .into_serde::<LevelDefinition>()
-            .expect("Error during level loading");
+        load_json(url.as_str()).await
+    }
 
-        Ok(level)
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn load_file(dir: &Path, name: &str) -> Result<LevelDefinition, String> {
+        let url = format!("assets/levels/{}.json", name);
+        read_json(dir, url.as_str())
     }
 
     pub fn music_sheet(&self) -> &str {
         self.music_sheet.as_ref()
     }
 
+    pub fn sprite_sheet(&self) -> &str {
+        self.sprite_sheet.as_ref()
+    }
+
+    pub fn pattern_sheet(&self) -> &str {
+        self.pattern_sheet.as_ref()
+    }
+
     pub fn entities(&self) -> &[EntityDefinition] {
         self.entities.as_slice()
     }
@@ -284,32 +602,105 @@ impl LevelDefinition {
         matrix
     }
 
-    pub async fn build(&self) -> Result<LevelSpec, JsValue> {
-        let patterns = load_patterns(self.pattern_sheet.as_str()).await?;
-        let all_tile_data = self.compute_tiles_data(&patterns);
+    pub fn build(&self, patterns: &HashMap<String, PatternDefinition>) -> LevelSpec {
+        let all_tile_data = self.compute_tiles_data(patterns);
 
         let size = self.compute_size(&all_tile_data);
         let mut tiles = vec![];
+        let mut original_tiles = vec![];
         for v in all_tile_data {
             let background_matrix = self.create_background_matrix(size, v.to_vec());
+            original_tiles.push(background_matrix.clone());
             tiles.push(Rc::new(RefCell::new(background_matrix)));
         }
 
+        let parallax = self
+            .layers
+            .iter()
+            .map(|layer| Parallax::new(layer.scroll.unwrap_or(1.), layer.repeat))
+            .collect();
         let music = self.music_sheet.clone();
         let entities = self.entities.clone();
-        let sprite_sheet = SpriteSheet::load(self.sprite_sheet.as_str()).await?;
-        let bg_sprites = Rc::new(sprite_sheet);
+        let sprite_sheet = self.sprite_sheet.clone();
         let gravity = self.gravity;
         let triggers = self.triggers.clone();
+        let spawns = self.spawns.clone();
+        let camera = self.camera.clone();
+        let next = self.next.clone();
 
-        let result = LevelSpec {
+        LevelSpec {
             music,
             tiles,
-            bg_sprites,
+            original_tiles,
+            parallax,
+            sprite_sheet,
             gravity,
             triggers,
             entities,
-        };
-        Ok(result)
+            spawns,
+            camera,
+            next,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::assets::config::Configuration;
+
+    fn level_spec(chance: &str, entities: &str) -> LevelSpec {
+        let json = format!(
+            r#"{{
+                "spriteSheet": "overworld",
+                "musicSheet": "overworld",
+                "patternSheet": "overworld-pattern",
+                "layers": [{{ "tiles": [
+                    {{ "name": "ground", "type": "ground", "ranges": [[0, 32, 13, 2]] }},
+                    {{ "name": "chance", "type": "chance", "content": "{chance}", "ranges": [[4, 1, 9, 1]] }}
+                ] }}],
+                "entities": [{entities}],
+                "triggers": []
+            }}"#
+        );
+        let definition: LevelDefinition = serde_json::from_str(&json).unwrap();
+        definition.build(&HashMap::new())
+    }
+
+    #[test]
+    fn should_check_level_mobs() {
+        let mut config: Configuration =
+            serde_json::from_str(include_str!("../../www/assets/config.json")).unwrap();
+        let goomba = r#"{ "name": "goomba", "pos": [64, 64] }"#;
+        assert_eq!(level_spec("star", goomba).check(&config.mobs), Ok(()));
+
+        let bowser = r#"{ "name": "bowser", "pos": [64, 64] }"#;
+        assert_eq!(
+            level_spec("coin", bowser).check(&config.mobs),
+            Err(String::from("Unknown mob bowser"))
+        );
+
+        config.mobs.remove("star");
+        assert_eq!(
+            level_spec("star", goomba).check(&config.mobs),
+            Err(String::from("Unknown mob star in the chance tile 4,9"))
+        );
+    }
+
+    #[test]
+    fn should_scroll_and_repeat_backdrop() {
+        let parallax = Parallax::new(0.5, Some(48));
+        assert_eq!(parallax.camera((320., 64.)), (160., 32.));
+
+        assert_eq!(parallax.columns(2, &(0..=16)), vec![2]);
+        assert_eq!(parallax.columns(2, &(40..=56)), vec![50]);
+        assert_eq!(parallax.columns(44, &(40..=140)), vec![44, 92, 140]);
+        assert!(parallax.columns(30, &(40..=56)).is_empty());
+        // Beyond the repeated width, the tiles are not drawn
+        assert!(parallax.columns(60, &(40..=70)).is_empty());
+
+        let parallax = Parallax::default();
+        assert_eq!(parallax.camera((320., 64.)), (320., 64.));
+        assert_eq!(parallax.columns(60, &(40..=70)), vec![60]);
     }
 }
@@ -1,19 +1,32 @@
 use std::collections::HashMap;
+#[cfg(not(target_arch = "wasm32"))]
+use std::fs;
+#[cfg(not(target_arch = "wasm32"))]
+use std::path::Path;
 use std::rc::Rc;
 
 use js_sys::Promise;
+use serde::de::DeserializeOwned;
 use wasm_bindgen::prelude::*;
 use wasm_bindgen::{JsCast, JsValue};
 use wasm_bindgen_futures::JsFuture;
-use web_sys::{HtmlImageElement, Request, Response};
+use web_sys::{AudioContext, HtmlImageElement, Request, Response};
 
-use crate::assets::audio::musics::MusicPlayer;
-use crate::assets::audio::sounds::AudioBoard;
+use crate::assets::audio::musics::HtmlMusicPlayer;
+use crate::assets::audio::sounds::WebAudioBoard;
+use crate::assets::audio::{AudioBoard, MusicPlayer, Silence};
 use crate::assets::config::Configuration;
 use crate::assets::font::Font;
 use crate::assets::levels::{LevelDefinition, LevelSpec};
+use crate::assets::patterns::load_patterns;
+#[cfg(not(target_arch = "wasm32"))]
+use crate::assets::patterns::load_patterns_file;
 use crate::assets::sprites::SpriteSheet;
 use crate::utils::window;
+#[cfg(not(target_arch = "wasm32"))]
+use crate::video::framebuffer::Framebuffer;
+#[cfg(not(target_arch = "wasm32"))]
+use crate::video::Bitmap;
 
 pub mod animations;
 pub mod audio;
@@ -26,6 +39,19 @@ pub mod tiles;
 
 pub const TILE_SIZE: u32 = 16;
 
+const LEVELS: [&str; 2] = ["1-1", "1-2"];
+const SPRITES: [&str; 8] = [
+    "mario",
+    "luigi",
+    "bullet",
+    "cannon",
+    "goomba",
+    "koopa",
+    "items",
+    "particles",
+];
+const MUSICS: [&str; 3] = ["overworld", "underworld", "silent"];
+
 #[wasm_bindgen]
 extern "C" {
     #[wasm_bindgen(js_namespace = loader)]
@@ -41,12 +67,40 @@ async fn load_image(path: &str) -> Result<Rc<HtmlImageElement>, JsValue> {
     Ok(image)
 }
 
-async fn load_json(url: &str) -> Result<JsValue, JsValue> {
+async fn load_json<T: DeserializeOwned>(url: &str) -> Result<T, JsValue> {
     let request = Request::new_with_str(url)?;
     let resp_value = JsFuture::from(window().fetch_with_request(&request)).await?;
     let resp: Response = resp_value.dyn_into().unwrap();
+    let text = JsFuture::from(resp.text()?).await?.as_string().unwrap();
+
+    serde_json::from_str(text.as_str())
+        .map_err(|err| JsValue::from_str(&format!("Error during loading of {}: {}", url, err)))
+}
+
+/// Read a JSON asset from the `www` directory, without any browser
+#[cfg(not(target_arch = "wasm32"))]
+fn read_json<T: DeserializeOwned>(dir: &Path, url: &str) -> Result<T, String> {
+    let text = fs::read_to_string(dir.join(url))
+        .map_err(|err| format!("Error during loading of {}: {}", url, err))?;
+
+    serde_json::from_str(text.as_str())
+        .map_err(|err| format!("Error during loading of {}: {}", url, err))
+}
+
+/// Read a PNG or GIF image from the `www` directory into a framebuffer bitmap
+#[cfg(not(target_arch = "wasm32"))]
+fn read_image(dir: &Path, url: &str) -> Result<Bitmap, String> {
+    let bytes = fs::read(dir.join(url))
+        .map_err(|err| format!("Error during loading of {}: {}", url, err))?;
+    let framebuffer = if url.ends_with(".gif") {
+        Framebuffer::decode_gif(bytes.as_slice())
+    } else {
+        Framebuffer::decode_png(bytes.as_slice())
+    };
 
-    JsFuture::from(resp.json()?).await
+    framebuffer
+        .map(Bitmap::from_pixels)
+        .map_err(|err| format!("Error during loading of {}: {}", url, err))
 }
 
 /// I choose to have
@@ -55,53 +109,64 @@ pub struct Assets {
     configuration: Configuration,
     levels: HashMap<String, Rc<LevelSpec>>,
     spite_sheets: HashMap<String, Rc<SpriteSheet>>,
-    music_players: HashMap<String, Rc<MusicPlayer>>,
-    audio_boards: HashMap<String, Rc<AudioBoard>>,
-    font: Rc<Font>,
+    music_players: HashMap<String, Rc<dyn MusicPlayer>>,
+    audio_boards: HashMap<String, Rc<dyn AudioBoard>>,
+    font: Option<Rc<Font>>,
 }
 
 impl Assets {
     pub async fn load() -> Result<Assets, JsValue> {
-        let loading_levels = vec!["1-1", "1-2"];
-        let loading_sprites = vec!["mario", "luigi", "bullet", "cannon", "goomba", "koopa"];
-        let loading_musics = vec!["overworld", "underworld", "silent"];
-
         // Configuration
         let configuration = Configuration::load().await?;
 
         // Levels
         let mut levels = HashMap::new();
-        for &level_name in loading_levels.iter() {
+        let mut spite_sheets = HashMap::new();
+        for level_name in LEVELS {
             let level_def = LevelDefinition::load(level_name).await?;
-            let level = level_def.build().await?;
+            let patterns = load_patterns(level_def.pattern_sheet()).await?;
+            let level = level_def.build(&patterns);
+            level.check(&configuration.mobs).map_err(|err| {
+                JsValue::from_str(&format!("Error in level {}: {}", level_name, err))
+            })?;
+
+            let sheet = level.sprite_sheet();
+            if !spite_sheets.contains_key(sheet) {
+                let spite_sheet = SpriteSheet::load(sheet).await?;
+                spite_sheets.insert(String::from(sheet), Rc::new(spite_sheet));
+            }
             levels.insert(String::from(level_name), Rc::new(level));
         }
 
         // Sprites
-        let mut spite_sheets = HashMap::new();
-        for &sheet in loading_sprites.iter() {
+        for sheet in SPRITES {
             let spite_sheet = SpriteSheet::load(sheet).await?;
             spite_sheets.insert(String::from(sheet), Rc::new(spite_sheet));
         }
 
         // Music
-        let mut music_players = HashMap::new();
-        for &music in loading_musics.iter() {
-            let music_player = MusicPlayer::load_music(music, configuration.sounds.music).await?;
+        let mut music_players: HashMap<String, Rc<dyn MusicPlayer>> = HashMap::new();
+        for music in MUSICS {
+            let music_player =
+                HtmlMusicPlayer::load_music(music, configuration.sounds.music).await?;
             music_players.insert(String::from(music), Rc::new(music_player));
         }
 
         // Audio
-        let mut audio_boards = HashMap::new();
-        for &sheet in loading_sprites.iter() {
-            if let Ok(audio) = AudioBoard::load_sounds(sheet, configuration.sounds.fx).await {
+        let audio_context = Rc::new(AudioContext::new()?);
+        let mut audio_boards: HashMap<String, Rc<dyn AudioBoard>> = HashMap::new();
+        for sheet in SPRITES {
+            let volume = configuration.sounds.fx;
+            if let Ok(audio) =
+                WebAudioBoard::load_sounds(sheet, audio_context.clone(), volume).await
+            {
                 audio_boards.insert(String::from(sheet), Rc::new(audio));
             }
         }
 
         // Font
         let font = Font::load().await?;
-        let font = Rc::new(font);
+        let font = Some(Rc::new(font));
 
         let result = Self {
             configuration,
@@ -114,15 +179,90 @@ impl Assets {
         Ok(result)
     }
 
+    /// Load the assets from the `www` directory, with framebuffer images and without sounds
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn load_dir(dir: &Path) -> Result<Assets, String> {
+        // Configuration
+        let configuration = Configuration::load_file(dir)?;
+
+        // Levels
+        let mut levels = HashMap::new();
+        let mut spite_sheets = HashMap::new();
+        for level_name in LEVELS {
+            let level_def = LevelDefinition::load_file(dir, level_name)?;
+            let patterns = load_patterns_file(dir, level_def.pattern_sheet())?;
+            let level = level_def.build(&patterns);
+            level
+                .check(&configuration.mobs)
+                .map_err(|err| format!("Error in level {}: {}", level_name, err))?;
+
+            let sheet = level.sprite_sheet();
+            if !spite_sheets.contains_key(sheet) {
+                let spite_sheet = SpriteSheet::load_file(dir, sheet)?;
+                spite_sheets.insert(String::from(sheet), Rc::new(spite_sheet));
+            }
+            levels.insert(String::from(level_name), Rc::new(level));
+        }
+
+        // Sprites
+        for sheet in SPRITES {
+            let spite_sheet = SpriteSheet::load_file(dir, sheet)?;
+            spite_sheets.insert(String::from(sheet), Rc::new(spite_sheet));
+        }
+
+        // Music
+        let mut music_players: HashMap<String, Rc<dyn MusicPlayer>> = HashMap::new();
+        for music in MUSICS {
+            music_players.insert(String::from(music), Rc::new(Silence));
+        }
+
+        // Font
+        let font = Font::load_file(dir)?;
+        let font = Some(Rc::new(font));
+
+        let result = Self {
+            configuration,
+            levels,
+            spite_sheets,
+            audio_boards: HashMap::new(),
+            music_players,
+            font,
+        };
+        Ok(result)
+    }
+
+    /// Assets without images nor sounds, enough to run a level simulation
+    pub fn headless(configuration: Configuration, levels: HashMap<String, LevelSpec>) -> Assets {
+        let mut music_players: HashMap<String, Rc<dyn MusicPlayer>> = HashMap::new();
+        for level in levels.values() {
+            music_players.insert(String::from(level.music()), Rc::new(Silence));
+        }
+        let levels = levels
+            .into_iter()
+            .map(|(name, level)| (name, Rc::new(level)))
+            .collect();
+
+        Self {
+            configuration,
+            levels,
+            spite_sheets: HashMap::new(),
+            music_players,
+            audio_boards: HashMap::new(),
+            font: None,
+        }
+    }
+
     pub fn configuration(&self) -> Configuration {
         self.configuration.clone()
     }
 
     pub fn level(&self, name: &str) -> Rc<LevelSpec> {
-        self.levels
-            .get(name)
+        self.find_level(name)
             .unwrap_or_else(|| panic!("Level {} not found!", name))
-            .clone()
+    }
+
+    pub fn find_level(&self, name: &str) -> Option<Rc<LevelSpec>> {
+        self.levels.get(name).cloned()
     }
 
     pub fn spite_sheet(&self, name: &str) -> Rc<SpriteSheet> {
@@ -132,11 +272,11 @@ impl Assets {
             .clone()
     }
 
-    pub fn audio_board(&self, name: &str) -> Option<Rc<AudioBoard>> {
+    pub fn audio_board(&self, name: &str) -> Option<Rc<dyn AudioBoard>> {
         self.audio_boards.get(name).cloned()
     }
 
-    pub fn music_player(&self, name: &str) -> Rc<MusicPlayer> {
+    pub fn music_player(&self, name: &str) -> Rc<dyn MusicPlayer> {
         self.music_players
             .get(name)
             .unwrap_or_else(|| panic!("MusicSheet {} not found!", name))
@@ -144,6 +284,8 @@ impl Assets {
     }
 
     pub fn font(&self) -> Rc<Font> {
-        self.font.clone()
+        self.font
+            .clone()
+            .unwrap_or_else(|| panic!("Font not found!"))
     }
 }
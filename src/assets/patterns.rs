@@ -1,10 +1,14 @@
 use std::collections::HashMap;
+#[cfg(not(target_arch = "wasm32"))]
+use std::path::Path;
 use std::slice::Iter;
 
 use serde::Deserialize;
 use wasm_bindgen::JsValue;
 
 use crate::assets::load_json;
+#[cfg(not(target_arch = "wasm32"))]
+use crate::assets::read_json;
 use crate::assets::tiles::TilesDefinition;
 use crate::utils::log;
 
@@ -22,10 +26,14 @@ impl PatternDefinition {
 pub async fn load_patterns(name: &str) -> Result<HashMap<String, PatternDefinition>, JsValue> {
     log(&format!("Loading patterns {}", name));
     let url = format!("assets/sprites/patterns/{}.json", name);
-    let patterns = load_json(url.as_str())
-        .await?
-        .into_serde::<HashMap<String, PatternDefinition>>()
-        .expect("Error during patter loading");
+    load_json(url.as_str()).await
+}
 
-    Ok(patterns)
+#[cfg(not(target_arch = "wasm32"))]
+pub fn load_patterns_file(
+    dir: &Path,
+    name: &str,
+) -> Result<HashMap<String, PatternDefinition>, String> {
+    let url = format!("assets/sprites/patterns/{}.json", name);
+    read_json(dir, url.as_str())
 }
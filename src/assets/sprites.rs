@@ -1,16 +1,19 @@
 use std::collections::HashMap;
-use std::rc::Rc;
+#[cfg(not(target_arch = "wasm32"))]
+use std::path::Path;
 
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use wasm_bindgen::JsValue;
-use web_sys::{CanvasRenderingContext2d, HtmlCanvasElement, HtmlImageElement};
 
 use crate::assets::animations::{Animation, AnimationDefinition, AnimationName};
 use crate::assets::{load_image, load_json, TILE_SIZE};
+#[cfg(not(target_arch = "wasm32"))]
+use crate::assets::{read_image, read_json};
 use crate::physics::{Direction, Size};
-use crate::utils::{create_image_buffer, log};
+use crate::utils::log;
+use crate::video::{Bitmap, Renderer};
 
-#[derive(Deserialize, Hash, Clone, Copy, Debug, PartialEq, Eq)]
+#[derive(Serialize, Deserialize, Hash, Clone, Copy, Debug, PartialEq, Eq)]
 pub enum Sprite {
     // Mario
     #[serde(alias = "idle")]
@@ -27,6 +30,30 @@ pub enum Sprite {
     Dead,
     #[serde(alias = "jump")]
     Jump,
+    #[serde(alias = "big-idle")]
+    BigIdle,
+    #[serde(alias = "big-run-1")]
+    BigRun1,
+    #[serde(alias = "big-run-2")]
+    BigRun2,
+    #[serde(alias = "big-run-3")]
+    BigRun3,
+    #[serde(alias = "big-break")]
+    BigBreak,
+    #[serde(alias = "big-jump")]
+    BigJump,
+    #[serde(alias = "fire-idle")]
+    FireIdle,
+    #[serde(alias = "fire-run-1")]
+    FireRun1,
+    #[serde(alias = "fire-run-2")]
+    FireRun2,
+    #[serde(alias = "fire-run-3")]
+    FireRun3,
+    #[serde(alias = "fire-break")]
+    FireBreak,
+    #[serde(alias = "fire-jump")]
+    FireJump,
     // Goomba, Koopa
     #[serde(alias = "walk-1")]
     Walk1,
@@ -64,6 +91,27 @@ pub enum Sprite {
     PipeLeft,
     #[serde(alias = "pipe-vert-right")]
     PipeRight,
+    // Flagpole & castle
+    #[serde(alias = "flagpole-ball")]
+    FlagpoleBall,
+    #[serde(alias = "flagpole-pole")]
+    FlagpolePole,
+    #[serde(alias = "flag")]
+    Flag,
+    #[serde(alias = "castle-top")]
+    CastleTop,
+    #[serde(alias = "castle-top-filled")]
+    CastleTopFilled,
+    #[serde(alias = "castle-window-left")]
+    CastleWindowLeft,
+    #[serde(alias = "castle-brick")]
+    CastleBrick,
+    #[serde(alias = "castle-window-right")]
+    CastleWindowRight,
+    #[serde(alias = "castle-door-top")]
+    CastleDoorTop,
+    #[serde(alias = "castle-door")]
+    CastleDoor,
     // Cloud
     #[serde(alias = "cloud-1-1")]
     Cloud11,
@@ -84,6 +132,8 @@ pub enum Sprite {
     Chance2,
     #[serde(alias = "chance-3")]
     Chance3,
+    #[serde(alias = "chance-spent")]
+    ChanceSpent,
     // Coin
     #[serde(alias = "coin-1")]
     Coin1,
@@ -100,6 +150,32 @@ pub enum Sprite {
     Cannon3,
     #[serde(alias = "bullet")]
     Bullet,
+    // Items
+    #[serde(alias = "mushroom")]
+    Mushroom,
+    #[serde(alias = "one-up")]
+    OneUp,
+    #[serde(alias = "fire-flower")]
+    FireFlower,
+    #[serde(alias = "star")]
+    Star,
+    #[serde(alias = "fireball")]
+    Fireball,
+    // Particles
+    #[serde(alias = "debris-1")]
+    Debris1,
+    #[serde(alias = "debris-2")]
+    Debris2,
+    #[serde(alias = "sparkle-1")]
+    Sparkle1,
+    #[serde(alias = "sparkle-2")]
+    Sparkle2,
+    #[serde(alias = "sparkle-3")]
+    Sparkle3,
+    #[serde(alias = "puff-1")]
+    Puff1,
+    #[serde(alias = "puff-2")]
+    Puff2,
 }
 
 #[derive(Deserialize, Clone, Copy, Debug)]
@@ -163,21 +239,16 @@ impl SpriteSheetDefinition {
         log(&format!("Loading sprite sheet '{}'", name));
 
         let url = format!("assets/sprites/{}.json", name);
-        let result = load_json(url.as_str())
-            .await?
-            .into_serde::<SpriteSheetDefinition>()
-            .expect("Error during sprites loading");
-
-        Ok(result)
+        load_json(url.as_str()).await
     }
 }
 
 // SpriteSheet
 pub struct SpriteSheet {
     name: String,
-    image: Rc<HtmlImageElement>,
+    image: Bitmap,
     tile_size: Size,
-    sprites: HashMap<Sprite, HtmlCanvasElement>,
+    sprites: HashMap<Sprite, Bitmap>,
     sprites_size: HashMap<Sprite, Size>,
     animations: HashMap<AnimationName, Animation>,
 }
@@ -185,14 +256,27 @@ pub struct SpriteSheet {
 impl SpriteSheet {
     pub async fn load(name: &str) -> Result<SpriteSheet, JsValue> {
         let definition = SpriteSheetDefinition::load(name).await?;
+        let image = load_image(&definition.image_url).await?;
+        let image = Bitmap::from_image(&image);
+
+        Ok(SpriteSheet::build(name, &definition, image))
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn load_file(dir: &Path, name: &str) -> Result<SpriteSheet, String> {
+        let url = format!("assets/sprites/{}.json", name);
+        let definition: SpriteSheetDefinition = read_json(dir, url.as_str())?;
+        let image = read_image(dir, &definition.image_url)?;
 
+        Ok(SpriteSheet::build(name, &definition, image))
+    }
+
+    fn build(name: &str, definition: &SpriteSheetDefinition, image: Bitmap) -> SpriteSheet {
         let name = String::from(name);
         let tile_width = definition.tile_width.unwrap_or(TILE_SIZE);
         let tile_height = definition.tile_height.unwrap_or(TILE_SIZE);
         let tile_size = Size::new(tile_width, tile_height);
 
-        let image = load_image(&definition.image_url).await?;
-
         let mut result = SpriteSheet::new(name, image.clone(), tile_size);
 
         // Tiles
@@ -202,6 +286,11 @@ impl SpriteSheet {
             result.define(tile_def.name, &rect);
         }
 
+        // Frames
+        for frame_def in definition.frames.iter() {
+            result.define(frame_def.name, &frame_def.rect);
+        }
+
         // Animations
         for animation_def in definition.animations.iter() {
             let mut animation =
@@ -227,16 +316,15 @@ impl SpriteSheet {
             for &d in [Direction::Left, Direction::Stop, Direction::Right].iter() {
                 for frame_def in definition.frames.iter() {
                     animation.define(frame_def.name, d, &frame_def.rect);
-                    result.define(frame_def.name, &frame_def.rect);
                 }
             }
             result.animations.insert(animation_def.name(), animation);
         }
 
-        Ok(result)
+        result
     }
 
-    fn new(name: String, image: Rc<HtmlImageElement>, tile_size: Size) -> Self {
+    fn new(name: String, image: Bitmap, tile_size: Size) -> Self {
         let sprites = HashMap::default();
         let sprites_size = HashMap::default();
         let animations = HashMap::new();
@@ -251,14 +339,14 @@ impl SpriteSheet {
     }
 
     fn define(&mut self, frame: Sprite, rect: &Rectangle) {
-        let buffer = create_image_buffer(self.image.clone(), rect, false);
+        let buffer = self.image.crop(rect, false);
         self.sprites.insert(frame, buffer);
         self.sprites_size.insert(frame, rect.size());
     }
 
     pub fn draw_tile_animation(
         &self,
-        context: &CanvasRenderingContext2d,
+        renderer: &dyn Renderer,
         animation: AnimationName,
         x: f64,
         y: f64,
@@ -270,12 +358,12 @@ impl SpriteSheet {
             .get(&animation)
             .unwrap_or_else(|| panic!("[{}] Animation {:?} not found!", self.name, animation));
         let frame = anim.frame(distance);
-        anim.draw_frame(context, x, y, frame, direction);
+        anim.draw_frame(renderer, x, y, frame, direction);
     }
 
     pub fn draw_tile_animation_fixed(
         &self,
-        context: &CanvasRenderingContext2d,
+        renderer: &dyn Renderer,
         animation: AnimationName,
         frame: Sprite,
         x: f64,
@@ -286,17 +374,15 @@ impl SpriteSheet {
             .animations
             .get(&animation)
             .unwrap_or_else(|| panic!("[{}] Animation {:?} not found!", self.name, animation));
-        anim.draw_frame(context, x, y, frame, direction);
+        anim.draw_frame(renderer, x, y, frame, direction);
     }
 
-    fn draw_image(&self, context: &CanvasRenderingContext2d, sprite: Sprite, x: f64, y: f64) {
+    pub fn draw_image(&self, renderer: &dyn Renderer, sprite: Sprite, x: f64, y: f64) {
         let buffer = self
             .sprites
             .get(&sprite)
             .unwrap_or_else(|| panic!("[{}] Sprite {:?} not found!", self.name, sprite));
-        context
-            .draw_image_with_html_canvas_element(buffer, x.floor(), y.floor())
-            .unwrap();
+        renderer.blit(buffer, x, y);
     }
 
     pub fn size(&self, sprite: Sprite) -> Size {
@@ -310,10 +396,10 @@ impl SpriteSheet {
         self.tile_size
     }
 
-    pub fn draw_tile(&self, context: &CanvasRenderingContext2d, sprite: Sprite, x: f64, y: f64) {
+    pub fn draw_tile(&self, renderer: &dyn Renderer, sprite: Sprite, x: f64, y: f64) {
         let Size { width, height } = self.tile_size;
         let x = x * width as f64;
         let y = y * height as f64;
-        self.draw_image(context, sprite, x, y);
+        self.draw_image(renderer, sprite, x, y);
     }
 }
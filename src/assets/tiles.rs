@@ -1,7 +1,7 @@
 use serde::Deserialize;
 
 use crate::assets::animations::AnimationName;
-use crate::assets::levels::{TileData, TileType};
+use crate::assets::levels::{ChanceContent, TileData, TileType};
 use crate::assets::sprites::Sprite;
 use crate::physics::bounding_box::BBox;
 use crate::physics::Size;
@@ -63,6 +63,7 @@ pub struct TilesDefinition {
     #[serde(alias = "type")]
     kind: Option<TileType>,
     animation: Option<AnimationName>,
+    content: Option<ChanceContent>,
     ranges: Vec<Vec<u32>>,
 }
 
@@ -88,6 +89,13 @@ impl TilesDefinition {
         let left = (x * tile_size.width) as f64;
         let top = (y * tile_size.height) as f64;
         let rectangle = BBox::new(top, left, tile_size);
-        TileData::new(sprite, (x, y), self.kind, self.animation, rectangle)
+        TileData::new(
+            sprite,
+            (x, y),
+            self.kind,
+            self.animation,
+            rectangle,
+            self.content,
+        )
     }
 }
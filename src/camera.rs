@@ -1,8 +1,74 @@
 use core::cell::RefCell;
 use std::rc::Rc;
 
+use serde::Deserialize;
+
+use crate::assets::TILE_SIZE;
+use crate::physics::bounding_box::BBox;
 use crate::physics::{Position, Size};
 
+/// Space between the left of the view and the leading player
+const LEAD: f64 = (TILE_SIZE * 6) as f64;
+/// Space behind the last player
+const MARGIN: f64 = TILE_SIZE as f64;
+/// The camera does not move vertically while the players are
+/// between the top and the bottom of the dead zone
+const DEAD_ZONE_TOP: f64 = (TILE_SIZE * 4) as f64;
+const DEAD_ZONE_BOTTOM: f64 = (TILE_SIZE * 5) as f64;
+
+/// How the camera moves with the players
+#[derive(Deserialize, Clone, Copy, Debug, Default, PartialEq)]
+#[serde(tag = "mode", rename_all = "kebab-case")]
+pub enum CameraMode {
+    /// Follow the players in both directions
+    #[default]
+    Follow,
+    /// Never scroll back, the players cannot walk back off screen
+    Forward,
+    /// Follow the players without leaving the zone
+    Lock,
+    /// Scroll at the speed, in pixels per second, whatever the players do
+    AutoScroll { speed: f64 },
+}
+
+impl CameraMode {
+    /// The players cannot leave the view
+    pub fn holds_players(&self) -> bool {
+        *self != CameraMode::Follow
+    }
+}
+
+/// The mode of the camera in a rectangle of the level, the whole level without one
+#[derive(Deserialize, Clone, Copy, Debug)]
+pub struct CameraZone {
+    #[serde(flatten)]
+    mode: CameraMode,
+    pos: Option<Position>,
+    size: Option<Size>,
+}
+
+impl CameraZone {
+    pub fn mode(&self) -> CameraMode {
+        self.mode
+    }
+
+    pub fn area(&self) -> Option<BBox> {
+        match (self.pos, self.size) {
+            (Some(pos), Some(size)) => Some(BBox::new(pos.x(), pos.y(), size)),
+            _ => None,
+        }
+    }
+
+    /// The last zone containing the point, the zones of the level come first
+    pub fn find(zones: &[CameraZone], x: f64, y: f64) -> Option<CameraZone> {
+        zones
+            .iter()
+            .rev()
+            .find(|zone| zone.area().is_none_or(|area| area.contains(x, y)))
+            .copied()
+    }
+}
+
 pub struct Camera {
     position: Rc<RefCell<Position>>,
     size: Size,
@@ -18,6 +84,10 @@ impl Camera {
         self.size.width
     }
 
+    pub fn height(&self) -> u32 {
+        self.size.height
+    }
+
     pub fn position(&self) -> (f64, f64) {
         (self.position.borrow().x(), self.position.borrow().y())
     }
@@ -26,7 +96,201 @@ impl Camera {
         self.position.borrow_mut().set_x(x);
     }
 
-    // pub fn set_y(&mut self, y: f64) {
-    //     self.position.borrow_mut().set_y(y);
-    // }
+    pub fn set_y(&mut self, y: f64) {
+        self.position.borrow_mut().set_y(y);
+    }
+
+    /// Follow the leading player, without leaving the last one behind,
+    /// the camera stays between 0 and max_x
+    pub fn follow(&mut self, players: &[f64], max_x: f64) {
+        let first = players.iter().copied().reduce(f64::min);
+        let last = players.iter().copied().reduce(f64::max);
+        if let (Some(first), Some(last)) = (first, last) {
+            let x = (last - LEAD).min(first - MARGIN);
+            self.set_x(x.max(0.).min(max_x));
+        }
+    }
+
+    /// Move with the players, by the mode of the zone of the leading player.
+    /// The camera stays between 0 and max, the mode used is returned
+    pub fn focus(
+        &mut self,
+        zones: &[CameraZone],
+        players: &[(f64, f64)],
+        max: (f64, f64),
+        dt: f64,
+    ) -> CameraMode {
+        let Some(leader) = players
+            .iter()
+            .copied()
+            .reduce(|a, b| if b.0 > a.0 { b } else { a })
+        else {
+            return CameraMode::default();
+        };
+        let zone = CameraZone::find(zones, leader.0, leader.1);
+        let mode = zone.map(|zone| zone.mode()).unwrap_or_default();
+        let (max_x, max_y) = max;
+        let xs: Vec<f64> = players.iter().map(|(x, _)| *x).collect();
+        let ys: Vec<f64> = players.iter().map(|(_, y)| *y).collect();
+
+        let (previous, _) = self.position();
+        match mode {
+            CameraMode::Follow | CameraMode::Lock => self.follow(&xs, max_x),
+            CameraMode::Forward => {
+                self.follow(&xs, max_x);
+                let (x, _) = self.position();
+                self.set_x(x.max(previous));
+            }
+            CameraMode::AutoScroll { speed } => {
+                self.set_x((previous + speed * dt).max(0.).min(max_x));
+            }
+        }
+        self.follow_y(&ys, max_y);
+
+        if let (CameraMode::Lock, Some(area)) = (mode, zone.and_then(|zone| zone.area())) {
+            let (x, y) = self.position();
+            let right = area.right() - self.size.width as f64;
+            let bottom = area.bottom() - self.size.height as f64;
+            self.set_x(x.min(right).max(area.left()));
+            self.set_y(y.min(bottom).max(area.top()));
+        }
+        mode
+    }
+
+    /// Scroll when a player leaves the dead zone, the highest one wins,
+    /// the camera stays between 0 and max_y
+    pub fn follow_y(&mut self, players: &[f64], max_y: f64) {
+        let highest = players.iter().copied().reduce(f64::min);
+        let lowest = players.iter().copied().reduce(f64::max);
+        if let (Some(highest), Some(lowest)) = (highest, lowest) {
+            let (_, mut y) = self.position();
+            let bottom = self.size.height as f64 - DEAD_ZONE_BOTTOM;
+            if lowest > y + bottom {
+                y = lowest - bottom;
+            }
+            if highest < y + DEAD_ZONE_TOP {
+                y = highest - DEAD_ZONE_TOP;
+            }
+            self.set_y(y.max(0.).min(max_y));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn camera() -> Camera {
+        Camera::new(Size::new(256, 240))
+    }
+
+    #[test]
+    fn should_follow_single_player() {
+        let mut camera = camera();
+        camera.follow(&[40.], 1000.);
+        assert_eq!(camera.position(), (0., 0.));
+
+        camera.follow(&[500.], 1000.);
+        assert_eq!(camera.position(), (500. - LEAD, 0.));
+
+        camera.follow(&[2000.], 1000.);
+        assert_eq!(camera.position(), (1000., 0.));
+    }
+
+    #[test]
+    fn should_keep_players_in_view() {
+        let mut camera = camera();
+        camera.follow(&[500., 520.], 1000.);
+        assert_eq!(camera.position(), (520. - LEAD, 0.));
+
+        // The last player holds the camera back
+        camera.follow(&[500., 700.], 1000.);
+        assert_eq!(camera.position(), (500. - MARGIN, 0.));
+    }
+
+    fn zones(json: &str) -> Vec<CameraZone> {
+        serde_json::from_str(json).unwrap()
+    }
+
+    #[test]
+    fn should_find_zone() {
+        let zones = zones(
+            r#"[{ "mode": "forward" },
+                { "mode": "lock", "pos": { "x": 1000, "y": 0 }, "size": { "width": 256, "height": 240 } }]"#,
+        );
+        assert_eq!(
+            CameraZone::find(&zones, 100., 100.).unwrap().mode(),
+            CameraMode::Forward
+        );
+        assert_eq!(
+            CameraZone::find(&zones, 1100., 100.).unwrap().mode(),
+            CameraMode::Lock
+        );
+        assert!(CameraZone::find(&[], 100., 100.).is_none());
+    }
+
+    #[test]
+    fn should_only_scroll_forward() {
+        let mut camera = camera();
+        let zones = zones(r#"[{ "mode": "forward" }]"#);
+        camera.focus(&zones, &[(500., 100.)], (1000., 0.), 0.1);
+        assert_eq!(camera.position(), (500. - LEAD, 0.));
+
+        camera.focus(&zones, &[(300., 100.)], (1000., 0.), 0.1);
+        assert_eq!(camera.position(), (500. - LEAD, 0.));
+        assert!(CameraMode::Forward.holds_players());
+    }
+
+    #[test]
+    fn should_stay_in_lock_zone() {
+        let mut camera = camera();
+        let zones = zones(
+            r#"[{ "mode": "lock", "pos": { "x": 1000, "y": 0 }, "size": { "width": 320, "height": 240 } }]"#,
+        );
+        camera.focus(&zones, &[(1010., 100.)], (2000., 0.), 0.1);
+        assert_eq!(camera.position(), (1000., 0.));
+
+        camera.focus(&zones, &[(1300., 100.)], (2000., 0.), 0.1);
+        assert_eq!(camera.position(), (1320. - 256., 0.));
+
+        // Out of the zone, the camera follows again
+        camera.focus(&zones, &[(1500., 100.)], (2000., 0.), 0.1);
+        assert_eq!(camera.position(), (1500. - LEAD, 0.));
+    }
+
+    #[test]
+    fn should_auto_scroll() {
+        let mut camera = camera();
+        let zones = zones(r#"[{ "mode": "auto-scroll", "speed": 32 }]"#);
+        for _ in 0..10 {
+            camera.focus(&zones, &[(500., 100.)], (1000., 0.), 0.5);
+        }
+        assert_eq!(camera.position(), (160., 0.));
+
+        for _ in 0..100 {
+            camera.focus(&zones, &[(10., 100.)], (1000., 0.), 0.5);
+        }
+        assert_eq!(camera.position(), (1000., 0.));
+    }
+
+    #[test]
+    fn should_scroll_out_of_dead_zone() {
+        let mut camera = camera();
+        camera.follow_y(&[100.], 480.);
+        assert_eq!(camera.position(), (0., 0.));
+
+        // Falling below the dead zone
+        camera.follow_y(&[400.], 480.);
+        assert_eq!(camera.position(), (0., 400. - 240. + DEAD_ZONE_BOTTOM));
+
+        // Moving within the dead zone
+        camera.follow_y(&[320.], 480.);
+        assert_eq!(camera.position(), (0., 400. - 240. + DEAD_ZONE_BOTTOM));
+
+        // Climbing above the dead zone, up to the top of the level
+        camera.follow_y(&[200.], 480.);
+        assert_eq!(camera.position(), (0., 200. - DEAD_ZONE_TOP));
+        camera.follow_y(&[-50.], 480.);
+        assert_eq!(camera.position(), (0., 0.));
+    }
 }
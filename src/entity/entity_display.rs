@@ -1,8 +1,7 @@
-use web_sys::CanvasRenderingContext2d;
-
 use crate::assets::animations::AnimationName;
 use crate::assets::sprites::{Sprite, SpriteSheet};
 use crate::physics::Direction;
+use crate::video::Renderer;
 
 #[derive(Debug)]
 pub enum EntityDisplay {
@@ -19,6 +18,15 @@ pub enum EntityDisplay {
     SpriteOnly {
         sprite: Sprite,
     },
+    SpriteAt {
+        sprite: Sprite,
+        x: f64,
+        y: f64,
+    },
+    Tiles {
+        sprite: Sprite,
+        count: u32,
+    },
 }
 
 impl EntityDisplay {
@@ -50,19 +58,39 @@ impl EntityDisplay {
         EntityDisplay::SpriteOnly { sprite }
     }
 
-    pub fn draw(&self, context: &CanvasRenderingContext2d, x: f64, y: f64, sprites: &SpriteSheet) {
+    /// A sprite drawn at an offset in the entity
+    pub(crate) fn sprite_at(sprite: Sprite, x: f64, y: f64) -> EntityDisplay {
+        EntityDisplay::SpriteAt { sprite, x, y }
+    }
+
+    /// The same tile repeated from left to right
+    pub(crate) fn tiles(sprite: Sprite, count: u32) -> EntityDisplay {
+        EntityDisplay::Tiles { sprite, count }
+    }
+
+    pub fn draw(&self, renderer: &dyn Renderer, x: f64, y: f64, sprites: &SpriteSheet) {
         match self {
             EntityDisplay::DisplayAnimation {
                 name,
                 distance,
                 direction,
-            } => sprites.draw_tile_animation(context, *name, x, y, *distance, *direction),
+            } => sprites.draw_tile_animation(renderer, *name, x, y, *distance, *direction),
             EntityDisplay::DisplaySprite {
                 name,
                 sprite,
                 direction,
-            } => sprites.draw_tile_animation_fixed(context, *name, *sprite, x, y, *direction),
-            EntityDisplay::SpriteOnly { sprite } => sprites.draw_tile(context, *sprite, x, y),
+            } => sprites.draw_tile_animation_fixed(renderer, *name, *sprite, x, y, *direction),
+            EntityDisplay::SpriteOnly { sprite } => sprites.draw_tile(renderer, *sprite, x, y),
+            EntityDisplay::SpriteAt {
+                sprite,
+                x: offset_x,
+                y: offset_y,
+            } => sprites.draw_image(renderer, *sprite, x + offset_x, y + offset_y),
+            EntityDisplay::Tiles { sprite, count } => {
+                for index in 0..*count {
+                    sprites.draw_tile(renderer, *sprite, x + index as f64, y);
+                }
+            }
         }
     }
 }
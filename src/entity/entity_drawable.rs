@@ -21,6 +21,10 @@ pub trait DrawableEntity {
     fn entity_display(&self) -> Option<EntityDisplay> {
         None
     }
+    /// The height drawn from the top of the entity, when partially hidden
+    fn visible_height(&self) -> Option<f64> {
+        None
+    }
 
     fn position(&self) -> (f64, f64) {
         self.entity().borrow().position()
@@ -0,0 +1,250 @@
+use core::cell::RefCell;
+use std::cell::Cell;
+use std::rc::Rc;
+
+use crate::assets::animations::AnimationName;
+use crate::assets::audio::sounds::Fx;
+use crate::assets::config::{MobsDefault, TraitDefinition};
+use crate::assets::sprites::Sprite;
+use crate::assets::TILE_SIZE;
+use crate::entity::entity_display::EntityDisplay;
+use crate::entity::entity_drawable::DrawableEntity;
+use crate::entity::traits::bullet_behavior::BulletBehavior;
+use crate::entity::traits::emitter::Emitter;
+use crate::entity::traits::fireball_behavior::FireballBehavior;
+use crate::entity::traits::flagpole::FlagpoleBehavior;
+use crate::entity::traits::goomba_behavior::GoombaBehavior;
+use crate::entity::traits::item_behavior::ItemBehavior;
+use crate::entity::traits::killable::Killable;
+use crate::entity::traits::koopa_behavior::{KoopaBehavior, KoopaState};
+use crate::entity::traits::physics::Physics;
+use crate::entity::traits::platform::PlatformBehavior;
+use crate::entity::traits::power::PowerUp;
+use crate::entity::traits::solid::Solid;
+use crate::entity::traits::stomper::Stomper;
+use crate::entity::traits::velocity::Velocity;
+use crate::entity::traits::walk::Walk;
+use crate::entity::{Entity, EntityFeature, Living};
+use crate::physics::{Direction, Position};
+
+/// How the mob is drawn, chosen from its behavior
+enum MobDisplay {
+    Hidden,
+    Walk,
+    Goomba,
+    Koopa(Rc<RefCell<KoopaBehavior>>),
+    Bullet,
+    Sprite(Sprite),
+    Flagpole(Rc<RefCell<FlagpoleBehavior>>),
+    Platform,
+}
+
+/// A mob built from the traits declared in the configuration
+pub struct MobEntity {
+    entity: Rc<RefCell<Entity>>,
+    display: MobDisplay,
+}
+
+impl MobEntity {
+    pub fn new(mut entity: Entity, param: &MobsDefault, physics: Physics) -> Self {
+        let tile_collider = physics.tile_collider();
+        let (x, y) = entity.position();
+        let origin = Position::new(x, y);
+        let mut physics = Some(physics);
+        let mut platform = false;
+        let mut solid: Option<Rc<RefCell<Solid>>> = None;
+        let mut walk: Option<Rc<RefCell<Walk>>> = None;
+        let mut display = MobDisplay::Hidden;
+
+        // The traits order is checked with the configuration, see MobsDefault::check
+        for definition in param.traits.iter() {
+            match definition {
+                TraitDefinition::Solid => {
+                    let t = Rc::new(RefCell::new(Solid::new()));
+                    entity.add_trait(t.clone());
+                    solid = Some(t);
+                }
+                TraitDefinition::Walk => {
+                    let t = Rc::new(RefCell::new(Walk::new(entity.dx)));
+                    entity.add_trait(t.clone());
+                    walk = Some(t);
+                    if let MobDisplay::Hidden = display {
+                        display = MobDisplay::Walk;
+                    }
+                }
+                TraitDefinition::Velocity => {
+                    entity.add_trait(Rc::new(RefCell::new(Velocity::default())));
+                }
+                TraitDefinition::Physics => {
+                    if let Some(physics) = physics.take() {
+                        entity.add_trait(Rc::new(RefCell::new(physics)));
+                    }
+                }
+                TraitDefinition::Stomper => {
+                    entity.add_trait(Rc::new(RefCell::new(Stomper::new())));
+                    entity.features.push(EntityFeature::Stomper);
+                }
+                TraitDefinition::Killable { ddx, ddy } => {
+                    if let Some(solid) = solid.clone() {
+                        let killable = Killable::new(solid, *ddx, *ddy);
+                        entity.add_trait(Rc::new(RefCell::new(killable)));
+                        entity.features.push(EntityFeature::Killable);
+                    }
+                }
+                TraitDefinition::Goomba => {
+                    entity.add_trait(Rc::new(RefCell::new(GoombaBehavior::default())));
+                    display = MobDisplay::Goomba;
+                }
+                TraitDefinition::Koopa {
+                    hide_duration,
+                    panic_speed,
+                } => {
+                    if let Some(walk) = walk.clone() {
+                        let behavior = KoopaBehavior::new(walk, *hide_duration, *panic_speed);
+                        let behavior = Rc::new(RefCell::new(behavior));
+                        entity.add_trait(behavior.clone());
+                        display = MobDisplay::Koopa(behavior);
+                    }
+                }
+                TraitDefinition::Bullet => {
+                    entity.add_trait(Rc::new(RefCell::new(BulletBehavior::default())));
+                    display = MobDisplay::Bullet;
+                }
+                TraitDefinition::Emitter {
+                    interval,
+                    mob,
+                    speed,
+                    hold_fire,
+                } => {
+                    let emitter = MobEntity::emitter(*interval, mob.clone(), *speed, *hold_fire);
+                    entity.add_trait(Rc::new(RefCell::new(emitter)));
+                }
+                TraitDefinition::Item { power } => {
+                    entity.add_trait(Rc::new(RefCell::new(ItemBehavior::new(*power))));
+                    let sprite = match power {
+                        PowerUp::Mushroom => Sprite::Mushroom,
+                        PowerUp::FireFlower => Sprite::FireFlower,
+                        PowerUp::Star => Sprite::Star,
+                        PowerUp::OneUp => Sprite::OneUp,
+                    };
+                    display = MobDisplay::Sprite(sprite);
+                }
+                TraitDefinition::Fireball => {
+                    entity.add_trait(Rc::new(RefCell::new(FireballBehavior::default())));
+                    display = MobDisplay::Sprite(Sprite::Fireball);
+                }
+                TraitDefinition::Flagpole { castle } => {
+                    let behavior = Rc::new(RefCell::new(FlagpoleBehavior::new(*castle)));
+                    entity.add_trait(behavior.clone());
+                    display = MobDisplay::Flagpole(behavior);
+                }
+                TraitDefinition::Platform {
+                    path,
+                    looping,
+                    falls,
+                } => {
+                    let behavior = PlatformBehavior::new(
+                        param.speed,
+                        origin,
+                        path.clone(),
+                        *looping,
+                        *falls,
+                        tile_collider.clone(),
+                    );
+                    entity.add_trait(Rc::new(RefCell::new(behavior)));
+                    platform = true;
+                    display = MobDisplay::Platform;
+                }
+            }
+        }
+
+        let entity = Rc::new(RefCell::new(entity));
+        if platform {
+            tile_collider.borrow_mut().add_platform(entity.clone());
+        }
+        Self { entity, display }
+    }
+
+    /// Emit a mob toward the player, unless the player is too close
+    fn emitter(interval: f64, mob: String, speed: f64, hold_fire: f64) -> Emitter {
+        let mut emitter = Emitter::new(interval);
+        let count = Rc::new(Cell::new(0));
+        emitter.add_emitter(Box::new(move |source, level| {
+            let (x, y) = source.borrow().position();
+            let delta_x = if let Some(player) = level.find_player() {
+                let (player_x, _) = player.borrow().position();
+                player_x - x
+            } else {
+                hold_fire
+            };
+
+            if delta_x.abs() < hold_fire {
+                return;
+            }
+
+            count.set(count.get() + 1);
+            let id = format!("{} #{}", mob, count.get());
+            let Some((sheet, emitted)) = level.build_mob(id, mob.as_str(), Position::new(x, y))
+            else {
+                return;
+            };
+            emitted.borrow().entity().borrow_mut().dx = speed * delta_x.signum();
+            source.borrow_mut().creation.push((sheet, emitted));
+
+            source.borrow_mut().play_fx(Fx::Shoot);
+        }));
+        emitter
+    }
+
+    fn direction(&self) -> Direction {
+        if self.entity.borrow().dx < 0. {
+            Direction::Left
+        } else {
+            Direction::Right
+        }
+    }
+}
+
+impl DrawableEntity for MobEntity {
+    fn entity(&self) -> Rc<RefCell<Entity>> {
+        self.entity.clone()
+    }
+
+    fn entity_display(&self) -> Option<EntityDisplay> {
+        let dist = self.entity.borrow().lifetime();
+        let direction = self.direction();
+        let dead = self.entity.borrow().living == Living::Dead;
+
+        let result = match &self.display {
+            MobDisplay::Hidden => return None,
+            MobDisplay::Walk => EntityDisplay::animation(AnimationName::Walk, dist, direction),
+            MobDisplay::Goomba if dead => {
+                EntityDisplay::sprite_direction(AnimationName::Walk, Sprite::Flat, direction)
+            }
+            MobDisplay::Goomba => EntityDisplay::animation(AnimationName::Walk, dist, direction),
+            MobDisplay::Koopa(behavior) => match behavior.borrow().state() {
+                KoopaState::Hiding if behavior.borrow().hide_time() > 3. => {
+                    EntityDisplay::animation(AnimationName::Wake, dist, direction)
+                }
+                KoopaState::Hiding | KoopaState::Panic => {
+                    EntityDisplay::sprite_direction(AnimationName::Walk, Sprite::Hiding, direction)
+                }
+                KoopaState::Walking => {
+                    EntityDisplay::animation(AnimationName::Walk, dist, direction)
+                }
+            },
+            MobDisplay::Bullet => {
+                EntityDisplay::sprite_direction(AnimationName::Bullet, Sprite::Bullet, direction)
+            }
+            MobDisplay::Sprite(sprite) => EntityDisplay::sprite(*sprite),
+            MobDisplay::Flagpole(behavior) => {
+                EntityDisplay::sprite_at(Sprite::Flag, 0., behavior.borrow().flag_y())
+            }
+            MobDisplay::Platform => {
+                let count = self.entity.borrow().size().width / TILE_SIZE;
+                EntityDisplay::tiles(Sprite::Chocolate, count)
+            }
+        };
+        Some(result)
+    }
+}
@@ -6,15 +6,11 @@ use std::fmt::Debug;
 use std::rc::Rc;
 use std::vec::Drain;
 
-use web_sys::AudioContext;
-
-use crate::assets::audio::sounds::{AudioBoard, Fx};
+use crate::assets::audio::sounds::Fx;
+use crate::assets::audio::AudioBoard;
 use crate::assets::config::MobsDefault;
-use crate::entity::bullet::BulletEntity;
-use crate::entity::cannon::CannonEntity;
 use crate::entity::entity_drawable::DrawableEntity;
-use crate::entity::goomba::GoombaEntity;
-use crate::entity::koopa::KoopaEntity;
+use crate::entity::mob::MobEntity;
 use crate::entity::traits::physics::Physics;
 use crate::entity::traits::EntityTrait;
 use crate::events::{Event, EventBuffer};
@@ -22,24 +18,22 @@ use crate::physics::bounding_box::BBox;
 use crate::physics::{Position, Size};
 use crate::utils::log;
 
-pub mod bullet;
-pub mod cannon;
 pub mod entity_display;
 pub mod entity_drawable;
-pub mod goomba;
-pub mod koopa;
+pub mod mob;
 pub mod player;
 pub mod player_env;
 pub mod traits;
 pub mod trigger;
 
+const PLAYER_BOUNCE: f64 = 400.;
+
 pub fn create_mobs(
     id: String,
-    mobs: &str,
     param: &MobsDefault,
     position: Position,
     physics: Physics,
-    audio: Option<Rc<AudioBoard>>,
+    audio: Option<Rc<dyn AudioBoard>>,
 ) -> Rc<RefCell<dyn DrawableEntity>> {
     let bounding_box = param
         .bbox
@@ -51,13 +45,7 @@ pub fn create_mobs(
     entity.x = position.x();
     entity.y = position.y();
 
-    match mobs {
-        "goomba" => Rc::new(RefCell::new(GoombaEntity::new(entity, physics))),
-        "koopa" => Rc::new(RefCell::new(KoopaEntity::new(entity, physics))),
-        "bullet" => Rc::new(RefCell::new(BulletEntity::new(entity))),
-        "cannon" => Rc::new(RefCell::new(CannonEntity::new(entity))),
-        _ => panic!("Mobs {} not found!", mobs),
-    }
+    Rc::new(RefCell::new(MobEntity::new(entity, param, physics)))
 }
 
 #[derive(Hash, Clone, Copy, Debug, Eq, PartialEq)]
@@ -111,7 +99,7 @@ pub struct Entity {
     features: Vec<EntityFeature>,
 
     // Audio
-    audio_board: Option<Rc<AudioBoard>>,
+    audio_board: Option<Rc<dyn AudioBoard>>,
     sounds: HashSet<Fx>,
 
     // Creation
@@ -123,7 +111,7 @@ impl Entity {
         id: String,
         bounding_box: BBox,
         size: Size,
-        audio_board: Option<Rc<AudioBoard>>,
+        audio_board: Option<Rc<dyn AudioBoard>>,
     ) -> Self {
         let traits = vec![];
         let lifetime = 0.;
@@ -206,6 +194,12 @@ impl Entity {
         self.dy = dy;
     }
 
+    /// Move without changing the velocity, e.g. carried by a platform
+    pub fn translate(&mut self, dx: f64, dy: f64) {
+        self.x += dx;
+        self.y += dy;
+    }
+
     // Gravity
     pub fn apply_gravity(&mut self, dy: f64) {
         self.dy += dy;
@@ -225,7 +219,7 @@ impl Entity {
     }
 
     // Features
-    fn is(&self, feature: EntityFeature) -> bool {
+    pub fn is(&self, feature: EntityFeature) -> bool {
         self.features.contains(&feature)
     }
     fn is_stomper(&self) -> bool {
@@ -252,10 +246,10 @@ impl Entity {
         self.sounds.insert(fx);
     }
 
-    pub fn play_sounds(&mut self, audio_context: &AudioContext) {
+    pub fn play_sounds(&mut self) {
         if let Some(ab) = &self.audio_board {
             for &fx in self.sounds.iter() {
-                ab.play(audio_context, fx);
+                ab.play(fx);
             }
         }
         self.sounds.clear();
@@ -277,15 +271,23 @@ pub fn finalize(event_buffer: Rc<RefCell<EventBuffer>>, entity: Rc<RefCell<Entit
     let e = entity.clone();
     let traits = entity.borrow().traits.clone();
     for event in event_buffer.borrow_mut().drain_entity(id.as_str()).iter() {
+        // A hurt entity may survive
+        if let Event::Killed(_) = event {
+            if hurt(&traits, e.clone()) {
+                continue;
+            }
+        }
+
         for t in traits.iter() {
             // log(&format!("<{:?}> on {:?}", event, t.borrow().name()));
             if let Ok(mut t) = t.try_borrow_mut() {
                 match event {
-                    Event::Stomper { .. } => t.on_stomper(e.clone()),
+                    Event::Stomper(_) => t.on_stomper(e.clone()),
                     Event::Stomped(_) => t.on_stomped(e.clone()),
                     Event::Killer(_) => t.on_killer(e.clone()),
                     Event::Killed(_) => t.on_killed(e.clone()),
                     Event::Coins(_, count) => t.on_coin(e.clone(), *count),
+                    Event::PowerUp(_, power) => t.on_power_up(e.clone(), *power),
                     _ => log(&format!("Event skipped: {:?}", event)),
                 }
             } else {
@@ -298,3 +300,50 @@ pub fn finalize(event_buffer: Rc<RefCell<EventBuffer>>, entity: Rc<RefCell<Entit
         }
     }
 }
+
+fn hurt(traits: &[Rc<RefCell<dyn EntityTrait>>], entity: Rc<RefCell<Entity>>) -> bool {
+    traits.iter().any(|t| {
+        t.try_borrow_mut()
+            .map(|mut t| t.on_hurt(entity.clone()))
+            .unwrap_or(false)
+    })
+}
+
+/// Kill the entity even if it could survive a hurt, e.g. when the time is out
+pub fn kill(entity: Rc<RefCell<Entity>>) {
+    let traits = entity.borrow().traits.clone();
+    for t in traits.iter() {
+        if let Ok(mut t) = t.try_borrow_mut() {
+            t.on_killed(entity.clone());
+        }
+    }
+}
+
+/// Two players meet, the falling one bounces on the other, otherwise they are pushed apart
+pub fn players_meet(us: Rc<RefCell<Entity>>, them: Rc<RefCell<Entity>>, stomp: bool, bump: bool) {
+    let alive = |e: &Rc<RefCell<Entity>>| e.borrow().living == Living::Alive;
+    if !alive(&us) || !alive(&them) {
+        return;
+    }
+
+    let us_box = us.borrow().collision_box();
+    let them_box = them.borrow().collision_box();
+    let (dy, them_dy) = (us.borrow().dy, them.borrow().dy);
+    let falling = dy > 0. && dy > them_dy && us_box.top() < them_box.top();
+
+    if stomp && falling {
+        let mut us = us.borrow_mut();
+        us.dy = -PLAYER_BOUNCE;
+        us.play_fx(Fx::Stomp);
+    } else if bump {
+        // Each player moves half of the overlap
+        let overlap = us_box.right().min(them_box.right()) - us_box.left().max(them_box.left());
+        let center = us_box.left() + us_box.right();
+        let side = if center < them_box.left() + them_box.right() {
+            -1.
+        } else {
+            1.
+        };
+        us.borrow_mut().x += side * overlap / 2.;
+    }
+}
@@ -2,7 +2,7 @@ use std::cell::RefCell;
 use std::rc::Rc;
 
 use crate::assets::animations::AnimationName;
-use crate::assets::audio::sounds::AudioBoard;
+use crate::assets::audio::AudioBoard;
 use crate::assets::config::PlayerDefault;
 use crate::assets::sprites::Sprite;
 use crate::entity::entity_display::EntityDisplay;
@@ -12,8 +12,10 @@ use crate::entity::traits::jump::Jump;
 use crate::entity::traits::killable::Killable;
 use crate::entity::traits::physics::Physics;
 use crate::entity::traits::player::PlayerTrait;
+use crate::entity::traits::power::{PlayerForm, Power};
 use crate::entity::traits::solid::Solid;
 use crate::entity::traits::stomper::Stomper;
+use crate::entity::traits::warp::Warp;
 use crate::entity::{Entity, EntityFeature, Living};
 use crate::game::PlayerInfo;
 use crate::physics::bounding_box::BBox;
@@ -24,6 +26,9 @@ pub struct PlayerEntity {
     go: Rc<RefCell<Go>>,
     jump: Rc<RefCell<Jump>>,
     player_trait: Rc<RefCell<PlayerTrait>>,
+    power: Rc<RefCell<Power>>,
+    warp: Rc<RefCell<Warp>>,
+    visible: bool,
 }
 
 impl PlayerEntity {
@@ -32,7 +37,7 @@ impl PlayerEntity {
         position: Position,
         param: &PlayerDefault,
         physics: Physics,
-        audio: Option<Rc<AudioBoard>>,
+        audio: Option<Rc<dyn AudioBoard>>,
     ) -> Self {
         let size = param.size;
         let bounding_box = BBox::new(0., 0., size);
@@ -49,6 +54,8 @@ impl PlayerEntity {
         let physics = Rc::new(RefCell::new(physics));
         let player_trait = PlayerTrait::new(player_info);
         let player_trait = Rc::new(RefCell::new(player_trait));
+        let power = Rc::new(RefCell::new(Power::new(go.clone(), size)));
+        let warp = Rc::new(RefCell::new(Warp::new(go.clone())));
 
         entity.add_trait(solid);
         entity.add_trait(go.clone());
@@ -57,32 +64,87 @@ impl PlayerEntity {
         entity.add_trait(killable);
         entity.add_trait(physics);
         entity.add_trait(player_trait.clone());
+        entity.add_trait(power.clone());
+        entity.add_trait(warp.clone());
 
         // Features
         entity.features.push(EntityFeature::Stomper);
         entity.features.push(EntityFeature::Player);
 
         let entity = Rc::new(RefCell::new(entity));
+        power
+            .borrow_mut()
+            .set_form(entity.clone(), player_info.form());
         Self {
             entity,
             go,
             jump,
             player_trait,
+            power,
+            warp,
+            visible: true,
         }
     }
 
+    /// The position is the one of a small player, a bigger one keeps the same feet
     pub fn reset(&mut self, player_info: &PlayerInfo, position: Position) {
+        self.power.borrow_mut().reset(self.entity.clone());
         self.entity.borrow_mut().set_x(position.x(), 0.);
         self.entity.borrow_mut().set_y(position.y(), 0.);
+        self.power
+            .borrow_mut()
+            .set_form(self.entity.clone(), player_info.form());
+        self.entity.borrow_mut().revive();
         self.go.borrow_mut().reset();
         self.jump.borrow_mut().reset();
         self.player_trait.borrow_mut().reset(player_info);
+        self.visible = true;
+    }
+
+    pub fn move_to(&self, position: Position) {
+        self.entity.borrow_mut().set_x(position.x(), 0.);
+        self.entity.borrow_mut().set_y(position.y(), 0.);
+    }
+
+    /// The lives, score, coins and form of the player, a dead player loses its power up
+    pub fn info(&self) -> PlayerInfo {
+        let player_trait = self.player_trait.borrow();
+        let form = match self.entity.borrow().living {
+            Living::Alive => self.power.borrow().form(),
+            _ => PlayerForm::Small,
+        };
+        PlayerInfo::new(
+            self.id().as_str(),
+            player_trait.lives().get(),
+            player_trait.score().get(),
+            player_trait.coins().get(),
+        )
+        .with_form(form)
     }
 
     pub fn player_trait(&self) -> Rc<RefCell<PlayerTrait>> {
         self.player_trait.clone()
     }
 
+    pub fn set_visible(&mut self, visible: bool) {
+        self.visible = visible;
+    }
+
+    /// Stop moving and jumping
+    pub fn halt(&mut self) {
+        self.go.borrow_mut().reset();
+        self.jump.borrow_mut().reset();
+    }
+
+    pub fn is_warping(&self) -> bool {
+        self.warp.borrow().is_warping()
+    }
+    pub fn exit_pipe(&self, spawn: Position, width: f64) {
+        self.warp
+            .borrow_mut()
+            .exit(self.entity.clone(), spawn, width);
+    }
+
     pub fn jump_start(&mut self) {
         if self.entity.borrow().living != Living::Alive {
             return;
@@ -129,6 +191,20 @@ impl PlayerEntity {
 
         self.go.borrow_mut().stop_run();
     }
+    pub fn down(&mut self) {
+        if self.entity.borrow().living != Living::Alive {
+            return;
+        }
+
+        self.warp.borrow_mut().down();
+    }
+    pub fn fire(&mut self) {
+        if self.entity.borrow().living != Living::Alive {
+            return;
+        }
+
+        self.power.borrow_mut().fire();
+    }
 }
 
 impl DrawableEntity for PlayerEntity {
@@ -140,34 +216,67 @@ impl DrawableEntity for PlayerEntity {
         self.entity.clone()
     }
 
-    fn entity_display(&self) -> Option<EntityDisplay> {
-        let name = AnimationName::Run;
+    fn visible_height(&self) -> Option<f64> {
+        self.warp.borrow().visible_height(&self.entity.borrow())
+    }
 
+    fn entity_display(&self) -> Option<EntityDisplay> {
         if self.entity.borrow().living != Living::Alive {
             return Some(EntityDisplay::sprite(Sprite::Dead));
         }
+        if !self.visible {
+            return None;
+        }
+
+        // Blink while recovering from a hit
+        let power = self.power.borrow();
+        let blink = (self.entity.borrow().lifetime() * 20.) as u32;
+        if power.is_recovering() && blink.is_multiple_of(2) {
+            return None;
+        }
+
+        let (name, idle, jump, skid) = match power.form() {
+            PlayerForm::Small => (
+                AnimationName::Run,
+                Sprite::Idle,
+                Sprite::Jump,
+                Sprite::Break,
+            ),
+            PlayerForm::Big => (
+                AnimationName::RunBig,
+                Sprite::BigIdle,
+                Sprite::BigJump,
+                Sprite::BigBreak,
+            ),
+            PlayerForm::Fire => (
+                AnimationName::RunFire,
+                Sprite::FireIdle,
+                Sprite::FireJump,
+                Sprite::FireBreak,
+            ),
+        };
 
         if self.jump.borrow().is_jumping() {
             return Some(EntityDisplay::sprite_direction(
                 name,
-                Sprite::Jump,
-                self.go.borrow().direction(),
+                jump,
+                self.go.borrow().heading(),
             ));
         }
 
         let distance = self.go.borrow().distance();
         let result = if distance > 0. {
             let dx = self.entity.borrow().dx;
-            let direction = self.go.borrow().direction();
+            let direction = self.go.borrow().heading();
             if (dx > 0. && direction == Direction::Left)
                 || (dx < 0. && direction == Direction::Right)
             {
-                EntityDisplay::sprite_direction(name, Sprite::Break, self.go.borrow().direction())
+                EntityDisplay::sprite_direction(name, skid, self.go.borrow().heading())
             } else {
                 EntityDisplay::animation(name, distance, direction)
             }
         } else {
-            EntityDisplay::sprite_direction(name, Sprite::Idle, self.go.borrow().direction())
+            EntityDisplay::sprite_direction(name, idle, self.go.borrow().heading())
         };
         Some(result)
     }
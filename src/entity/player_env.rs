@@ -5,11 +5,13 @@ use std::cell::Cell;
 use std::hash::{Hash, Hasher};
 use std::rc::Rc;
 
+use crate::assets::levels::SpawnDefinition;
 use crate::entity::entity_drawable::DrawableEntity;
 use crate::entity::player::PlayerEntity;
+use crate::entity::traits::level_end::LevelEnd;
 use crate::entity::traits::level_timer::LevelTimer;
 use crate::entity::traits::player_controller::PlayerController;
-use crate::entity::{Entity, Living};
+use crate::entity::{kill, Entity, Living};
 use crate::game::PlayerInfo;
 use crate::input::ActionHandler;
 use crate::physics::bounding_box::BBox;
@@ -19,6 +21,8 @@ pub struct PlayerEnv {
     entity: Rc<RefCell<Entity>>,
     player: Rc<RefCell<PlayerEntity>>,
     level_timer: Rc<RefCell<LevelTimer>>,
+    checkpoint: Rc<RefCell<Option<SpawnDefinition>>>,
+    level_end: Rc<RefCell<LevelEnd>>,
 }
 
 impl PlayerEnv {
@@ -28,23 +32,26 @@ impl PlayerEnv {
         let bbox = BBox::new(0., 0., size);
         let mut entity = Entity::new(id, bbox, size, None);
 
-        let mut checkpoint = Position::default();
-        checkpoint.set_x(8.);
-        let checkpoint = Rc::new(RefCell::new(checkpoint));
+        let checkpoint = Rc::new(RefCell::new(None));
 
         // Traits
-        let controller = PlayerController::new(player.borrow().entity(), checkpoint);
+        let controller = PlayerController::new(player.clone(), checkpoint.clone());
         let controller = Rc::new(RefCell::new(controller));
         let level_timer = LevelTimer::new(300., 100.);
         let level_timer = Rc::new(RefCell::new(level_timer));
+        let level_end = LevelEnd::new(player.clone(), level_timer.clone());
+        let level_end = Rc::new(RefCell::new(level_end));
         entity.add_trait(controller);
         entity.add_trait(level_timer.clone());
+        entity.add_trait(level_end.clone());
 
         let entity = Rc::new(RefCell::new(entity));
         Self {
             player,
             entity,
             level_timer,
+            checkpoint,
+            level_end,
         }
     }
 
@@ -67,16 +74,59 @@ impl PlayerEnv {
     pub fn position(&self) -> (f64, f64) {
         self.player.borrow().position()
     }
+    pub fn info(&self) -> PlayerInfo {
+        self.player.borrow().info()
+    }
+    pub fn living(&self) -> Living {
+        self.player.borrow().entity().borrow().living
+    }
 
     pub fn update_player(&self, player_info: &PlayerInfo, position: Position) {
         self.time().set(300.);
+        *self.checkpoint.borrow_mut() = None;
         self.player.borrow_mut().reset(player_info, position);
     }
 
+    /// The exact position, in the current form of the player
+    pub fn move_to(&self, position: Position) {
+        self.player.borrow().move_to(position);
+    }
+
+    /// End the level, the player slides down the pole then walks to the castle
+    pub fn grab_flagpole(&self, pole: BBox, height: f64, castle_x: f64) {
+        self.level_end.borrow_mut().grab(pole, height, castle_x);
+    }
+
+    /// No time left, the player dies whatever its power
+    pub fn time_out(&self) {
+        let entity = self.player.borrow().entity();
+        let alive = entity.borrow().living == Living::Alive;
+        if alive {
+            kill(entity);
+        }
+    }
+
+    /// Keep the player between the edges of the view
+    pub fn keep_in(&self, left: f64, right: f64) {
+        let entity = self.player.borrow().entity();
+        let width = entity.borrow().size().width as f64;
+        let x = entity.borrow().x;
+        let kept = x.max(left).min(right - width);
+        if kept != x {
+            entity.borrow_mut().set_x(kept, 0.);
+        }
+    }
+
+    pub fn exit_pipe(&self, spawn: Position, width: f64) {
+        self.player.borrow().exit_pipe(spawn, width);
+    }
+
     // Control
     fn can_control(&self) -> bool {
         let living = self.player.borrow().entity().borrow().living;
         living == Living::Alive
+            && !self.player.borrow().is_warping()
+            && !self.level_end.borrow().is_running()
     }
 }
 
@@ -133,6 +183,20 @@ impl ActionHandler for PlayerEnv {
         }
         self.player.borrow_mut().stop_run();
     }
+
+    fn down(&mut self) {
+        if !self.can_control() {
+            return;
+        }
+        self.player.borrow_mut().down();
+    }
+
+    fn fire(&mut self) {
+        if !self.can_control() {
+            return;
+        }
+        self.player.borrow_mut().fire();
+    }
 }
 
 impl Debug for PlayerEnv {
@@ -0,0 +1,74 @@
+use core::cell::RefCell;
+use std::rc::Rc;
+
+use crate::entity::traits::EntityTrait;
+use crate::entity::{Entity, EntityFeature, Living, ObstructionSide};
+use crate::events::EventBuffer;
+use crate::game::GameContext;
+use crate::physics::bounding_box::BBox;
+use crate::scene::level::Level;
+
+pub struct FireballBehavior {
+    bounce: f64,
+    grounded: bool,
+    remove_after: f64,
+}
+
+impl Default for FireballBehavior {
+    fn default() -> Self {
+        let bounce = 250.;
+        let grounded = false;
+        let remove_after = 3.;
+        Self {
+            bounce,
+            grounded,
+            remove_after,
+        }
+    }
+}
+
+impl EntityTrait for FireballBehavior {
+    fn name(&self) -> &str {
+        "fireball"
+    }
+
+    fn update(&mut self, entity: Rc<RefCell<Entity>>, _context: &GameContext, _level: &Level) {
+        // Bounce once all the tiles are resolved
+        if self.grounded {
+            self.grounded = false;
+            entity.borrow_mut().dy = -self.bounce;
+        }
+
+        let alive = entity.borrow().living == Living::Alive;
+        if alive && entity.borrow().lifetime > self.remove_after {
+            entity.borrow_mut().remove();
+        }
+    }
+
+    fn obstruct(&mut self, entity: Rc<RefCell<Entity>>, side: ObstructionSide, _rect: BBox) {
+        match side {
+            ObstructionSide::Bottom => self.grounded = true,
+            ObstructionSide::Left | ObstructionSide::Right => entity.borrow_mut().remove(),
+            ObstructionSide::Top => {}
+        }
+    }
+
+    fn collides(
+        &mut self,
+        us: Rc<RefCell<Entity>>,
+        them: Rc<RefCell<Entity>>,
+        event_buffer: Rc<RefCell<EventBuffer>>,
+    ) {
+        if us.borrow().living != Living::Alive {
+            return;
+        }
+
+        let enemy = !them.borrow().is(EntityFeature::Player);
+        if enemy && them.borrow().is_killable() {
+            event_buffer
+                .borrow_mut()
+                .kill(us.borrow().id(), them.borrow().id());
+            us.borrow_mut().remove();
+        }
+    }
+}
@@ -0,0 +1,77 @@
+use core::cell::RefCell;
+use std::rc::Rc;
+
+use crate::entity::traits::EntityTrait;
+use crate::entity::{Entity, EntityFeature, Living};
+use crate::events::EventBuffer;
+use crate::game::GameContext;
+use crate::scene::level::Level;
+
+pub const SLIDE_SPEED: f64 = 120.;
+const FLAG_TOP: f64 = 16.;
+const FLAG_HEIGHT: f64 = 16.;
+
+/// The end of the level, the player grabbing the pole slides down with the flag
+pub struct FlagpoleBehavior {
+    castle: f64,
+    grab: Option<(String, f64)>,
+    grabbed: bool,
+    flag_y: f64,
+}
+
+impl FlagpoleBehavior {
+    /// The castle door is at the given distance on the right of the flagpole
+    pub fn new(castle: f64) -> Self {
+        let grab = None;
+        let grabbed = false;
+        let flag_y = FLAG_TOP;
+        Self {
+            castle,
+            grab,
+            grabbed,
+            flag_y,
+        }
+    }
+
+    /// The top of the flag in the flagpole
+    pub fn flag_y(&self) -> f64 {
+        self.flag_y
+    }
+}
+
+impl EntityTrait for FlagpoleBehavior {
+    fn name(&self) -> &str {
+        "flagpole"
+    }
+
+    fn update(&mut self, entity: Rc<RefCell<Entity>>, context: &GameContext, level: &Level) {
+        if let Some((id, bottom)) = self.grab.take() {
+            self.grabbed = true;
+            let pole = entity.borrow().collision_box();
+            let height = pole.bottom() - bottom;
+            let castle_x = entity.borrow().x + self.castle;
+            if let Some(player) = level.find_player_named(id.as_str()) {
+                player.borrow().grab_flagpole(pole, height, castle_x);
+            }
+        }
+
+        if self.grabbed {
+            let bottom = entity.borrow().size().height as f64 - FLAG_HEIGHT;
+            self.flag_y = (self.flag_y + SLIDE_SPEED * context.dt()).min(bottom);
+        }
+    }
+
+    fn collides(
+        &mut self,
+        _us: Rc<RefCell<Entity>>,
+        them: Rc<RefCell<Entity>>,
+        _event_buffer: Rc<RefCell<EventBuffer>>,
+    ) {
+        let player = them.borrow().is(EntityFeature::Player);
+        let alive = them.borrow().living == Living::Alive;
+        if player && alive && !self.grabbed {
+            let bottom = them.borrow().collision_box().bottom();
+            self.grab = Some((them.borrow().id(), bottom));
+        }
+    }
+}
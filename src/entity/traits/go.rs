@@ -55,7 +55,7 @@ impl Go {
         self.acceleration = 0.;
     }
 
-    pub fn direction(&self) -> Direction {
+    pub fn heading(&self) -> Direction {
         self.heading
     }
     pub fn distance(&self) -> f64 {
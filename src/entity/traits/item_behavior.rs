@@ -0,0 +1,41 @@
+use core::cell::RefCell;
+use std::rc::Rc;
+
+use crate::entity::traits::power::PowerUp;
+use crate::entity::traits::EntityTrait;
+use crate::entity::{Entity, EntityFeature, Living};
+use crate::events::EventBuffer;
+
+pub struct ItemBehavior {
+    power: PowerUp,
+}
+
+impl ItemBehavior {
+    pub fn new(power: PowerUp) -> Self {
+        Self { power }
+    }
+}
+
+impl EntityTrait for ItemBehavior {
+    fn name(&self) -> &str {
+        "item"
+    }
+
+    fn collides(
+        &mut self,
+        us: Rc<RefCell<Entity>>,
+        them: Rc<RefCell<Entity>>,
+        event_buffer: Rc<RefCell<EventBuffer>>,
+    ) {
+        let player = them.borrow().is(EntityFeature::Player);
+        let alive = them.borrow().living == Living::Alive;
+        if !player || !alive || us.borrow().living != Living::Alive {
+            return;
+        }
+
+        event_buffer
+            .borrow_mut()
+            .power_up(them.borrow().id(), self.power);
+        us.borrow_mut().remove();
+    }
+}
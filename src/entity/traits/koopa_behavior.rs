@@ -8,19 +8,14 @@ use crate::events::EventBuffer;
 use crate::game::GameContext;
 use crate::scene::level::Level;
 
-#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
 pub enum KoopaState {
+    #[default]
     Walking,
     Hiding,
     Panic,
 }
 
-impl Default for KoopaState {
-    fn default() -> Self {
-        KoopaState::Walking
-    }
-}
-
 pub struct KoopaBehavior {
     state: KoopaState,
     walk: Rc<RefCell<Walk>>,
@@ -31,12 +26,10 @@ pub struct KoopaBehavior {
 }
 
 impl KoopaBehavior {
-    pub fn new(walk: Rc<RefCell<Walk>>) -> Self {
+    pub fn new(walk: Rc<RefCell<Walk>>, hide_duration: f64, panic_speed: f64) -> Self {
         let state = KoopaState::default();
         let hide_time = 0.;
-        let hide_duration = 5.;
         let walk_speed = 0.;
-        let panic_speed = 300.;
         Self {
             state,
             walk,
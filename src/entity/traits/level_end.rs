@@ -0,0 +1,137 @@
+use core::cell::RefCell;
+use std::cell::Cell;
+use std::rc::Rc;
+
+use crate::entity::entity_drawable::DrawableEntity;
+use crate::entity::player::PlayerEntity;
+use crate::entity::traits::flagpole::SLIDE_SPEED;
+use crate::entity::traits::level_timer::LevelTimer;
+use crate::entity::traits::EntityTrait;
+use crate::entity::Entity;
+use crate::game::GameContext;
+use crate::physics::bounding_box::BBox;
+use crate::physics::Direction;
+use crate::scene::level::Level;
+
+const TIME_SCORE: u32 = 50;
+const NEXT_LEVEL_DELAY: f64 = 1.;
+/// The player blocked on the way to the castle enters it anyway
+const WALK_TIMEOUT: f64 = 5.;
+/// Bonus by grab height, from the bottom of the pole
+const HEIGHT_BONUS: [(f64, u32); 5] =
+    [(128., 5000), (96., 2000), (64., 800), (32., 400), (0., 100)];
+
+enum LevelEndState {
+    Playing,
+    Slide { pole: BBox, castle_x: f64 },
+    Walk { castle_x: f64, time: f64 },
+    Count,
+    Wait { time: f64 },
+    Finished,
+}
+
+/// The sequence after grabbing the flagpole, the player is not controlled anymore
+pub struct LevelEnd {
+    player: Rc<RefCell<PlayerEntity>>,
+    level_timer: Rc<RefCell<LevelTimer>>,
+    score: Rc<Cell<u32>>,
+    state: LevelEndState,
+}
+
+impl LevelEnd {
+    pub fn new(player: Rc<RefCell<PlayerEntity>>, level_timer: Rc<RefCell<LevelTimer>>) -> Self {
+        let score = player.borrow().player_trait().borrow().score();
+        let state = LevelEndState::Playing;
+        Self {
+            player,
+            level_timer,
+            score,
+            state,
+        }
+    }
+
+    pub fn is_running(&self) -> bool {
+        !matches!(self.state, LevelEndState::Playing)
+    }
+
+    /// The player grabbed the pole at the height above its bottom
+    pub(crate) fn grab(&mut self, pole: BBox, height: f64, castle_x: f64) {
+        if self.is_running() {
+            return;
+        }
+        self.level_timer.borrow_mut().stop();
+        self.player.borrow_mut().halt();
+
+        let (_, bonus) = HEIGHT_BONUS
+            .iter()
+            .find(|(min, _)| height >= *min)
+            .copied()
+            .unwrap_or_default();
+        self.score.set(self.score.get() + bonus);
+
+        self.state = LevelEndState::Slide { pole, castle_x };
+    }
+}
+
+impl EntityTrait for LevelEnd {
+    fn name(&self) -> &str {
+        "level_end"
+    }
+
+    fn update(&mut self, _entity: Rc<RefCell<Entity>>, context: &GameContext, level: &Level) {
+        let entity = self.player.borrow().entity();
+        match &mut self.state {
+            LevelEndState::Playing | LevelEndState::Finished => {}
+            LevelEndState::Slide { pole, castle_x } => {
+                let size = entity.borrow().size();
+                let bottom = pole.bottom() - size.height as f64;
+                let y = (entity.borrow().y + SLIDE_SPEED * context.dt()).min(bottom);
+                entity
+                    .borrow_mut()
+                    .set_x(pole.left() - size.width as f64, 0.);
+                entity.borrow_mut().set_y(y, 0.);
+                if y >= bottom {
+                    self.player.borrow_mut().start_move(Direction::Right);
+                    self.state = LevelEndState::Walk {
+                        castle_x: *castle_x,
+                        time: 0.,
+                    };
+                }
+            }
+            LevelEndState::Walk { castle_x, time } => {
+                *time += context.dt();
+                if entity.borrow().x >= *castle_x || *time > WALK_TIMEOUT {
+                    self.player.borrow_mut().halt();
+                    self.player.borrow_mut().set_visible(false);
+                    self.state = LevelEndState::Count;
+                }
+            }
+            LevelEndState::Count => {
+                // Remaining time converted to score
+                let time = self.level_timer.borrow().current_time();
+                if time.get() >= 1. {
+                    time.set(time.get() - 1.);
+                    self.score.set(self.score.get() + TIME_SCORE);
+                } else {
+                    time.set(0.);
+                    self.state = LevelEndState::Wait { time: 0. };
+                }
+            }
+            LevelEndState::Wait { time } => {
+                *time += context.dt();
+                if *time > NEXT_LEVEL_DELAY {
+                    let emitter = context.emitter();
+                    match level.next_level() {
+                        Some(next) => {
+                            let player = self.player.borrow().info();
+                            emitter.borrow_mut().goto(next.as_str(), player, None)
+                        }
+                        // The last level, back to the title
+                        None => emitter.borrow_mut().title(),
+                    }
+                    self.state = LevelEndState::Finished;
+                }
+            }
+        }
+    }
+}
@@ -11,23 +11,31 @@ pub struct LevelTimer {
     current_time: Rc<Cell<f64>>,
     hurry_time: f64,
     hurry_emitted: bool,
+    stopped: bool,
 }
 
 impl LevelTimer {
     pub fn new(total_time: f64, hurry_time: f64) -> Self {
         let current_time = Rc::new(Cell::new(total_time));
         let hurry_emitted = true;
+        let stopped = false;
 
         Self {
             current_time,
             hurry_time,
             hurry_emitted,
+            stopped,
         }
     }
 
     pub fn current_time(&self) -> Rc<Cell<f64>> {
         self.current_time.clone()
     }
+
+    /// The time does not run anymore, e.g. at the end of the level
+    pub fn stop(&mut self) {
+        self.stopped = true;
+    }
 }
 
 impl EntityTrait for LevelTimer {
@@ -36,6 +44,9 @@ impl EntityTrait for LevelTimer {
     }
 
     fn update(&mut self, _entity: Rc<RefCell<Entity>>, context: &GameContext, _level: &Level) {
+        if self.stopped {
+            return;
+        }
         let mut ct = self.current_time.get();
         ct -= 2. * context.dt();
         self.current_time.set(ct);
@@ -51,6 +62,8 @@ impl EntityTrait for LevelTimer {
         }
 
         if ct < 0. {
+            self.current_time.set(0.);
+            self.stopped = true;
             context.emitter().borrow_mut().timeout();
         }
     }
@@ -4,6 +4,7 @@ use std::cell::RefCell;
 use std::fmt::Debug;
 use std::rc::Rc;
 
+use crate::entity::traits::power::PowerUp;
 use crate::entity::{Entity, ObstructionSide};
 use crate::events::EventBuffer;
 use crate::game::GameContext;
@@ -12,21 +13,28 @@ use crate::scene::level::Level;
 
 pub mod bullet_behavior;
 pub mod emitter;
+pub mod fireball_behavior;
+pub mod flagpole;
 pub mod go;
 pub mod goomba_behavior;
 pub mod gravity;
+pub mod item_behavior;
 pub mod jump;
 pub mod killable;
 pub mod koopa_behavior;
+pub mod level_end;
 pub mod level_timer;
 pub mod physics;
+pub mod platform;
 pub mod player;
 pub mod player_controller;
+pub mod power;
 pub mod solid;
 pub mod stomper;
 pub mod trigger;
 pub mod velocity;
 pub mod walk;
+pub mod warp;
 
 pub trait EntityTrait {
     fn name(&self) -> &str;
@@ -37,6 +45,11 @@ pub trait EntityTrait {
     fn on_killer(&mut self, _entity: Rc<RefCell<Entity>>) {}
     fn on_killed(&mut self, _entity: Rc<RefCell<Entity>>) {}
     fn on_coin(&mut self, _entity: Rc<RefCell<Entity>>, _count: u32) {}
+    fn on_power_up(&mut self, _entity: Rc<RefCell<Entity>>, _power: PowerUp) {}
+    /// Return true when the entity survives the hit, it is then not killed
+    fn on_hurt(&mut self, _entity: Rc<RefCell<Entity>>) -> bool {
+        false
+    }
 
     // Operations
     fn update(&mut self, _entity: Rc<RefCell<Entity>>, _context: &GameContext, _level: &Level) {}
@@ -20,6 +20,10 @@ impl Physics {
             tile_collider,
         }
     }
+
+    pub fn tile_collider(&self) -> Rc<RefCell<TileCollider>> {
+        self.tile_collider.clone()
+    }
 }
 
 impl EntityTrait for Physics {
@@ -30,6 +34,9 @@ impl EntityTrait for Physics {
     fn update(&mut self, entity: Rc<RefCell<Entity>>, context: &GameContext, _level: &Level) {
         let dt = context.dt();
 
+        // Platform
+        self.tile_collider.borrow().carry(entity.clone(), dt);
+
         // Y
         entity.borrow_mut().apply_velocity_y(dt);
         self.tile_collider
@@ -0,0 +1,114 @@
+use core::cell::RefCell;
+use std::rc::Rc;
+
+use crate::entity::traits::EntityTrait;
+use crate::entity::{Entity, Living};
+use crate::game::GameContext;
+use crate::physics::tile_collider::TileCollider;
+use crate::physics::Position;
+use crate::scene::level::Level;
+
+/// A solid entity moving along its path, the path points are relative to the origin,
+/// where the platform was created.
+/// The platform goes back and forth, or around when looping.
+/// A falling platform does not follow any path, it falls once stepped on
+pub struct PlatformBehavior {
+    speed: f64,
+    path: Vec<Position>,
+    looping: bool,
+    falls: bool,
+    origin: Position,
+    target: usize,
+    backward: bool,
+    falling: bool,
+    tile_collider: Rc<RefCell<TileCollider>>,
+}
+
+impl PlatformBehavior {
+    pub fn new(
+        speed: f64,
+        origin: Position,
+        path: Vec<Position>,
+        looping: bool,
+        falls: bool,
+        tile_collider: Rc<RefCell<TileCollider>>,
+    ) -> Self {
+        let target = 0;
+        let backward = false;
+        let falling = false;
+
+        Self {
+            speed,
+            path,
+            looping,
+            falls,
+            origin,
+            target,
+            backward,
+            falling,
+            tile_collider,
+        }
+    }
+
+    /// The next point of the path, once the target is reached
+    fn next_target(&mut self) {
+        let last = self.path.len() - 1;
+        if self.looping {
+            self.target = (self.target + 1) % self.path.len();
+            return;
+        }
+        if self.target == last {
+            self.backward = true;
+        } else if self.target == 0 {
+            self.backward = false;
+        }
+        self.target = if self.backward {
+            self.target - 1
+        } else {
+            self.target + 1
+        };
+    }
+
+    /// The velocity toward the target, without going past it
+    fn velocity(&mut self, position: (f64, f64), dt: f64) -> (f64, f64) {
+        let target = self.path[self.target];
+        let dx = self.origin.x() + target.x() - position.0;
+        let dy = self.origin.y() + target.y() - position.1;
+        let distance = (dx * dx + dy * dy).sqrt();
+        if distance <= self.speed * dt {
+            self.next_target();
+            return (dx / dt, dy / dt);
+        }
+        (dx / distance * self.speed, dy / distance * self.speed)
+    }
+}
+
+impl EntityTrait for PlatformBehavior {
+    fn name(&self) -> &str {
+        "platform"
+    }
+
+    fn update(&mut self, entity: Rc<RefCell<Entity>>, context: &GameContext, level: &Level) {
+        let dt = context.dt();
+        let (dx, dy) = if self.falls {
+            let id = entity.borrow().id();
+            self.falling = self.falling || self.tile_collider.borrow().is_ridden(id.as_str());
+            (0., if self.falling { self.speed } else { 0. })
+        } else if self.path.len() > 1 {
+            self.velocity(entity.borrow().position(), dt)
+        } else {
+            (0., 0.)
+        };
+
+        let mut entity = entity.borrow_mut();
+        entity.dx = dx;
+        entity.dy = dy;
+        entity.apply_velocity_x(dt);
+        entity.apply_velocity_y(dt);
+
+        // Fallen out of the level
+        if self.falling && entity.y > level.height() && entity.living == Living::Alive {
+            entity.remove();
+        }
+    }
+}
@@ -2,11 +2,13 @@ use std::cell::{Cell, RefCell};
 use std::rc::Rc;
 
 use crate::assets::audio::sounds::Fx;
+use crate::entity::traits::power::PowerUp;
 use crate::entity::traits::EntityTrait;
 use crate::entity::Entity;
 use crate::game::PlayerInfo;
 
 const COIN_LIVE_THRESHOLD: u32 = 100;
+const POWER_UP_SCORE: u32 = 1000;
 
 #[derive(Default)]
 pub struct PlayerTrait {
@@ -63,7 +65,17 @@ impl EntityTrait for PlayerTrait {
 
     fn on_killed(&mut self, _entity: Rc<RefCell<Entity>>) {
         let lives = self.lives.get();
-        self.lives.set(lives - 1);
+        self.lives.set(lives.saturating_sub(1));
+    }
+
+    fn on_power_up(&mut self, _entity: Rc<RefCell<Entity>>, power: PowerUp) {
+        if let PowerUp::OneUp = power {
+            let lives = self.lives.get() + 1;
+            self.lives.set(lives);
+        } else {
+            let sc = self.score.get();
+            self.score.set(sc + POWER_UP_SCORE);
+        }
     }
 
     fn on_coin(&mut self, entity: Rc<RefCell<Entity>>, count: u32) {
@@ -1,30 +1,62 @@
 use core::cell::RefCell;
 use std::rc::Rc;
 
+use crate::assets::levels::SpawnDefinition;
+use crate::entity::entity_drawable::DrawableEntity;
+use crate::entity::player::PlayerEntity;
 use crate::entity::traits::EntityTrait;
 use crate::entity::{Entity, Living};
 use crate::game::GameContext;
 use crate::physics::Position;
 use crate::scene::level::Level;
+use crate::utils::log;
 
 pub struct PlayerController {
-    checkpoint: Rc<RefCell<Position>>,
-    player: Rc<RefCell<Entity>>,
+    checkpoint: Rc<RefCell<Option<SpawnDefinition>>>,
+    player: Rc<RefCell<PlayerEntity>>,
     removed_time: f64,
-    respawn_after: f64,
+    restart_after: f64,
+    restarted: bool,
 }
 
 impl PlayerController {
-    pub fn new(player: Rc<RefCell<Entity>>, checkpoint: Rc<RefCell<Position>>) -> Self {
-        let respawn_after = 3.;
+    pub fn new(
+        player: Rc<RefCell<PlayerEntity>>,
+        checkpoint: Rc<RefCell<Option<SpawnDefinition>>>,
+    ) -> Self {
+        let restart_after = 1.;
         let removed_time = 0.;
+        let restarted = false;
         Self {
             checkpoint,
             player,
             removed_time,
-            respawn_after,
+            restart_after,
+            restarted,
         }
     }
+
+    /// The position of a partner still playing in the level
+    fn partner(&self, level: &Level) -> Option<(f64, f64)> {
+        let name = self.player.borrow().id();
+        level
+            .players()
+            .iter()
+            .filter(|player| player.borrow().name() != name)
+            .find(|player| player.borrow().living() != Living::NoExistence)
+            .map(|player| player.borrow().position())
+    }
+
+    /// Without any partner left, a single player reports the end of the level:
+    /// the first one with lives left, or the first one of the level
+    fn reports_death(&self, level: &Level) -> bool {
+        let players = level.players();
+        let reporter = players
+            .iter()
+            .find(|player| player.borrow().lives().get() > 0)
+            .or_else(|| players.first());
+        reporter.is_some_and(|player| player.borrow().name() == self.player.borrow().id())
+    }
 }
 
 impl EntityTrait for PlayerController {
@@ -32,18 +64,51 @@ impl EntityTrait for PlayerController {
         "player_controller"
     }
 
-    fn update(&mut self, _entity: Rc<RefCell<Entity>>, context: &GameContext, _level: &Level) {
-        let living = self.player.borrow().living;
+    fn update(&mut self, _entity: Rc<RefCell<Entity>>, context: &GameContext, level: &Level) {
+        let entity = self.player.borrow().entity();
+        let living = entity.borrow().living;
         let dt = context.dt();
+
+        // Checkpoints passed
+        if let Living::Alive = living {
+            self.restarted = false;
+            let (x, _) = entity.borrow().position();
+            if let Some(checkpoint) = level.checkpoint(x) {
+                let passed = self.checkpoint.borrow().as_ref().map(|c| c.position().x());
+                if passed.is_none_or(|passed| checkpoint.position().x() > passed) {
+                    log(&format!("Checkpoint {}", checkpoint.name()));
+                    *self.checkpoint.borrow_mut() = Some(checkpoint);
+                }
+            }
+        }
+
+        // The death sequence is over
         if let Living::NoExistence = living {
             self.removed_time += dt;
-            if self.removed_time > self.respawn_after {
+            if self.removed_time <= self.restart_after || self.restarted {
+                return;
+            }
+
+            // In co-op, back next to a partner while lives are left
+            if let Some((x, y)) = self.partner(level) {
+                let info = self.player.borrow().info();
+                if info.lives() > 0 {
+                    self.removed_time = 0.;
+                    self.player.borrow_mut().reset(&info, Position::new(x, y));
+                }
+                return;
+            }
+
+            // Otherwise the level restarts with a life less
+            if self.reports_death(level) {
                 self.removed_time = 0.;
-                let x = self.checkpoint.borrow().x();
-                let y = self.checkpoint.borrow().y();
-                self.player.borrow_mut().set_x(x, 0.);
-                self.player.borrow_mut().set_y(y, 0.);
-                self.player.borrow_mut().revive();
+                self.restarted = true;
+                let player = level.current_player();
+                let spawn = self.checkpoint.borrow().as_ref().map(|c| c.name().into());
+                context
+                    .emitter()
+                    .borrow_mut()
+                    .player_died(level.name(), player, spawn);
             }
         }
     }
@@ -0,0 +1,194 @@
+use core::cell::RefCell;
+use std::rc::Rc;
+
+use serde::{Deserialize, Serialize};
+
+use crate::entity::traits::go::Go;
+use crate::entity::traits::EntityTrait;
+use crate::entity::{Entity, EntityFeature, Living};
+use crate::events::EventBuffer;
+use crate::game::GameContext;
+use crate::physics::bounding_box::BBox;
+use crate::physics::{Direction, Position, Size};
+use crate::scene::level::Level;
+
+const INVINCIBLE_DURATION: f64 = 10.;
+const RECOVER_DURATION: f64 = 2.;
+const MAX_FIREBALLS: usize = 2;
+
+/// An item collected by the player
+#[derive(Deserialize, Clone, Copy, Debug, Eq, PartialEq)]
+#[serde(rename_all = "kebab-case")]
+pub enum PowerUp {
+    Mushroom,
+    FireFlower,
+    Star,
+    OneUp,
+}
+
+/// The growth state of the player
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, Eq, PartialEq)]
+#[serde(rename_all = "kebab-case")]
+pub enum PlayerForm {
+    #[default]
+    Small,
+    Big,
+    Fire,
+}
+
+pub struct Power {
+    go: Rc<RefCell<Go>>,
+    small: Size,
+    form: PlayerForm,
+    invincible_time: f64,
+    recover_time: f64,
+    fire: bool,
+    fired: u32,
+    fireballs: Vec<Rc<RefCell<Entity>>>,
+}
+
+impl Power {
+    pub fn new(go: Rc<RefCell<Go>>, small: Size) -> Self {
+        let form = PlayerForm::default();
+        let invincible_time = 0.;
+        let recover_time = 0.;
+        let fire = false;
+        let fired = 0;
+        let fireballs = vec![];
+
+        Self {
+            go,
+            small,
+            form,
+            invincible_time,
+            recover_time,
+            fire,
+            fired,
+            fireballs,
+        }
+    }
+
+    pub fn form(&self) -> PlayerForm {
+        self.form
+    }
+    pub fn is_invincible(&self) -> bool {
+        self.invincible_time > 0.
+    }
+    pub fn is_recovering(&self) -> bool {
+        self.recover_time > 0.
+    }
+
+    /// Throw a fireball at the next update, only in fire form
+    pub fn fire(&mut self) {
+        self.fire = true;
+    }
+
+    /// Small, without any star nor recovery
+    pub fn reset(&mut self, entity: Rc<RefCell<Entity>>) {
+        self.invincible_time = 0.;
+        self.recover_time = 0.;
+        self.fire = false;
+        self.set_form(entity, PlayerForm::Small);
+    }
+
+    pub fn set_form(&mut self, entity: Rc<RefCell<Entity>>, form: PlayerForm) {
+        let height = match form {
+            PlayerForm::Small => self.small.height,
+            PlayerForm::Big | PlayerForm::Fire => self.small.height * 2,
+        };
+        let size = Size::new(self.small.width, height);
+
+        // Keep the feet on the ground
+        let mut entity = entity.borrow_mut();
+        entity.y += entity.size.height as f64 - height as f64;
+        entity.size = size;
+        entity.bounding_box = BBox::new(0., 0., size);
+        self.form = form;
+    }
+
+    fn throw_fireball(&mut self, entity: Rc<RefCell<Entity>>, level: &Level) {
+        self.fireballs
+            .retain(|fireball| fireball.borrow().living() != Living::NoExistence);
+        if self.fireballs.len() >= MAX_FIREBALLS {
+            return;
+        }
+
+        let (x, y) = entity.borrow().position();
+        let width = entity.borrow().size.width as f64;
+        let position = Position::new(x + width / 2. - 4., y + 8.);
+        self.fired += 1;
+        let id = format!("{} fireball #{}", entity.borrow().id, self.fired);
+
+        let Some((sheet, fireball)) = level.build_mob(id, "fireball", position) else {
+            return;
+        };
+        let ball = fireball.borrow().entity();
+        if self.go.borrow().heading() == Direction::Left {
+            ball.borrow_mut().dx *= -1.;
+        }
+        self.fireballs.push(ball);
+        entity.borrow_mut().creation.push((sheet, fireball));
+    }
+}
+
+impl EntityTrait for Power {
+    fn name(&self) -> &str {
+        "power"
+    }
+
+    fn on_power_up(&mut self, entity: Rc<RefCell<Entity>>, power: PowerUp) {
+        match (power, self.form) {
+            (PowerUp::Mushroom | PowerUp::FireFlower, PlayerForm::Small) => {
+                self.set_form(entity, PlayerForm::Big)
+            }
+            (PowerUp::FireFlower, _) => self.set_form(entity, PlayerForm::Fire),
+            (PowerUp::Star, _) => self.invincible_time = INVINCIBLE_DURATION,
+            _ => {}
+        }
+    }
+
+    fn on_hurt(&mut self, entity: Rc<RefCell<Entity>>) -> bool {
+        if self.is_invincible() || self.is_recovering() {
+            return true;
+        }
+
+        match self.form {
+            PlayerForm::Small => false,
+            PlayerForm::Big | PlayerForm::Fire => {
+                self.set_form(entity, PlayerForm::Small);
+                self.recover_time = RECOVER_DURATION;
+                true
+            }
+        }
+    }
+
+    fn update(&mut self, entity: Rc<RefCell<Entity>>, context: &GameContext, level: &Level) {
+        let dt = context.dt();
+        self.invincible_time = (self.invincible_time - dt).max(0.);
+        self.recover_time = (self.recover_time - dt).max(0.);
+
+        let alive = entity.borrow().living == Living::Alive;
+        if self.fire && alive && self.form == PlayerForm::Fire {
+            self.throw_fireball(entity, level);
+        }
+        self.fire = false;
+    }
+
+    fn collides(
+        &mut self,
+        us: Rc<RefCell<Entity>>,
+        them: Rc<RefCell<Entity>>,
+        event_buffer: Rc<RefCell<EventBuffer>>,
+    ) {
+        if !self.is_invincible() || us.borrow().living != Living::Alive {
+            return;
+        }
+
+        let enemy = !them.borrow().is(EntityFeature::Player);
+        if enemy && them.borrow().is_killable() {
+            event_buffer
+                .borrow_mut()
+                .kill(us.borrow().id(), them.borrow().id());
+        }
+    }
+}
@@ -0,0 +1,139 @@
+use core::cell::RefCell;
+use std::rc::Rc;
+
+use crate::assets::levels::TriggerDefinition;
+use crate::entity::traits::go::Go;
+use crate::entity::traits::EntityTrait;
+use crate::entity::{Entity, Living};
+use crate::game::GameContext;
+use crate::physics::Position;
+use crate::scene::level::Level;
+
+const WARP_SPEED: f64 = 32.;
+const WARP_PAUSE: f64 = 0.5;
+
+/// Sliding into a pipe, the top of the pipe hides the bottom of the player
+enum WarpState {
+    Idle,
+    Enter {
+        pipe: TriggerDefinition,
+        top: f64,
+        time: f64,
+    },
+    Warped {
+        top: f64,
+    },
+    Exit {
+        top: f64,
+        time: f64,
+    },
+}
+
+pub struct Warp {
+    go: Rc<RefCell<Go>>,
+    state: WarpState,
+    down: bool,
+}
+
+impl Warp {
+    pub fn new(go: Rc<RefCell<Go>>) -> Self {
+        let state = WarpState::Idle;
+        let down = false;
+        Self { go, state, down }
+    }
+
+    pub fn is_warping(&self) -> bool {
+        !matches!(self.state, WarpState::Idle)
+    }
+
+    /// Enter the pipe below the player at the next update
+    pub(crate) fn down(&mut self) {
+        self.down = true;
+    }
+
+    /// Rise from the middle of the pipe whose top left corner is the spawn point
+    pub(crate) fn exit(&mut self, entity: Rc<RefCell<Entity>>, spawn: Position, pipe_width: f64) {
+        let width = entity.borrow().size().width as f64;
+        let top = spawn.y();
+        entity
+            .borrow_mut()
+            .set_x(spawn.x() + (pipe_width - width) / 2., 0.);
+        entity.borrow_mut().set_y(top, 0.);
+        self.go.borrow_mut().reset();
+        self.state = WarpState::Exit { top, time: 0. };
+    }
+
+    /// The height of the player above the pipe, None when not in a pipe
+    pub fn visible_height(&self, entity: &Entity) -> Option<f64> {
+        let top = match self.state {
+            WarpState::Idle => return None,
+            WarpState::Enter { top, .. } => top,
+            WarpState::Warped { top } => top,
+            WarpState::Exit { top, .. } => top,
+        };
+        let height = entity.size().height as f64;
+        Some((top - entity.y).clamp(0., height))
+    }
+}
+
+impl EntityTrait for Warp {
+    fn name(&self) -> &str {
+        "warp"
+    }
+
+    fn update(&mut self, entity: Rc<RefCell<Entity>>, context: &GameContext, level: &Level) {
+        let down = self.down;
+        self.down = false;
+        if entity.borrow().living != Living::Alive {
+            self.state = WarpState::Idle;
+            return;
+        }
+
+        let dt = context.dt();
+        let height = entity.borrow().size().height as f64;
+        let x = entity.borrow().x;
+        match &mut self.state {
+            WarpState::Idle => {
+                let pipe = level.find_pipe(entity.borrow().collision_box());
+                if let (true, Some(pipe)) = (down, pipe) {
+                    self.go.borrow_mut().reset();
+                    let top = pipe.position().1 as f64;
+                    self.state = WarpState::Enter {
+                        pipe,
+                        top,
+                        time: 0.,
+                    };
+                }
+            }
+            WarpState::Enter { pipe, top, time } => {
+                *time += dt;
+                let y = (*top - height + WARP_SPEED * *time).min(*top);
+                entity.borrow_mut().set_x(x, 0.);
+                entity.borrow_mut().set_y(y, 0.);
+                if *time > height / WARP_SPEED + WARP_PAUSE {
+                    // The player in the pipe, the partners follow in co-op
+                    let id = entity.borrow().id();
+                    let player = level
+                        .find_player_named(id.as_str())
+                        .map(|player| player.borrow().info())
+                        .unwrap_or_else(|| level.current_player());
+                    context.emitter().borrow_mut().trigger(pipe.clone(), player);
+                    self.state = WarpState::Warped { top: *top };
+                }
+            }
+            WarpState::Warped { top } => {
+                entity.borrow_mut().set_x(x, 0.);
+                entity.borrow_mut().set_y(*top, 0.);
+            }
+            WarpState::Exit { top, time } => {
+                *time += dt;
+                let y = *top - WARP_SPEED * *time;
+                entity.borrow_mut().set_x(x, 0.);
+                entity.borrow_mut().set_y(y.max(*top - height), 0.);
+                if y <= *top - height {
+                    self.state = WarpState::Idle;
+                }
+            }
+        }
+    }
+}
@@ -5,25 +5,64 @@ use std::fmt;
 use std::rc::Rc;
 
 use crate::assets::levels::{TriggerDefinition, TriggerKind};
+use crate::entity::traits::power::PowerUp;
 use crate::entity::Entity;
 use crate::game::PlayerInfo;
+use crate::input::bindings::KeyBindings;
+use crate::physics::particles::ParticleEffect;
+use crate::physics::Position;
 use crate::utils::log;
 
 #[derive(Clone, Debug)]
 pub enum Event {
     // Entity
-    Stomper { id: String, y: f64 },
+    Stomper(String),
     Stomped(String),
     Killer(String),
     Killed(String),
     Coins(String, u32),
+    PowerUp(String, PowerUp),
     // Scene
     SceneComplete,
-    GotoLevel { level: String, player: PlayerInfo },
+    GotoLevel {
+        level: String,
+        player: PlayerInfo,
+        spawn: Option<String>,
+    },
+    PlayerDied {
+        level: String,
+        player: PlayerInfo,
+        spawn: Option<String>,
+    },
+    Title,
+    /// Back to the title from the pause menu, the game can be continued
+    Quit,
+    NewGame {
+        players: Vec<String>,
+        coop: bool,
+    },
+    Resume,
+    Options,
+    KeysChanged(Vec<KeyBindings>),
     // Time
     TimeOk,
     Hurry,
     Timeout,
+    // Level
+    Spawn {
+        mob: String,
+        position: Position,
+    },
+    Particles {
+        effect: ParticleEffect,
+        position: Position,
+    },
+    // Tile
+    Bump {
+        id: String,
+        layer: usize,
+        position: (u32, u32),
+    },
 }
 
 impl PartialEq for Event {
@@ -36,27 +75,62 @@ impl Event {
     fn id(&self) -> Option<&str> {
         match self {
             // Entity
-            Event::Stomper { id, .. } => Some(id),
+            Event::Stomper(id) => Some(id),
             Event::Stomped(id) => Some(id),
             Event::Killer(id) => Some(id),
             Event::Killed(id) => Some(id),
             Event::Coins(id, _) => Some(id),
+            Event::PowerUp(id, _) => Some(id),
             //  Scene
             Event::SceneComplete => None,
             Event::GotoLevel { .. } => None,
+            Event::PlayerDied { .. } => None,
+            Event::Title => None,
+            Event::Quit => None,
+            Event::NewGame { .. } => None,
+            Event::Resume => None,
+            Event::Options => None,
+            Event::KeysChanged(_) => None,
             // Time
             Event::Hurry => None,
             Event::TimeOk => None,
             Event::Timeout => None,
+            // Level
+            Event::Spawn { .. } => None,
+            Event::Particles { .. } => None,
+            // Tile
+            Event::Bump { .. } => None,
         }
     }
 
     fn is_level(&self) -> bool {
-        matches!(self, Event::Hurry | Event::TimeOk | Event::Timeout)
+        matches!(
+            self,
+            Event::Hurry
+                | Event::TimeOk
+                | Event::Timeout
+                | Event::Spawn { .. }
+                | Event::Particles { .. }
+        )
+    }
+
+    fn is_tile(&self) -> bool {
+        matches!(self, Event::Bump { .. })
     }
 
     fn is_system(&self) -> bool {
-        matches!(self, Event::SceneComplete | Event::GotoLevel { .. })
+        matches!(
+            self,
+            Event::SceneComplete
+                | Event::GotoLevel { .. }
+                | Event::PlayerDied { .. }
+                | Event::Title
+                | Event::Quit
+                | Event::NewGame { .. }
+                | Event::Resume
+                | Event::Options
+                | Event::KeysChanged(_)
+        )
     }
 }
 
@@ -82,13 +156,47 @@ impl EventBuffer {
     }
     pub fn trigger(&mut self, trigger: TriggerDefinition, player: PlayerInfo) {
         match trigger.kind() {
-            TriggerKind::Goto => {
-                let level = String::from(trigger.name());
-                let event = Event::GotoLevel { level, player };
-                self.push_event(event)
+            TriggerKind::Goto | TriggerKind::Pipe => {
+                self.goto(trigger.name(), player, trigger.spawn());
             }
         }
     }
+    pub fn goto(&mut self, level: &str, player: PlayerInfo, spawn: Option<String>) {
+        let level = String::from(level);
+        self.push_event(Event::GotoLevel {
+            level,
+            player,
+            spawn,
+        });
+    }
+    pub fn player_died(&mut self, level: &str, player: PlayerInfo, spawn: Option<String>) {
+        let level = String::from(level);
+        self.push_event(Event::PlayerDied {
+            level,
+            player,
+            spawn,
+        });
+    }
+    pub fn title(&mut self) {
+        self.push_event(Event::Title);
+    }
+    pub fn quit(&mut self) {
+        self.push_event(Event::Quit);
+    }
+    /// Players take turns, or play together in co-op
+    pub fn new_game(&mut self, players: Vec<String>, coop: bool) {
+        self.push_event(Event::NewGame { players, coop });
+    }
+    pub fn resume(&mut self) {
+        self.push_event(Event::Resume);
+    }
+    pub fn options(&mut self) {
+        self.push_event(Event::Options);
+    }
+    /// The keys chosen by the players, to keep them
+    pub fn keys_changed(&mut self, bindings: Vec<KeyBindings>) {
+        self.push_event(Event::KeysChanged(bindings));
+    }
 
     // Time
     pub fn time_ok(&mut self) {
@@ -101,11 +209,34 @@ impl EventBuffer {
         self.push_event(Event::Timeout);
     }
 
+    // Level
+    pub fn spawn(&mut self, mob: &str, position: Position) {
+        let mob = String::from(mob);
+        self.push_event(Event::Spawn { mob, position });
+    }
+    pub fn particles(&mut self, effect: ParticleEffect, position: Position) {
+        self.push_event(Event::Particles { effect, position });
+    }
+
+    // Tile
+    pub fn bump(&mut self, entity_id: String, layer: usize, position: (u32, u32)) {
+        let id = entity_id;
+        self.push_event(Event::Bump {
+            id,
+            layer,
+            position,
+        });
+    }
+
     // Entity
     pub fn coin(&mut self, entity_id: String, count: u32) {
         self.push_event(Event::Coins(entity_id, count));
     }
 
+    pub fn power_up(&mut self, entity_id: String, power: PowerUp) {
+        self.push_event(Event::PowerUp(entity_id, power));
+    }
+
     pub fn kill(&mut self, killer_id: String, killed_id: String) {
         self.push_event(Event::Killer(killer_id));
         self.push_event(Event::Killed(killed_id));
@@ -115,13 +246,15 @@ impl EventBuffer {
         stomper_entity: Rc<RefCell<Entity>>,
         stomped_entity: Rc<RefCell<Entity>>,
     ) {
-        let top = stomped_entity.borrow().collision_box().top();
-        let height = stomper_entity.borrow().size().height as f64;
         let id = stomper_entity.borrow().id();
-        let y = top - height;
 
-        self.push_event(Event::Stomper { id, y });
+        self.push_event(Event::Stomper(id));
         self.push_event(Event::Stomped(stomped_entity.borrow().id()));
+
+        // A puff under the feet of the stomper
+        let bbox = stomper_entity.borrow().collision_box();
+        let position = Position::new(bbox.left() + bbox.width() / 2. - 4., bbox.bottom() - 4.);
+        self.particles(ParticleEffect::StompPuff, position);
     }
 
     pub fn drain_entity(&mut self, id: &str) -> Vec<Event> {
@@ -152,6 +285,20 @@ impl EventBuffer {
         drain
     }
 
+    pub fn drain_tile(&mut self) -> Vec<Event> {
+        let mut drain = vec![];
+        let mut left = vec![];
+        for event in self.events.clone().iter() {
+            if event.is_tile() {
+                drain.push(event.clone());
+            } else {
+                left.push(event.clone());
+            }
+        }
+        self.events = left;
+        drain
+    }
+
     pub fn drain_system(&mut self) -> Vec<Event> {
         let mut drain = vec![];
         let mut left = vec![];
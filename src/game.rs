@@ -1,16 +1,19 @@
 use std::cell::RefCell;
 use std::rc::Rc;
 
-use web_sys::{AudioContext, CanvasRenderingContext2d};
+use serde::{Deserialize, Serialize};
 
+use crate::entity::traits::power::PlayerForm;
 use crate::events::EventBuffer;
 
-#[derive(Clone, Debug)]
+#[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct PlayerInfo {
     name: String,
     lives: u32,
     score: u32,
     coins: u32,
+    #[serde(default)]
+    form: PlayerForm,
 }
 
 impl PlayerInfo {
@@ -21,9 +24,15 @@ impl PlayerInfo {
             lives,
             score,
             coins,
+            form: PlayerForm::Small,
         }
     }
 
+    /// The player keeps its power up in the next level
+    pub fn with_form(self, form: PlayerForm) -> Self {
+        Self { form, ..self }
+    }
+
     pub fn name(&self) -> &str {
         self.name.as_str()
     }
@@ -36,44 +45,27 @@ impl PlayerInfo {
     pub fn coins(&self) -> u32 {
         self.coins
     }
+    pub fn form(&self) -> PlayerForm {
+        self.form
+    }
 }
 
 #[derive(Clone)]
 pub struct GameContext {
-    audio_context: Rc<AudioContext>,
-    video_context: Rc<CanvasRenderingContext2d>,
     event_buffer: Rc<RefCell<EventBuffer>>,
     dt: f64,
 }
 
 impl GameContext {
-    pub fn new(
-        audio_context: Rc<AudioContext>,
-        video_context: Rc<CanvasRenderingContext2d>,
-        event_buffer: Rc<RefCell<EventBuffer>>,
-        dt: f64,
-    ) -> Self {
-        Self {
-            audio_context,
-            video_context,
-            event_buffer,
-            dt,
-        }
+    pub fn new(event_buffer: Rc<RefCell<EventBuffer>>, dt: f64) -> Self {
+        Self { event_buffer, dt }
     }
 
     pub fn dt(&self) -> f64 {
         self.dt
     }
 
-    pub fn video_context(&self) -> Rc<CanvasRenderingContext2d> {
-        self.video_context.clone()
-    }
-
     pub fn emitter(&self) -> Rc<RefCell<EventBuffer>> {
         self.event_buffer.clone()
     }
-
-    pub fn audio_context(&self) -> &AudioContext {
-        &self.audio_context
-    }
 }
@@ -0,0 +1,117 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::input::Action;
+
+/// The keys chosen by a player, each one replaces the keys of its action in the keymap
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct KeyBindings {
+    keys: HashMap<Action, String>,
+}
+
+impl KeyBindings {
+    fn from_json(json: &str) -> Result<Self, String> {
+        serde_json::from_str(json).map_err(|err| err.to_string())
+    }
+
+    /// The keys of every player, by player index
+    pub fn players_from_json(json: &str) -> Result<Vec<Self>, String> {
+        serde_json::from_str(json)
+            // The keys of the first player only, saved before every player could choose them
+            .or_else(|_| Self::from_json(json).map(|bindings| vec![bindings]))
+            .map_err(|err| err.to_string())
+    }
+
+    pub fn players_to_json(players: &[Self]) -> String {
+        serde_json::to_string(players).expect("Bindings should be serializable")
+    }
+
+    /// The keymap with the chosen keys
+    pub fn apply(&self, keymap: &HashMap<String, Action>) -> HashMap<String, Action> {
+        let mut result: HashMap<String, Action> = keymap
+            .iter()
+            .filter(|(_, action)| !self.keys.contains_key(action))
+            .map(|(key, action)| (key.clone(), *action))
+            .collect();
+        for (action, key) in self.keys.iter() {
+            result.insert(key.clone(), *action);
+        }
+        result
+    }
+
+    /// Choose the key of the action, the conflicting action is returned
+    /// when the key is already used in the keymap
+    pub fn bind(
+        &mut self,
+        keymap: &HashMap<String, Action>,
+        action: Action,
+        key: &str,
+    ) -> Result<(), Action> {
+        match keymap.get(key) {
+            Some(other) if *other != action => Err(*other),
+            _ => {
+                self.keys.insert(action, String::from(key));
+                Ok(())
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn keymap() -> HashMap<String, Action> {
+        [
+            ("KeyA", Action::MoveLeft),
+            ("ArrowLeft", Action::MoveLeft),
+            ("Space", Action::Jump),
+            ("KeyO", Action::Run),
+        ]
+        .iter()
+        .map(|(key, action)| (String::from(*key), *action))
+        .collect()
+    }
+
+    #[test]
+    fn should_replace_keys_of_action() {
+        let mut bindings = KeyBindings::default();
+        bindings.bind(&keymap(), Action::MoveLeft, "KeyQ").unwrap();
+
+        let keymap = bindings.apply(&keymap());
+        assert_eq!(keymap.get("KeyQ"), Some(&Action::MoveLeft));
+        assert_eq!(keymap.get("KeyA"), None);
+        assert_eq!(keymap.get("ArrowLeft"), None);
+        assert_eq!(keymap.get("Space"), Some(&Action::Jump));
+    }
+
+    #[test]
+    fn should_detect_conflicts() {
+        let mut bindings = KeyBindings::default();
+        assert_eq!(
+            bindings.bind(&keymap(), Action::Run, "Space"),
+            Err(Action::Jump)
+        );
+        // A key of the same action is not a conflict
+        assert_eq!(bindings.bind(&keymap(), Action::Run, "KeyO"), Ok(()));
+    }
+
+    #[test]
+    fn should_persist_as_json() {
+        let mut bindings = KeyBindings::default();
+        bindings.bind(&keymap(), Action::Jump, "KeyK").unwrap();
+
+        let players = vec![KeyBindings::default(), bindings.clone()];
+        let json = KeyBindings::players_to_json(&players);
+        assert_eq!(json, r#"[{"keys":{}},{"keys":{"Jump":"KeyK"}}]"#);
+        assert_eq!(KeyBindings::players_from_json(json.as_str()), Ok(players));
+
+        // The keys of the first player only are still read
+        assert_eq!(
+            KeyBindings::players_from_json(r#"{"keys":{"Jump":"KeyK"}}"#),
+            Ok(vec![bindings])
+        );
+        assert!(KeyBindings::players_from_json("{}").is_err());
+    }
+}
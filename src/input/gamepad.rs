@@ -0,0 +1,222 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::rc::Rc;
+
+use wasm_bindgen::JsCast;
+use web_sys::{Gamepad, GamepadButton};
+
+use crate::assets::config::GamepadConfiguration;
+use crate::input::{Action, ActionHandler, InputSource, KeyState};
+use crate::utils::window;
+
+/// The axes of the left stick
+const STICK_X: usize = 0;
+const STICK_Y: usize = 1;
+
+/// The buttons pressed and the axes of a connected gamepad
+#[derive(Clone, Debug, Default)]
+pub struct GamepadState {
+    pub buttons: Vec<bool>,
+    pub axes: Vec<f64>,
+}
+
+impl GamepadState {
+    fn read(gamepad: &Gamepad) -> Self {
+        let buttons = gamepad
+            .buttons()
+            .iter()
+            .map(|button| button.unchecked_into::<GamepadButton>().pressed())
+            .collect();
+        let axes = gamepad
+            .axes()
+            .iter()
+            .map(|axis| axis.as_f64().unwrap_or_default())
+            .collect();
+
+        Self { buttons, axes }
+    }
+
+    fn axis(&self, index: usize) -> f64 {
+        self.axes.get(index).copied().unwrap_or_default()
+    }
+}
+
+/// The gamepads are polled every frame, the gamepad i drives the player i,
+/// a single player is driven by all of them
+pub struct Gamepads {
+    dead_zone: f64,
+    buttons: HashMap<usize, Action>,
+    handlers: Vec<Rc<RefCell<dyn ActionHandler>>>,
+    pressed: HashMap<usize, Vec<Action>>,
+}
+
+impl Gamepads {
+    pub fn new(config: &GamepadConfiguration) -> Self {
+        let dead_zone = config.dead_zone;
+        let buttons = config.buttons.clone();
+        let handlers = vec![];
+        let pressed = HashMap::new();
+
+        Self {
+            dead_zone,
+            buttons,
+            handlers,
+            pressed,
+        }
+    }
+
+    /// The actions of the buttons pressed and the stick out of the dead zone
+    fn actions(&self, state: &GamepadState) -> Vec<Action> {
+        let (x, y) = (state.axis(STICK_X), state.axis(STICK_Y));
+        let stick = [
+            (x < -self.dead_zone, Action::MoveLeft),
+            (x > self.dead_zone, Action::MoveRight),
+            (y < -self.dead_zone, Action::Up),
+            (y > self.dead_zone, Action::Down),
+        ];
+        let stick = stick
+            .into_iter()
+            .filter(|(active, _)| *active)
+            .map(|(_, action)| action);
+        let buttons = state
+            .buttons
+            .iter()
+            .enumerate()
+            .filter(|(_, pressed)| **pressed)
+            .filter_map(|(index, _)| self.buttons.get(&index).copied());
+
+        let mut actions = vec![];
+        for action in stick.chain(buttons) {
+            if !actions.contains(&action) {
+                actions.push(action);
+            }
+        }
+        actions
+    }
+
+    fn handler(&self, index: usize) -> Option<Rc<RefCell<dyn ActionHandler>>> {
+        match self.handlers.len() {
+            1 => self.handlers.first().cloned(),
+            _ => self.handlers.get(index).cloned(),
+        }
+    }
+
+    /// Send the actions changed since the previous states, by gamepad index.
+    /// A disconnected gamepad releases its actions
+    pub fn update(&mut self, states: &[Option<GamepadState>]) {
+        let count = states
+            .len()
+            .max(self.pressed.keys().max().map_or(0, |i| i + 1));
+        for index in 0..count {
+            let actions = states
+                .get(index)
+                .and_then(|state| state.as_ref())
+                .map(|state| self.actions(state))
+                .unwrap_or_default();
+            let previous = self.pressed.remove(&index).unwrap_or_default();
+            if let Some(handler) = self.handler(index) {
+                let mut handler = handler.borrow_mut();
+                for action in previous.iter().filter(|a| !actions.contains(a)) {
+                    handler.handle(*action, KeyState::Released);
+                }
+                for action in actions.iter().filter(|a| !previous.contains(a)) {
+                    handler.handle(*action, KeyState::Pressed);
+                }
+            }
+            if !actions.is_empty() {
+                self.pressed.insert(index, actions);
+            }
+        }
+    }
+
+    /// The states of the gamepads connected to the browser
+    fn read() -> Vec<Option<GamepadState>> {
+        let Ok(gamepads) = window().navigator().get_gamepads() else {
+            return vec![];
+        };
+        gamepads
+            .iter()
+            .map(|gamepad| {
+                gamepad
+                    .dyn_into::<Gamepad>()
+                    .ok()
+                    .map(|gamepad| GamepadState::read(&gamepad))
+            })
+            .collect()
+    }
+}
+
+impl InputSource for Gamepads {
+    fn register_players(&mut self, handlers: &[Rc<RefCell<dyn ActionHandler>>]) {
+        self.handlers = handlers.to_vec();
+        self.pressed.clear();
+    }
+
+    fn poll(&mut self) {
+        let states = Gamepads::read();
+        self.update(&states);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::assets::config::Configuration;
+    use crate::input::tests::Inputs;
+
+    fn gamepads() -> Gamepads {
+        let config: Configuration =
+            serde_json::from_str(include_str!("../../www/assets/config.json")).unwrap();
+        Gamepads::new(&config.gamepad)
+    }
+
+    fn pad(buttons: &[usize], axes: (f64, f64)) -> Option<GamepadState> {
+        let mut state = GamepadState {
+            buttons: vec![false; 16],
+            axes: vec![axes.0, axes.1, 0., 0.],
+        };
+        for button in buttons {
+            state.buttons[*button] = true;
+        }
+        Some(state)
+    }
+
+    #[test]
+    fn should_map_buttons_and_stick() {
+        let mut gamepads = gamepads();
+        let player = Rc::new(RefCell::new(Inputs::default()));
+        gamepads.register_players(&[player.clone() as Rc<RefCell<dyn ActionHandler>>]);
+
+        // Within the dead zone, the stick is ignored
+        gamepads.update(&[pad(&[0], (0.2, -0.1))]);
+        gamepads.update(&[pad(&[0], (0.9, -0.1))]);
+        gamepads.update(&[pad(&[0], (0.9, -0.1))]);
+        assert_eq!(
+            player.borrow().pressed(),
+            vec![Action::Jump, Action::MoveRight]
+        );
+
+        gamepads.update(&[pad(&[], (0., 0.))]);
+        assert_eq!(
+            player.borrow().released(),
+            vec![Action::MoveRight, Action::Jump]
+        );
+    }
+
+    #[test]
+    fn should_route_gamepads_by_player() {
+        let mut gamepads = gamepads();
+        let first = Rc::new(RefCell::new(Inputs::default()));
+        let second = Rc::new(RefCell::new(Inputs::default()));
+        gamepads.register_players(&[first.clone(), second.clone()]);
+
+        gamepads.update(&[pad(&[14], (0., 0.)), pad(&[9], (0., 0.))]);
+        assert_eq!(first.borrow().pressed(), vec![Action::MoveLeft]);
+        assert_eq!(second.borrow().pressed(), vec![Action::Pause]);
+
+        // The second gamepad is disconnected
+        gamepads.update(&[pad(&[14], (0., 0.)), None]);
+        assert_eq!(second.borrow().released(), vec![Action::Pause]);
+        assert!(first.borrow().released().is_empty());
+    }
+}
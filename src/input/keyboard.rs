@@ -0,0 +1,321 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::rc::Rc;
+
+use wasm_bindgen::prelude::*;
+use wasm_bindgen::JsCast;
+use web_sys::KeyboardEvent;
+
+use crate::assets::config::Configuration;
+use crate::input::bindings::KeyBindings;
+use crate::input::{Action, ActionHandler, InputSource, KeyState};
+use crate::utils::window;
+
+/// The actions of a keymap sent to a handler
+struct Route {
+    keymap: HashMap<String, Action>,
+    key_states: HashMap<Action, KeyState>,
+    handler: Rc<RefCell<dyn ActionHandler>>,
+}
+
+impl Route {
+    fn new(keymap: HashMap<String, Action>, handler: Rc<RefCell<dyn ActionHandler>>) -> Self {
+        let key_states = HashMap::new();
+        Self {
+            keymap,
+            key_states,
+            handler,
+        }
+    }
+
+    fn key(&mut self, code: &str, state: KeyState) {
+        if let Some(action) = self.keymap.get(code) {
+            let old = self.key_states.insert(*action, state);
+            if (state == KeyState::Pressed && old.is_none())
+                || (old.is_some() && old != Some(state))
+            {
+                self.handler.borrow_mut().handle(*action, state);
+            }
+        }
+    }
+}
+
+type Routes = Rc<RefCell<Vec<Route>>>;
+
+/// The next key pressed is captured instead of sending its action
+#[derive(Default)]
+pub struct KeyCapture {
+    waiting: bool,
+    key: Option<String>,
+}
+
+impl KeyCapture {
+    pub fn start(&mut self) {
+        self.waiting = true;
+        self.key = None;
+    }
+
+    pub fn take(&mut self) -> Option<String> {
+        self.key.take()
+    }
+
+    /// True when the key is captured
+    fn capture(&mut self, code: &str, state: KeyState) -> bool {
+        if !self.waiting || state != KeyState::Pressed {
+            return false;
+        }
+        self.waiting = false;
+        self.key = Some(String::from(code));
+        true
+    }
+}
+
+pub struct Keyboard {
+    base_keymap: HashMap<String, Action>,
+    base_coop_keymaps: Vec<HashMap<String, Action>>,
+    bindings: Vec<KeyBindings>,
+    keymap: HashMap<String, Action>,
+    coop_keymaps: Vec<HashMap<String, Action>>,
+    routes: Routes,
+    capture: Rc<RefCell<KeyCapture>>,
+}
+
+impl Keyboard {
+    pub fn new(config: &Configuration) -> Self {
+        let base_keymap = config.keymap();
+        let base_coop_keymaps = config.coop.keymaps();
+        let bindings = vec![KeyBindings::default(); base_coop_keymaps.len().max(1)];
+        let keymap = base_keymap.clone();
+        let coop_keymaps = base_coop_keymaps.clone();
+        let routes = Rc::default();
+        let capture = Rc::default();
+
+        Self {
+            base_keymap,
+            base_coop_keymaps,
+            bindings,
+            keymap,
+            coop_keymaps,
+            routes,
+            capture,
+        }
+    }
+
+    /// The players who can choose their keys
+    pub fn players(&self) -> usize {
+        self.bindings.len()
+    }
+
+    /// The keymap of a player, with the keys chosen by the player.
+    /// The first player's one is the keymap used when playing alone
+    pub fn keymap(&self, player: usize) -> HashMap<String, Action> {
+        match player {
+            0 => self.keymap.clone(),
+            _ => self.coop_keymaps.get(player).cloned().unwrap_or_default(),
+        }
+    }
+
+    pub fn bindings(&self) -> Vec<KeyBindings> {
+        self.bindings.clone()
+    }
+
+    /// Replace the keys chosen by the players, the handlers use the new keymaps.
+    /// The keys of the first player apply alone and in co-op
+    pub fn set_bindings(&mut self, mut bindings: Vec<KeyBindings>) {
+        bindings.resize(self.players(), KeyBindings::default());
+        self.keymap = bindings[0].apply(&self.base_keymap);
+        self.coop_keymaps = self
+            .base_coop_keymaps
+            .iter()
+            .zip(bindings.iter())
+            .map(|(keymap, player)| player.apply(keymap))
+            .collect();
+        self.bindings = bindings;
+
+        let handlers: Vec<_> = self
+            .routes
+            .borrow()
+            .iter()
+            .map(|route| route.handler.clone())
+            .collect();
+        self.register_players(&handlers);
+    }
+
+    /// Choose the key of an action for a player, the conflicting player and action
+    /// are returned when the key is already used, the players share the keyboard in co-op
+    pub fn bind(
+        &mut self,
+        player: usize,
+        action: Action,
+        key: &str,
+    ) -> Result<(), (usize, Action)> {
+        for (index, keymap) in self.coop_keymaps.iter().enumerate() {
+            match keymap.get(key) {
+                Some(other) if index != player || *other != action => return Err((index, *other)),
+                _ => {}
+            }
+        }
+
+        let mut bindings = self.bindings.clone();
+        let keymap = self.keymap(player);
+        bindings[player]
+            .bind(&keymap, action, key)
+            .map_err(|other| (player, other))?;
+        self.set_bindings(bindings);
+        Ok(())
+    }
+
+    pub fn key_capture(&self) -> Rc<RefCell<KeyCapture>> {
+        self.capture.clone()
+    }
+
+    /// The keymap of a player, the default one when playing alone
+    fn player_keymap(&self, index: usize, players: usize) -> HashMap<String, Action> {
+        if players > 1 {
+            if let Some(keymap) = self.coop_keymaps.get(index) {
+                return keymap.clone();
+            }
+        }
+        self.keymap.clone()
+    }
+
+    #[allow(dead_code)]
+    pub fn unregister(&mut self, name: &str) {
+        self.routes
+            .borrow_mut()
+            .retain(|route| route.handler.borrow().name() != name);
+    }
+
+    fn dispatch(routes: &Routes, code: &str, state: KeyState) {
+        for route in routes.borrow_mut().iter_mut() {
+            route.key(code, state);
+        }
+    }
+
+    pub fn listen(&mut self) {
+        let routes = self.routes.clone();
+        let capture = self.capture.clone();
+
+        let closure = Closure::wrap(Box::new(move |event: KeyboardEvent| {
+            let state = KeyState::from_event_type(event.type_());
+            if capture.borrow_mut().capture(event.code().as_str(), state) {
+                return;
+            }
+            Keyboard::dispatch(&routes, event.code().as_str(), state);
+        }) as Box<dyn FnMut(_)>);
+
+        for event in ["keydown", "keyup"].iter() {
+            window()
+                .add_event_listener_with_callback(event, closure.as_ref().unchecked_ref())
+                .expect("Cannot listen the event");
+        }
+        closure.forget();
+    }
+}
+
+impl InputSource for Keyboard {
+    /// Each handler receives the actions of its own keymap
+    fn register_players(&mut self, handlers: &[Rc<RefCell<dyn ActionHandler>>]) {
+        let count = handlers.len();
+        let routes = handlers
+            .iter()
+            .enumerate()
+            .map(|(index, handler)| Route::new(self.player_keymap(index, count), handler.clone()))
+            .collect();
+        *self.routes.borrow_mut() = routes;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::input::tests::Inputs;
+
+    #[test]
+    fn should_route_keys_by_player() {
+        let config: Configuration =
+            serde_json::from_str(include_str!("../../www/assets/config.json")).unwrap();
+        let mut keyboard = Keyboard::new(&config);
+        let first = Rc::new(RefCell::new(Inputs::default()));
+        let second = Rc::new(RefCell::new(Inputs::default()));
+        keyboard.register_players(&[first.clone(), second.clone()]);
+
+        Keyboard::dispatch(&keyboard.routes, "KeyD", KeyState::Pressed);
+        Keyboard::dispatch(&keyboard.routes, "KeyD", KeyState::Pressed);
+        Keyboard::dispatch(&keyboard.routes, "ArrowRight", KeyState::Pressed);
+        Keyboard::dispatch(&keyboard.routes, "ArrowRight", KeyState::Released);
+        Keyboard::dispatch(&keyboard.routes, "ArrowRight", KeyState::Pressed);
+
+        assert_eq!(first.borrow().pressed(), vec![Action::MoveRight]);
+        assert_eq!(
+            second.borrow().pressed(),
+            vec![Action::MoveRight, Action::MoveRight]
+        );
+    }
+
+    #[test]
+    fn should_use_default_keymap_alone() {
+        let config: Configuration =
+            serde_json::from_str(include_str!("../../www/assets/config.json")).unwrap();
+        let mut keyboard = Keyboard::new(&config);
+        let player = Rc::new(RefCell::new(Inputs::default()));
+        keyboard.register_players(&[player.clone() as Rc<RefCell<dyn ActionHandler>>]);
+
+        Keyboard::dispatch(&keyboard.routes, "ArrowRight", KeyState::Pressed);
+        Keyboard::dispatch(&keyboard.routes, "KeyP", KeyState::Pressed);
+        assert_eq!(
+            player.borrow().pressed(),
+            vec![Action::MoveRight, Action::Jump]
+        );
+    }
+
+    #[test]
+    fn should_rebind_live() {
+        let config: Configuration =
+            serde_json::from_str(include_str!("../../www/assets/config.json")).unwrap();
+        let mut keyboard = Keyboard::new(&config);
+        let player = Rc::new(RefCell::new(Inputs::default()));
+        keyboard.register_players(&[player.clone() as Rc<RefCell<dyn ActionHandler>>]);
+
+        assert_eq!(
+            keyboard.bind(0, Action::Run, "KeyA"),
+            Err((0, Action::MoveLeft))
+        );
+        assert_eq!(keyboard.bind(0, Action::Jump, "KeyK"), Ok(()));
+
+        Keyboard::dispatch(&keyboard.routes, "Space", KeyState::Pressed);
+        Keyboard::dispatch(&keyboard.routes, "KeyK", KeyState::Pressed);
+        assert_eq!(player.borrow().pressed(), vec![Action::Jump]);
+        assert_eq!(keyboard.keymap(0).get("KeyK"), Some(&Action::Jump));
+    }
+
+    #[test]
+    fn should_rebind_each_player_in_coop() {
+        let config: Configuration =
+            serde_json::from_str(include_str!("../../www/assets/config.json")).unwrap();
+        let mut keyboard = Keyboard::new(&config);
+        let first = Rc::new(RefCell::new(Inputs::default()));
+        let second = Rc::new(RefCell::new(Inputs::default()));
+        keyboard.register_players(&[first.clone(), second.clone()]);
+        assert_eq!(keyboard.players(), 2);
+
+        // A key of the other player is a conflict
+        assert_eq!(
+            keyboard.bind(0, Action::Jump, "ShiftRight"),
+            Err((1, Action::Run))
+        );
+
+        assert_eq!(keyboard.bind(0, Action::Jump, "KeyK"), Ok(()));
+        assert_eq!(keyboard.bind(1, Action::Jump, "KeyL"), Ok(()));
+        Keyboard::dispatch(&keyboard.routes, "KeyK", KeyState::Pressed);
+        Keyboard::dispatch(&keyboard.routes, "KeyL", KeyState::Pressed);
+        assert_eq!(first.borrow().pressed(), vec![Action::Jump]);
+        assert_eq!(second.borrow().pressed(), vec![Action::Jump]);
+
+        // Kept by the saved keys
+        let mut restored = Keyboard::new(&config);
+        restored.set_bindings(keyboard.bindings());
+        assert_eq!(restored.keymap(1).get("KeyL"), Some(&Action::Jump));
+        assert_eq!(restored.keymap(0).get("KeyK"), Some(&Action::Jump));
+    }
+}
@@ -0,0 +1,170 @@
+use std::cell::RefCell;
+use std::hash::Hash;
+use std::rc::Rc;
+
+use serde::{Deserialize, Serialize};
+
+use crate::physics::Direction;
+use crate::physics::Direction::{Left, Right};
+
+pub mod bindings;
+pub mod gamepad;
+pub mod keyboard;
+pub mod replay;
+pub mod touch;
+
+#[derive(Serialize, Deserialize, Hash, Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Action {
+    MoveRight,
+    MoveLeft,
+    Jump,
+    Up,
+    Down,
+    Run,
+    Fire,
+    Start,
+    Pause,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Eq, PartialEq)]
+pub enum KeyState {
+    Pressed,
+    Released,
+}
+
+impl KeyState {
+    pub fn from_event_type(event_type: String) -> Self {
+        if event_type == "keydown" {
+            KeyState::Pressed
+        } else {
+            KeyState::Released
+        }
+    }
+}
+
+pub trait ActionHandler {
+    fn name(&self) -> String;
+
+    fn handle(&mut self, action: Action, state: KeyState) {
+        match (action, state) {
+            // Jumps
+            (Action::Jump, KeyState::Pressed) => self.jump_start(),
+            (Action::Jump, KeyState::Released) => self.jump_cancel(),
+            // Move
+            (Action::MoveRight, KeyState::Pressed) => self.start_move(Right),
+            (Action::MoveRight, KeyState::Released) => self.stop_move(Right),
+            (Action::MoveLeft, KeyState::Pressed) => self.start_move(Left),
+            (Action::MoveLeft, KeyState::Released) => self.stop_move(Left),
+            // Run
+            (Action::Run, KeyState::Pressed) => self.start_run(),
+            (Action::Run, KeyState::Released) => self.stop_run(),
+            // Up / Down
+            (Action::Up, KeyState::Pressed) => self.up(),
+            (Action::Down, KeyState::Pressed) => self.down(),
+            // Fire
+            (Action::Fire, KeyState::Pressed) => self.fire(),
+            // Start
+            (Action::Start, KeyState::Pressed) => self.start(),
+            _ => {}
+        }
+    }
+
+    // Jump
+    fn jump_start(&mut self) {}
+    fn jump_cancel(&mut self) {}
+
+    // Move
+    fn start_move(&mut self, _direction: Direction) {}
+    fn stop_move(&mut self, _direction: Direction) {}
+
+    // Run
+    fn start_run(&mut self) {}
+    fn stop_run(&mut self) {}
+
+    // Up / Down
+    fn up(&mut self) {}
+    fn down(&mut self) {}
+
+    // Fire
+    fn fire(&mut self) {}
+
+    // Start
+    fn start(&mut self) {}
+}
+
+// Sources
+
+/// A device sending the actions of the players
+pub trait InputSource {
+    /// The handlers of the players, in order, replace the previous ones
+    fn register_players(&mut self, handlers: &[Rc<RefCell<dyn ActionHandler>>]);
+
+    /// Called every frame, for the devices without events
+    fn poll(&mut self) {}
+}
+
+/// All the input sources, the handlers are registered on each one
+#[derive(Default)]
+pub struct Input {
+    sources: Vec<Rc<RefCell<dyn InputSource>>>,
+}
+
+impl Input {
+    pub fn new(sources: Vec<Rc<RefCell<dyn InputSource>>>) -> Self {
+        Self { sources }
+    }
+
+    /// A single player
+    pub fn register(&mut self, handler: Rc<RefCell<dyn ActionHandler>>) {
+        self.register_players(&[handler]);
+    }
+
+    pub fn register_players(&mut self, handlers: &[Rc<RefCell<dyn ActionHandler>>]) {
+        for source in self.sources.iter() {
+            source.borrow_mut().register_players(handlers);
+        }
+    }
+
+    pub fn poll(&self) {
+        for source in self.sources.iter() {
+            source.borrow_mut().poll();
+        }
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+
+    /// Keep the actions received
+    #[derive(Default)]
+    pub struct Inputs(Vec<(Action, KeyState)>);
+
+    impl Inputs {
+        pub fn pressed(&self) -> Vec<Action> {
+            self.0
+                .iter()
+                .filter(|(_, state)| *state == KeyState::Pressed)
+                .map(|(action, _)| *action)
+                .collect()
+        }
+
+        pub fn released(&self) -> Vec<Action> {
+            self.0
+                .iter()
+                .filter(|(_, state)| *state == KeyState::Released)
+                .map(|(action, _)| *action)
+                .collect()
+        }
+    }
+
+    impl ActionHandler for Inputs {
+        fn name(&self) -> String {
+            String::from("inputs")
+        }
+
+        fn handle(&mut self, action: Action, state: KeyState) {
+            self.0.push((action, state));
+        }
+    }
+}
@@ -0,0 +1,153 @@
+use core::cell::RefCell;
+use std::cell::Cell;
+use std::rc::Rc;
+
+use serde::{Deserialize, Serialize};
+
+use crate::game::PlayerInfo;
+use crate::input::{Action, ActionHandler, KeyState};
+
+/// An input received before the update of the given level frame,
+/// the index of the player is omitted for the first one
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct InputRecord(
+    u32,
+    Action,
+    KeyState,
+    #[serde(default, skip_serializing_if = "is_first")] usize,
+);
+
+fn is_first(player: &usize) -> bool {
+    *player == 0
+}
+
+impl InputRecord {
+    pub fn frame(&self) -> u32 {
+        self.0
+    }
+    pub fn action(&self) -> Action {
+        self.1
+    }
+    pub fn state(&self) -> KeyState {
+        self.2
+    }
+    pub fn player(&self) -> usize {
+        self.3
+    }
+}
+
+/// The inputs of the players in a level, stored as `[frame, action, state, player]` tuples
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Replay {
+    level: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    spawn: Option<String>,
+    player: PlayerInfo,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    partners: Vec<PlayerInfo>,
+    inputs: Vec<InputRecord>,
+}
+
+impl Replay {
+    pub fn new(level: &str, spawn: Option<&str>, player: &PlayerInfo) -> Self {
+        let level = String::from(level);
+        let spawn = spawn.map(String::from);
+        let player = player.clone();
+        let partners = vec![];
+        let inputs = vec![];
+
+        Self {
+            level,
+            spawn,
+            player,
+            partners,
+            inputs,
+        }
+    }
+
+    pub fn from_json(json: &str) -> serde_json::Result<Self> {
+        serde_json::from_str(json)
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).expect("Replay should be serializable")
+    }
+
+    pub fn level(&self) -> &str {
+        self.level.as_str()
+    }
+    pub fn spawn(&self) -> Option<&str> {
+        self.spawn.as_deref()
+    }
+    pub fn player(&self) -> &PlayerInfo {
+        &self.player
+    }
+    /// The players along the first one in co-op
+    pub fn partners(&self) -> &[PlayerInfo] {
+        self.partners.as_slice()
+    }
+    pub fn inputs(&self) -> &[InputRecord] {
+        self.inputs.as_slice()
+    }
+
+    pub fn add_partner(&mut self, partner: &PlayerInfo) {
+        self.partners.push(partner.clone());
+    }
+
+    fn push(&mut self, frame: u32, action: Action, state: KeyState, player: usize) {
+        self.inputs.push(InputRecord(frame, action, state, player));
+    }
+
+    /// Send the inputs up to the frame to the handlers of the players,
+    /// starting at the given input. The index of the next input is returned
+    pub fn play(&self, from: usize, frame: u32, handlers: &[Rc<RefCell<Recorder>>]) -> usize {
+        let mut next = from;
+        for input in self.inputs[from..].iter() {
+            if input.frame() > frame {
+                break;
+            }
+            if let Some(handler) = handlers.get(input.player()) {
+                handler.borrow_mut().handle(input.action(), input.state());
+            }
+            next += 1;
+        }
+        next
+    }
+}
+
+/// Record the inputs of a player, before the pause menu
+pub struct Recorder {
+    handler: Rc<RefCell<dyn ActionHandler>>,
+    player: usize,
+    frame: Rc<Cell<u32>>,
+    replay: Rc<RefCell<Replay>>,
+}
+
+impl Recorder {
+    pub fn new(
+        handler: Rc<RefCell<dyn ActionHandler>>,
+        player: usize,
+        frame: Rc<Cell<u32>>,
+        replay: Rc<RefCell<Replay>>,
+    ) -> Self {
+        Self {
+            handler,
+            player,
+            frame,
+            replay,
+        }
+    }
+}
+
+impl ActionHandler for Recorder {
+    fn name(&self) -> String {
+        self.handler.borrow().name()
+    }
+
+    fn handle(&mut self, action: Action, state: KeyState) {
+        self.replay
+            .borrow_mut()
+            .push(self.frame.get(), action, state, self.player);
+        self.handler.borrow_mut().handle(action, state);
+    }
+}
@@ -0,0 +1,283 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::rc::Rc;
+
+use wasm_bindgen::prelude::*;
+use wasm_bindgen::JsCast;
+use web_sys::{AddEventListenerOptions, HtmlCanvasElement, TouchEvent};
+
+use crate::input::{Action, ActionHandler, InputSource, KeyState};
+use crate::physics::bounding_box::BBox;
+use crate::physics::Size;
+
+const BUTTON_SIZE: u32 = 24;
+const MARGIN: f64 = 8.;
+
+/// A region of the screen pressing an action while touched
+#[derive(Clone, Copy, Debug)]
+pub struct TouchButton {
+    action: Action,
+    label: &'static str,
+    bbox: BBox,
+}
+
+impl TouchButton {
+    fn new(action: Action, label: &'static str, x: f64, y: f64) -> Self {
+        let bbox = BBox::new(x, y, Size::new(BUTTON_SIZE, BUTTON_SIZE));
+        Self {
+            action,
+            label,
+            bbox,
+        }
+    }
+
+    pub fn action(&self) -> Action {
+        self.action
+    }
+    pub fn label(&self) -> &str {
+        self.label
+    }
+    pub fn bbox(&self) -> BBox {
+        self.bbox
+    }
+}
+
+/// A D-pad at the bottom left of the screen, A, B and Fire at the bottom right,
+/// Pause at the top right under the dashboard
+fn layout(screen: Size) -> Vec<TouchButton> {
+    let button = BUTTON_SIZE as f64;
+    let bottom = screen.height as f64 - MARGIN - button;
+    let right = screen.width as f64 - MARGIN - button;
+    vec![
+        TouchButton::new(Action::Up, "^", MARGIN + button, bottom - 2. * button),
+        TouchButton::new(Action::MoveLeft, "<", MARGIN, bottom - button),
+        TouchButton::new(
+            Action::MoveRight,
+            ">",
+            MARGIN + 2. * button,
+            bottom - button,
+        ),
+        TouchButton::new(Action::Down, "v", MARGIN + button, bottom),
+        TouchButton::new(Action::Run, "B", right - button - MARGIN, bottom),
+        TouchButton::new(Action::Jump, "A", right, bottom - button / 2.),
+        TouchButton::new(
+            Action::Fire,
+            "F",
+            right - button - MARGIN,
+            bottom - button - MARGIN,
+        ),
+        TouchButton::new(Action::Pause, "P", right, 5. * MARGIN),
+    ]
+}
+
+/// The on-screen buttons, the touches drive the first player.
+/// The controls are shown once a touch is detected
+pub struct TouchControls {
+    buttons: Vec<TouchButton>,
+    touches: HashMap<i32, Action>,
+    pressed: Vec<Action>,
+    handler: Option<Rc<RefCell<dyn ActionHandler>>>,
+    detected: bool,
+}
+
+impl TouchControls {
+    pub fn new(screen: Size) -> Self {
+        let buttons = layout(screen);
+        let touches = HashMap::new();
+        let pressed = vec![];
+        let handler = None;
+        let detected = false;
+
+        Self {
+            buttons,
+            touches,
+            pressed,
+            handler,
+            detected,
+        }
+    }
+
+    pub fn buttons(&self) -> &[TouchButton] {
+        &self.buttons
+    }
+
+    pub fn is_detected(&self) -> bool {
+        self.detected
+    }
+
+    pub fn is_pressed(&self, action: Action) -> bool {
+        self.pressed.contains(&action)
+    }
+
+    /// The action of the button under the point, in screen pixels
+    pub fn hit(&self, x: f64, y: f64) -> Option<Action> {
+        self.buttons
+            .iter()
+            .find(|button| button.bbox.contains(x, y))
+            .map(|button| button.action)
+    }
+
+    /// A touch moved to the point, or ended without any point
+    pub fn touch(&mut self, id: i32, point: Option<(f64, f64)>) {
+        self.detected = true;
+        match point.and_then(|(x, y)| self.hit(x, y)) {
+            Some(action) => self.touches.insert(id, action),
+            None => self.touches.remove(&id),
+        };
+
+        // Send the changes, several touches may press the same action
+        let mut actions: Vec<Action> = vec![];
+        for action in self.touches.values() {
+            if !actions.contains(action) {
+                actions.push(*action);
+            }
+        }
+        let previous = std::mem::replace(&mut self.pressed, actions);
+        if let Some(handler) = &self.handler {
+            let mut handler = handler.borrow_mut();
+            for action in previous.iter().filter(|a| !self.pressed.contains(a)) {
+                handler.handle(*action, KeyState::Released);
+            }
+            for action in self.pressed.iter().filter(|a| !previous.contains(a)) {
+                handler.handle(*action, KeyState::Pressed);
+            }
+        }
+    }
+
+    /// The touches of the canvas, converted to screen pixels
+    pub fn listen(controls: Rc<RefCell<TouchControls>>, canvas: &HtmlCanvasElement) {
+        let target = canvas.clone();
+        let closure = Closure::wrap(Box::new(move |event: TouchEvent| {
+            event.prevent_default();
+            let rect = target.get_bounding_client_rect();
+            let scale_x = target.width() as f64 / rect.width();
+            let scale_y = target.height() as f64 / rect.height();
+            let ended = matches!(event.type_().as_str(), "touchend" | "touchcancel");
+
+            let touches = event.changed_touches();
+            for index in 0..touches.length() {
+                if let Some(touch) = touches.get(index) {
+                    let point = (
+                        (touch.client_x() as f64 - rect.left()) * scale_x,
+                        (touch.client_y() as f64 - rect.top()) * scale_y,
+                    );
+                    let point = if ended { None } else { Some(point) };
+                    controls.borrow_mut().touch(touch.identifier(), point);
+                }
+            }
+        }) as Box<dyn FnMut(_)>);
+
+        // Not passive, the page must not scroll or zoom
+        let options = AddEventListenerOptions::new();
+        options.set_passive(false);
+        for event in ["touchstart", "touchmove", "touchend", "touchcancel"].iter() {
+            canvas
+                .add_event_listener_with_callback_and_add_event_listener_options(
+                    event,
+                    closure.as_ref().unchecked_ref(),
+                    &options,
+                )
+                .expect("Cannot listen the event");
+        }
+        closure.forget();
+    }
+}
+
+impl InputSource for TouchControls {
+    fn register_players(&mut self, handlers: &[Rc<RefCell<dyn ActionHandler>>]) {
+        self.handler = handlers.first().cloned();
+        self.touches.clear();
+        self.pressed.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::input::tests::Inputs;
+
+    fn controls() -> TouchControls {
+        TouchControls::new(Size::new(256, 240))
+    }
+
+    fn center(controls: &TouchControls, action: Action) -> (f64, f64) {
+        let button = controls
+            .buttons()
+            .iter()
+            .find(|button| button.action() == action)
+            .unwrap();
+        let bbox = button.bbox();
+        (
+            bbox.left() + bbox.width() / 2.,
+            bbox.top() + bbox.height() / 2.,
+        )
+    }
+
+    #[test]
+    fn should_hit_buttons() {
+        let controls = controls();
+        for action in [
+            Action::MoveLeft,
+            Action::MoveRight,
+            Action::Jump,
+            Action::Run,
+            Action::Fire,
+            Action::Pause,
+        ] {
+            let (x, y) = center(&controls, action);
+            assert_eq!(controls.hit(x, y), Some(action));
+        }
+        assert_eq!(controls.hit(128., 100.), None);
+
+        // Buttons do not overlap and stay on screen
+        for button in controls.buttons() {
+            let bbox = button.bbox();
+            assert!(bbox.left() >= 0. && bbox.right() <= 256.);
+            assert!(bbox.top() >= 0. && bbox.bottom() <= 240.);
+            let others = controls
+                .buttons()
+                .iter()
+                .filter(|other| other.action() != button.action() && other.bbox().overlaps(bbox));
+            assert_eq!(others.count(), 0);
+        }
+    }
+
+    #[test]
+    fn should_press_and_release_with_touches() {
+        let mut controls = controls();
+        let player = Rc::new(RefCell::new(Inputs::default()));
+        controls.register_players(&[player.clone() as Rc<RefCell<dyn ActionHandler>>]);
+        assert!(!controls.is_detected());
+
+        // One thumb on the D-pad, the other one on A
+        controls.touch(1, Some(center(&controls, Action::MoveRight)));
+        controls.touch(2, Some(center(&controls, Action::Jump)));
+        assert!(controls.is_detected());
+
+        // Sliding from right to left
+        controls.touch(1, Some(center(&controls, Action::MoveLeft)));
+        controls.touch(2, None);
+        assert_eq!(
+            player.borrow().pressed(),
+            vec![Action::MoveRight, Action::Jump, Action::MoveLeft]
+        );
+        assert_eq!(
+            player.borrow().released(),
+            vec![Action::MoveRight, Action::Jump]
+        );
+        assert!(controls.is_pressed(Action::MoveLeft));
+    }
+
+    #[test]
+    fn should_fire_and_pause_with_touches() {
+        let mut controls = controls();
+        let player = Rc::new(RefCell::new(Inputs::default()));
+        controls.register_players(&[player.clone() as Rc<RefCell<dyn ActionHandler>>]);
+
+        controls.touch(1, Some(center(&controls, Action::Fire)));
+        controls.touch(1, None);
+        controls.touch(2, Some(center(&controls, Action::Pause)));
+        assert_eq!(player.borrow().pressed(), vec![Action::Fire, Action::Pause]);
+        assert_eq!(player.borrow().released(), vec![Action::Fire]);
+    }
+}
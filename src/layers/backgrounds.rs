@@ -3,98 +3,122 @@ use core::ops::RangeInclusive;
 use std::cell::Cell;
 use std::rc::Rc;
 
-use web_sys::{CanvasRenderingContext2d, HtmlCanvasElement};
-
-use crate::assets::levels::TileData;
+use crate::assets::levels::{Parallax, TileData};
 use crate::assets::sprites::SpriteSheet;
 use crate::assets::TILE_SIZE;
 use crate::camera::Camera;
 use crate::layers::Drawable;
 use crate::physics::matrix::Matrix;
+use crate::physics::tile_bumps::TileBumps;
 use crate::physics::tile_resolver::TileResolver;
 use crate::physics::{Direction, Size};
-use crate::utils::{canvas, context_2d};
+use crate::video::Renderer;
 
 pub struct BackgroundsLayer {
-    size: Size,
-    buffer: HtmlCanvasElement,
-    buffer_context: CanvasRenderingContext2d,
+    buffer: Box<dyn Renderer>,
+    layer: usize,
     tiles: Rc<RefCell<Matrix<TileData>>>,
     sprites: Rc<SpriteSheet>,
-    range: RangeInclusive<usize>,
+    columns: RangeInclusive<usize>,
+    rows: RangeInclusive<usize>,
     version: u32,
     distance: Rc<Cell<f64>>,
+    bumps: Rc<RefCell<TileBumps>>,
+    bumping: bool,
+    parallax: Parallax,
 }
 
 impl BackgroundsLayer {
     pub(crate) fn new(
+        renderer: &dyn Renderer,
         view: Size,
+        layer: usize,
         tiles: Rc<RefCell<Matrix<TileData>>>,
         sprites: Rc<SpriteSheet>,
         distance: Rc<Cell<f64>>,
+        bumps: Rc<RefCell<TileBumps>>,
     ) -> Self {
         let width = (view.width + 1) * TILE_SIZE; // FIXME hide camera buffer
-        let height = view.height * TILE_SIZE;
+        let height = (view.height + 1) * TILE_SIZE;
         let size = Size::new(width, height);
-        let buffer = canvas(size);
-        let buffer_context = context_2d(&buffer);
-        let range = 0..=0;
+        let buffer = renderer.create_buffer(size);
+        let columns = 0..=0;
+        let rows = 0..=0;
         let version = 0;
+        let bumping = false;
+        let parallax = Parallax::default();
 
         Self {
-            size,
             buffer,
-            buffer_context,
+            layer,
             tiles,
             sprites,
-            range,
+            columns,
+            rows,
             distance,
             version,
+            bumps,
+            bumping,
+            parallax,
         }
     }
 
-    fn redraw(&mut self, range: RangeInclusive<usize>) {
+    /// Scroll slower or faster than the camera
+    pub fn with_parallax(self, parallax: Parallax) -> Self {
+        Self { parallax, ..self }
+    }
+
+    /// Draw the tiles of the window in the buffer, when they changed
+    fn redraw(&mut self, columns: RangeInclusive<usize>, rows: RangeInclusive<usize>) {
         let current_version = self.tiles.borrow().version();
-        if self.range != range || self.version != current_version {
+        // Bumped tiles move, redraw until they are back in place
+        let bumping = !self.bumps.borrow().is_empty();
+        let moved = self.columns != columns || self.rows != rows;
+        if moved || self.version != current_version || bumping || self.bumping {
             self.version = current_version;
-            let width = self.size.width as f64;
-            let height = self.size.height as f64;
+            self.bumping = bumping;
 
-            self.buffer_context.clear_rect(0., 0., width, height);
+            self.buffer.clear();
+            let bumps = self.bumps.borrow();
+            let tile_height = self.sprites.tile_size().height as f64;
             for (x, y, data) in self.tiles.borrow().iter() {
-                if range.contains(&x) {
+                if !rows.contains(&y) {
+                    continue;
+                }
+                let offset = bumps.offset(self.layer, x, y) / tile_height;
+                for column in self.parallax.columns(x, &columns) {
                     self.sprites.draw_tile(
-                        &self.buffer_context,
+                        self.buffer.as_ref(),
                         data.sprite(),
-                        (x - *range.start()) as f64,
-                        y as f64,
+                        (column - *columns.start()) as f64,
+                        (y - *rows.start()) as f64 + offset,
                     );
                 }
             }
-            self.range = range;
+            self.columns = columns;
+            self.rows = rows;
         }
     }
 }
 
 impl Drawable for BackgroundsLayer {
-    fn draw(&mut self, context: Rc<CanvasRenderingContext2d>, camera: &Camera) {
-        let (cam_x, cam_y) = camera.position();
-        let draw_width = camera.width() as usize;
-        let draw_from = TileResolver::index(TILE_SIZE, cam_x) as usize;
-        let draw_to = draw_from + draw_width as usize;
-        let range = draw_from..=draw_to;
+    fn draw(&mut self, renderer: &dyn Renderer, camera: &Camera) {
+        let (cam_x, cam_y) = self.parallax.camera(camera.position());
+        let left = TileResolver::index(TILE_SIZE, cam_x) as usize;
+        let columns = left..=left + (camera.width() / TILE_SIZE) as usize;
+        let top = TileResolver::index(TILE_SIZE, cam_y) as usize;
+        let rows = top..=top + (camera.height() / TILE_SIZE) as usize;
 
         // Update static tiles
-        self.redraw(range.clone());
+        self.redraw(columns.clone(), rows.clone());
 
         // Draw buffer
-        context
-            .draw_image_with_html_canvas_element(
-                &self.buffer,
-                (-cam_x % TILE_SIZE as f64).floor(),
-                (-cam_y).floor(),
-            )
-            .unwrap();
+        let tile = TILE_SIZE as f64;
+        renderer.blit(
+            &self.buffer.bitmap(),
+            (-cam_x % tile).floor(),
+            (-cam_y % tile).floor(),
+        );
 
         // Draw Animations
         let distance = self.distance.get();
@@ -102,14 +126,18 @@ impl Drawable for BackgroundsLayer {
         let tile_size = self.sprites.tile_size();
         for (x, y, data) in self.tiles.borrow().iter() {
             if let Some(animation) = data.animation() {
-                if range.contains(&x) {
-                    let ax = (x - *range.start()) * tile_size.width as usize;
-                    let ay = y * tile_size.height as usize;
+                if !rows.contains(&y) {
+                    continue;
+                }
+                for column in self.parallax.columns(x, &columns) {
+                    let ax = (column - *columns.start()) * tile_size.width as usize;
+                    let ay = ((y - *rows.start()) * tile_size.height as usize) as f64;
+                    let ay = ay + self.bumps.borrow().offset(self.layer, x, y);
                     self.sprites.draw_tile_animation(
-                        &self.buffer_context,
+                        self.buffer.as_ref(),
                         animation,
                         ax as f64,
-                        ay as f64,
+                        ay,
                         distance,
                         direction,
                     );
@@ -1,15 +1,13 @@
 use std::cell::RefCell;
 use std::rc::Rc;
 
-use wasm_bindgen::JsValue;
-use web_sys::CanvasRenderingContext2d;
-
 use crate::assets::TILE_SIZE;
 use crate::camera::Camera;
 use crate::entity::entity_drawable::DrawableEntity;
 use crate::entity::Living;
 use crate::layers::Drawable;
 use crate::physics::tile_resolver::TileResolver;
+use crate::video::{Color, Renderer};
 
 pub struct CollisionLayer {
     entity: Rc<RefCell<dyn DrawableEntity>>,
@@ -21,7 +19,7 @@ impl CollisionLayer {
     }
 
     fn draw_entity(
-        context: Rc<CanvasRenderingContext2d>,
+        renderer: &dyn Renderer,
         cam_x: f64,
         cam_y: f64,
         x: f64,
@@ -29,25 +27,20 @@ impl CollisionLayer {
         width: f64,
         height: f64,
     ) {
-        context.set_stroke_style(&JsValue::from("red"));
-        context.set_fill_style(&JsValue::from("rgba(128,0,0,.5"));
-        context.set_line_width(0.5);
-        context.stroke_rect(x - cam_x, y - cam_y, width, height);
+        renderer.stroke_rect(Color::RED, x - cam_x, y - cam_y, width, height);
     }
 
     fn draw_box(
-        context: Rc<CanvasRenderingContext2d>,
-        color: &str,
+        renderer: &dyn Renderer,
+        color: Color,
         cam_x: f64,
         cam_y: f64,
         tile_size: f64,
         xi: u32,
         yi: u32,
     ) {
-        context.set_stroke_style(&JsValue::from(color));
-        context.set_fill_style(&JsValue::from("rgba(0,0,128,.5"));
-        context.set_line_width(0.5);
-        context.stroke_rect(
+        renderer.stroke_rect(
+            color,
             xi as f64 * tile_size - cam_x,
             yi as f64 * tile_size - cam_y,
             tile_size,
@@ -57,7 +50,7 @@ impl CollisionLayer {
 }
 
 impl Drawable for CollisionLayer {
-    fn draw(&mut self, context: Rc<CanvasRenderingContext2d>, camera: &Camera) {
+    fn draw(&mut self, renderer: &dyn Renderer, camera: &Camera) {
         if self.entity.borrow().entity().borrow().living() != Living::Alive {
             return;
         }
@@ -70,7 +63,7 @@ impl Drawable for CollisionLayer {
         let y = collision_box.top();
         let width = collision_box.width();
         let height = collision_box.height();
-        CollisionLayer::draw_entity(context.clone(), cam_x, cam_y, x, y, width, height);
+        CollisionLayer::draw_entity(renderer, cam_x, cam_y, x, y, width, height);
 
         // Boxes
         let xs = TileResolver::index_range(TILE_SIZE, x, x + width);
@@ -78,7 +71,7 @@ impl Drawable for CollisionLayer {
         let tile_size = TILE_SIZE as f64;
         for xi in xs {
             for yi in ys.clone() {
-                CollisionLayer::draw_box(context.clone(), "blue", cam_x, cam_y, tile_size, xi, yi);
+                CollisionLayer::draw_box(renderer, Color::BLUE, cam_x, cam_y, tile_size, xi, yi);
             }
         }
     }
@@ -1,25 +1,18 @@
-use std::rc::Rc;
-
-use wasm_bindgen::JsValue;
-use web_sys::CanvasRenderingContext2d;
+use crate::physics::Size;
+use crate::video::{Color, Renderer};
 
 pub struct ColorsLayer {
-    color: String,
+    color: Color,
 }
 
 impl ColorsLayer {
-    pub fn new(color: &str) -> Self {
-        let color = String::from(color);
+    pub fn new(color: Color) -> Self {
         Self { color }
     }
 
-    pub fn draw(&mut self, context: Rc<CanvasRenderingContext2d>) {
-        let canvas = context.canvas().unwrap();
-        let width = canvas.width() as f64;
-        let height = canvas.height() as f64;
+    pub fn draw(&mut self, renderer: &dyn Renderer) {
+        let Size { width, height } = renderer.size();
 
-        context.set_fill_style(&JsValue::from(self.color.as_str()));
-        context.set_stroke_style(&JsValue::from(self.color.as_str()));
-        context.fill_rect(0., 0., width, height);
+        renderer.fill_rect(self.color, 0., 0., width as f64, height as f64);
     }
 }
@@ -1,32 +1,30 @@
 use std::cell::RefCell;
 use std::rc::Rc;
 
-use web_sys::{CanvasRenderingContext2d, HtmlCanvasElement};
-
-use crate::assets::sprites::SpriteSheet;
+use crate::assets::sprites::{Rectangle, SpriteSheet};
 use crate::camera::Camera;
 use crate::entity::entity_drawable::DrawableEntity;
 use crate::entity::Living;
 use crate::layers::Drawable;
-use crate::physics::Size;
-use crate::utils::{canvas, context_2d};
+use crate::video::Renderer;
 
 pub struct EntityLayer {
-    buffer: HtmlCanvasElement,
-    buffer_context: CanvasRenderingContext2d,
+    buffer: Box<dyn Renderer>,
     entity: Rc<RefCell<dyn DrawableEntity>>,
     sprites: Rc<SpriteSheet>,
 }
 
 impl EntityLayer {
-    pub fn new(entity: Rc<RefCell<dyn DrawableEntity>>, sprites: Rc<SpriteSheet>) -> Self {
+    pub fn new(
+        renderer: &dyn Renderer,
+        entity: Rc<RefCell<dyn DrawableEntity>>,
+        sprites: Rc<SpriteSheet>,
+    ) -> Self {
         let size = entity.borrow().size();
-        let buffer = canvas(size);
-        let buffer_context = context_2d(&buffer);
+        let buffer = renderer.create_buffer(size);
 
         Self {
             buffer,
-            buffer_context,
             entity,
             sprites,
         }
@@ -34,30 +32,37 @@ impl EntityLayer {
 }
 
 impl Drawable for EntityLayer {
-    fn draw(&mut self, context: Rc<CanvasRenderingContext2d>, camera: &Camera) {
+    fn draw(&mut self, renderer: &dyn Renderer, camera: &Camera) {
         let (cam_x, cam_y) = camera.position();
         let (x, y) = self.entity.borrow().position();
 
-        // Draw entity to buffer
-        let Size { width, height } = self.entity.borrow().size();
-        self.buffer_context
-            .clear_rect(0., 0., width as f64, height as f64);
+        // Draw entity to buffer, the entity may have grown
+        let size = self.entity.borrow().size();
+        if self.buffer.size() != size {
+            self.buffer = renderer.create_buffer(size);
+        }
+        self.buffer.clear();
 
         // Sprite or anim
         let removed = self.entity.borrow().living() == Living::NoExistence;
         if !removed {
             if let Some(entity_display) = self.entity.borrow().entity_display() {
-                entity_display.draw(&self.buffer_context, 0., 0., &self.sprites);
+                entity_display.draw(self.buffer.as_ref(), 0., 0., &self.sprites);
             }
         }
 
         // Draw buffer
-        context
-            .draw_image_with_html_canvas_element(
-                &self.buffer,
-                (x - cam_x).floor(),
-                (y - cam_y).floor(),
-            )
-            .unwrap();
+        match self.entity.borrow().visible_height() {
+            Some(height) => {
+                let source = Rectangle {
+                    x: 0,
+                    y: 0,
+                    width: size.width,
+                    height: height as u32,
+                };
+                renderer.blit_part(&self.buffer.bitmap(), &source, x - cam_x, y - cam_y);
+            }
+            None => renderer.blit(&self.buffer.bitmap(), x - cam_x, y - cam_y),
+        }
     }
 }
@@ -1,19 +1,20 @@
 use std::cell::RefCell;
 use std::rc::Rc;
 
-use web_sys::CanvasRenderingContext2d;
-
 use crate::camera::Camera;
+use crate::video::Renderer;
 
 pub mod backgrounds;
 pub mod collision;
 pub mod colors;
 pub mod dashboard;
 pub mod entity;
+pub mod particles;
 pub mod player_progress;
+pub mod touch;
 
 pub trait Drawable {
-    fn draw(&mut self, context: Rc<CanvasRenderingContext2d>, camera: &Camera);
+    fn draw(&mut self, renderer: &dyn Renderer, camera: &Camera);
     //fn draw(&mut self, game_context: &GameContext, level: &Level);
 }
 
@@ -29,9 +30,9 @@ impl Compositor {
 }
 
 impl Drawable for Compositor {
-    fn draw(&mut self, context: Rc<CanvasRenderingContext2d>, camera: &Camera) {
+    fn draw(&mut self, renderer: &dyn Renderer, camera: &Camera) {
         for layer in self.layers.iter() {
-            layer.borrow_mut().draw(context.clone(), camera);
+            layer.borrow_mut().draw(renderer, camera);
         }
     }
 }
@@ -0,0 +1,36 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use crate::assets::sprites::SpriteSheet;
+use crate::camera::Camera;
+use crate::layers::Drawable;
+use crate::physics::particles::Particles;
+use crate::video::Renderer;
+
+pub struct ParticlesLayer {
+    particles: Rc<RefCell<Particles>>,
+    sprites: Rc<SpriteSheet>,
+}
+
+impl ParticlesLayer {
+    pub fn new(particles: Rc<RefCell<Particles>>, sprites: Rc<SpriteSheet>) -> Self {
+        Self { particles, sprites }
+    }
+}
+
+impl Drawable for ParticlesLayer {
+    fn draw(&mut self, renderer: &dyn Renderer, camera: &Camera) {
+        let (cam_x, cam_y) = camera.position();
+        for particle in self.particles.borrow().alive() {
+            let (x, y) = particle.position();
+            self.sprites.draw_tile_animation(
+                renderer,
+                particle.animation(),
+                x - cam_x,
+                y - cam_y,
+                particle.age(),
+                particle.direction(),
+            );
+        }
+    }
+}
@@ -0,0 +1,47 @@
+use core::cell::RefCell;
+use std::rc::Rc;
+
+use crate::assets::font::Font;
+use crate::camera::Camera;
+use crate::input::touch::TouchControls;
+use crate::layers::Drawable;
+use crate::video::{Color, Renderer};
+
+const BUTTON: Color = Color::rgba(255, 255, 255, 160);
+const PRESSED: Color = Color::rgba(255, 255, 255, 96);
+
+/// The touch controls over the screen, once a touch is detected
+pub struct TouchLayer {
+    controls: Rc<RefCell<TouchControls>>,
+    font: Rc<Font>,
+}
+
+impl TouchLayer {
+    pub fn new(controls: Rc<RefCell<TouchControls>>, font: Rc<Font>) -> Self {
+        Self { controls, font }
+    }
+}
+
+impl Drawable for TouchLayer {
+    fn draw(&mut self, renderer: &dyn Renderer, _camera: &Camera) {
+        let controls = self.controls.borrow();
+        if !controls.is_detected() {
+            return;
+        }
+
+        let size = self.font.size() as f64;
+        for button in controls.buttons() {
+            let bbox = button.bbox();
+            let (x, y) = (bbox.left(), bbox.top());
+            if controls.is_pressed(button.action()) {
+                renderer.fill_rect(PRESSED, x, y, bbox.width(), bbox.height());
+            }
+            renderer.stroke_rect(BUTTON, x, y, bbox.width(), bbox.height());
+            let (label_x, label_y) = (
+                x + (bbox.width() - size) / 2.,
+                y + (bbox.height() - size) / 2.,
+            );
+            self.font.print(renderer, button.label(), label_x, label_y);
+        }
+    }
+}
@@ -8,6 +8,7 @@ use crate::assets::{Assets, TILE_SIZE};
 use crate::physics::Size;
 use crate::system::System;
 use crate::utils::{body, canvas, context_2d, log, request_animation_frame, set_panic_hook, time};
+use crate::video::canvas::CanvasRenderer;
 
 pub mod assets;
 mod camera;
@@ -20,6 +21,7 @@ mod physics;
 mod scene;
 pub mod system;
 mod utils;
+pub mod video;
 
 // When the `wee_alloc` feature is enabled, use `wee_alloc` as the global
 // allocator.
@@ -45,11 +47,11 @@ pub async fn run() -> Result<(), JsValue> {
     );
     let can = canvas(size);
     body().append_child(&can)?;
-    let context = Rc::new(context_2d(&can));
+    let renderer = CanvasRenderer::new(context_2d(&can));
 
-    // System / Player
-    let mut sys = System::new(assets, context);
-    sys.start("mario");
+    // System
+    let mut sys = System::new(assets, Box::new(renderer), &can);
+    sys.start();
 
     timer(Box::new(move || sys.update(DELTA_TIME)));
 
@@ -75,13 +77,6 @@ fn timer(mut updater: Box<dyn FnMut()>) {
         }
         last_time = time;
 
-        // Check ending
-        // if player.borrow().time().get() < 0. {
-        //     log("Finished!");
-        //     let _ = f.borrow_mut().take();
-        //     return;
-        // }
-
         // Schedule ourself for another requestAnimationFrame callback.
         request_animation_frame(f.borrow().as_ref().unwrap());
     }) as Box<dyn FnMut()>));
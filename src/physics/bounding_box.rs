@@ -1,10 +1,7 @@
-use std::rc::Rc;
-
-use web_sys::CanvasRenderingContext2d;
-
 use crate::camera::Camera;
 use crate::layers::Drawable;
 use crate::physics::Size;
+use crate::video::{Color, Renderer};
 
 #[derive(Copy, Clone, Debug)]
 pub struct BBox {
@@ -80,13 +77,18 @@ impl BBox {
             && self.left < other.right
             && self.right > other.left
     }
+
+    pub fn contains(&self, x: f64, y: f64) -> bool {
+        x >= self.left && x < self.right && y >= self.top && y < self.bottom
+    }
 }
 
 impl Drawable for BBox {
-    fn draw(&mut self, context: Rc<CanvasRenderingContext2d>, camera: &Camera) {
+    fn draw(&mut self, renderer: &dyn Renderer, camera: &Camera) {
         let (cam_x, cam_y) = camera.position();
 
-        context.stroke_rect(
+        renderer.stroke_rect(
+            Color::BLACK,
             self.left - cam_x,
             self.top - cam_y,
             self.width(),
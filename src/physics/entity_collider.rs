@@ -1,16 +1,27 @@
 use std::cell::RefCell;
 use std::rc::Rc;
 
+use crate::assets::config::CoopConfiguration;
 use crate::entity::traits::collides;
-use crate::entity::Entity;
+use crate::entity::{players_meet, Entity, EntityFeature};
 use crate::events::EventBuffer;
 
-#[derive(Default)]
 pub struct EntityCollider {
     entities: Vec<Rc<RefCell<Entity>>>,
+    stomp: bool,
+    bump: bool,
 }
 
 impl EntityCollider {
+    /// Whether players can stomp or bump each other
+    pub fn new(coop: &CoopConfiguration) -> Self {
+        Self {
+            entities: vec![],
+            stomp: coop.stomp,
+            bump: coop.bump,
+        }
+    }
+
     pub fn add_entity(&mut self, entity: Rc<RefCell<Entity>>) {
         // log(&format!("Add entity: {:?}", entity));
         self.entities.push(entity);
@@ -30,7 +41,14 @@ impl EntityCollider {
             }
             let entity_box = entity.borrow().collision_box();
 
-            if subject_box.overlaps(entity_box) {
+            if !subject_box.overlaps(entity_box) {
+                continue;
+            }
+            let players = subject.borrow().is(EntityFeature::Player)
+                && entity.borrow().is(EntityFeature::Player);
+            if players {
+                players_meet(subject.clone(), entity.clone(), self.stomp, self.bump);
+            } else {
                 collides(subject.clone(), entity.clone(), event_buffer.clone());
             }
         }
@@ -1,5 +1,6 @@
 use crate::physics::Size;
 
+#[derive(Clone)]
 pub struct Matrix<T> {
     grid: Vec<Vec<Option<T>>>,
     size: Size,
@@ -50,6 +51,12 @@ impl<T: Clone> Matrix<T> {
         self.version += 1;
     }
 
+    /// Replace all the cells by the ones of the other matrix
+    pub fn restore(&mut self, other: &Matrix<T>) {
+        self.grid = other.grid.clone();
+        self.version += 1;
+    }
+
     pub fn iter(&self) -> Vec<(usize, usize, &T)> {
         let mut result = vec![];
         for (x, column) in self.grid.iter().enumerate() {
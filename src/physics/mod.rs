@@ -1,10 +1,12 @@
 use std::ops::Mul;
 
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 
 pub mod bounding_box;
 pub mod entity_collider;
 pub mod matrix;
+pub mod particles;
+pub mod tile_bumps;
 pub mod tile_collider;
 pub mod tile_resolver;
 
@@ -26,7 +28,7 @@ pub enum Direction {
     Right,
 }
 
-#[derive(Deserialize, Clone, Copy, Default, Debug)]
+#[derive(Serialize, Deserialize, Clone, Copy, Default, Debug, PartialEq)]
 pub struct Position {
     x: f64,
     y: f64,
@@ -46,6 +48,9 @@ impl Position {
     pub fn y(&self) -> f64 {
         self.y
     }
+    pub fn set_y(&mut self, y: f64) {
+        self.y = y;
+    }
 }
 
 impl From<(u32, u32)> for Position {
@@ -57,7 +62,7 @@ impl From<(u32, u32)> for Position {
     }
 }
 
-#[derive(Deserialize, Clone, Copy, Default, Debug)]
+#[derive(Deserialize, Clone, Copy, Default, Debug, PartialEq, Eq)]
 pub struct Size {
     pub(crate) width: u32,
     pub(crate) height: u32,
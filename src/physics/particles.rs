@@ -0,0 +1,184 @@
+use crate::assets::animations::AnimationName;
+use crate::physics::{Direction, GravityForce, Position};
+
+const MAX_PARTICLES: usize = 64;
+const DEBRIS_LIFETIME: f64 = 1.;
+const DEBRIS_SPEED: f64 = 60.;
+const DEBRIS_JUMP: (f64, f64) = (-400., -250.);
+const SPARKLE_LIFETIME: f64 = 0.3;
+const PUFF_LIFETIME: f64 = 0.3;
+
+/// The effects that can be emitted by the level
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ParticleEffect {
+    BrickDebris,
+    CoinSparkle,
+    StompPuff,
+}
+
+/// A short-lived sprite, moved by its velocity and the gravity
+#[derive(Clone, Copy, Debug)]
+pub struct Particle {
+    x: f64,
+    y: f64,
+    dx: f64,
+    dy: f64,
+    gravity: f64,
+    lifetime: f64,
+    age: f64,
+    animation: AnimationName,
+    direction: Direction,
+}
+
+impl Particle {
+    fn new(position: Position, animation: AnimationName, lifetime: f64) -> Self {
+        Self {
+            x: position.x(),
+            y: position.y(),
+            dx: 0.,
+            dy: 0.,
+            gravity: 0.,
+            lifetime,
+            age: 0.,
+            animation,
+            direction: Direction::Right,
+        }
+    }
+
+    pub fn is_alive(&self) -> bool {
+        self.age < self.lifetime
+    }
+    pub fn position(&self) -> (f64, f64) {
+        (self.x, self.y)
+    }
+    pub fn age(&self) -> f64 {
+        self.age
+    }
+    pub fn animation(&self) -> AnimationName {
+        self.animation
+    }
+    pub fn direction(&self) -> Direction {
+        self.direction
+    }
+
+    fn update(&mut self, dt: f64) {
+        self.dy += self.gravity * dt;
+        self.x += self.dx * dt;
+        self.y += self.dy * dt;
+        self.age += dt;
+    }
+}
+
+/// A pool of particles, dead particles are reused by the next emission
+pub struct Particles {
+    gravity: GravityForce,
+    pool: Vec<Particle>,
+}
+
+impl Particles {
+    pub fn new(gravity: GravityForce) -> Self {
+        let pool = Vec::with_capacity(MAX_PARTICLES);
+        Self { gravity, pool }
+    }
+
+    pub fn emit(&mut self, effect: ParticleEffect, position: Position) {
+        match effect {
+            ParticleEffect::BrickDebris => self.emit_debris(position),
+            ParticleEffect::CoinSparkle => {
+                let sparkle = Particle::new(position, AnimationName::Sparkle, SPARKLE_LIFETIME);
+                self.add(sparkle);
+            }
+            ParticleEffect::StompPuff => {
+                let puff = Particle::new(position, AnimationName::Puff, PUFF_LIFETIME);
+                self.add(puff);
+            }
+        }
+    }
+
+    /// Four fragments thrown from the corners of the tile, spinning outward
+    fn emit_debris(&mut self, position: Position) {
+        let (top, bottom) = DEBRIS_JUMP;
+        let corners = [
+            (0., 0., -1., top),
+            (8., 0., 1., top),
+            (0., 8., -1., bottom),
+            (8., 8., 1., bottom),
+        ];
+        for (x, y, side, dy) in corners {
+            let position = Position::new(position.x() + x, position.y() + y);
+            let mut fragment = Particle::new(position, AnimationName::Debris, DEBRIS_LIFETIME);
+            fragment.dx = side * DEBRIS_SPEED;
+            fragment.dy = dy;
+            fragment.gravity = self.gravity.g;
+            fragment.direction = if side < 0. {
+                Direction::Left
+            } else {
+                Direction::Right
+            };
+            self.add(fragment);
+        }
+    }
+
+    fn add(&mut self, particle: Particle) {
+        if let Some(slot) = self.pool.iter_mut().find(|p| !p.is_alive()) {
+            *slot = particle;
+        } else if self.pool.len() < MAX_PARTICLES {
+            self.pool.push(particle);
+        } else if let Some(oldest) = self.pool.iter_mut().max_by(|a, b| a.age.total_cmp(&b.age)) {
+            *oldest = particle;
+        }
+    }
+
+    pub fn update(&mut self, dt: f64) {
+        for particle in self.pool.iter_mut().filter(|p| p.is_alive()) {
+            particle.update(dt);
+        }
+    }
+
+    pub fn alive(&self) -> impl Iterator<Item = &Particle> {
+        self.pool.iter().filter(|p| p.is_alive())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_throw_debris_then_reuse_them() {
+        let mut particles = Particles::new(GravityForce::new(1500.));
+        particles.emit(ParticleEffect::BrickDebris, Position::new(48., 160.));
+        assert_eq!(particles.alive().count(), 4);
+
+        // Fragments go up, spread, then fall
+        particles.update(0.1);
+        let (x, y) = particles.alive().next().unwrap().position();
+        assert!(x < 48. && y < 160.);
+        for _ in 0..6 {
+            particles.update(0.1);
+        }
+        let (_, y) = particles.alive().next().unwrap().position();
+        assert!(y > 160.);
+
+        particles.update(DEBRIS_LIFETIME);
+        assert_eq!(particles.alive().count(), 0);
+
+        // Dead particles are reused
+        particles.emit(ParticleEffect::StompPuff, Position::default());
+        assert_eq!(particles.alive().count(), 1);
+        assert_eq!(particles.pool.len(), 4);
+    }
+
+    #[test]
+    fn should_not_grow_over_capacity() {
+        let mut particles = Particles::new(GravityForce::default());
+        for _ in 0..MAX_PARTICLES {
+            particles.emit(ParticleEffect::CoinSparkle, Position::default());
+        }
+        particles.update(0.1);
+        particles.emit(ParticleEffect::BrickDebris, Position::default());
+
+        assert_eq!(particles.pool.len(), MAX_PARTICLES);
+        assert_eq!(particles.alive().count(), MAX_PARTICLES);
+    }
+}
@@ -0,0 +1,66 @@
+use std::f64::consts::PI;
+
+const BUMP_DURATION: f64 = 0.2;
+const BUMP_HEIGHT: f64 = 6.;
+
+/// A tile pushed up when hit from below
+struct TileBump {
+    layer: usize,
+    x: usize,
+    y: usize,
+    time: f64,
+}
+
+/// The tiles currently bumped, shared by the level and its backgrounds
+#[derive(Default)]
+pub struct TileBumps {
+    bumps: Vec<TileBump>,
+}
+
+impl TileBumps {
+    pub fn add(&mut self, layer: usize, x: usize, y: usize) {
+        self.bumps
+            .retain(|bump| (bump.layer, bump.x, bump.y) != (layer, x, y));
+        let time = 0.;
+        self.bumps.push(TileBump { layer, x, y, time });
+    }
+
+    pub fn update(&mut self, dt: f64) {
+        for bump in self.bumps.iter_mut() {
+            bump.time += dt;
+        }
+        self.bumps.retain(|bump| bump.time < BUMP_DURATION);
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.bumps.is_empty()
+    }
+
+    /// The vertical offset of the tile, negative while it goes up
+    pub fn offset(&self, layer: usize, x: usize, y: usize) -> f64 {
+        self.bumps
+            .iter()
+            .find(|bump| (bump.layer, bump.x, bump.y) == (layer, x, y))
+            .map(|bump| -BUMP_HEIGHT * (PI * bump.time / BUMP_DURATION).sin())
+            .unwrap_or(0.)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_move_up_then_back() {
+        let mut bumps = TileBumps::default();
+        bumps.add(0, 3, 10);
+
+        bumps.update(BUMP_DURATION / 2.);
+        assert_eq!(bumps.offset(0, 3, 10), -BUMP_HEIGHT);
+        assert_eq!(bumps.offset(1, 3, 10), 0.);
+
+        bumps.update(BUMP_DURATION / 2.);
+        assert!(bumps.is_empty());
+        assert_eq!(bumps.offset(0, 3, 10), 0.);
+    }
+}
@@ -1,27 +1,103 @@
 use std::cell::RefCell;
+use std::collections::HashMap;
 use std::rc::Rc;
 
-use crate::assets::levels::{TileData, TileType};
+use crate::assets::levels::{ChanceContent, TileData, TileType};
 use crate::assets::sprites::Sprite;
 use crate::assets::TILE_SIZE;
 use crate::entity::traits::obstruct;
-use crate::entity::{Entity, Living, ObstructionSide};
+use crate::entity::{Entity, EntityFeature, Living, ObstructionSide};
 use crate::events::EventBuffer;
 use crate::physics::bounding_box::BBox;
 use crate::physics::matrix::Matrix;
+use crate::physics::particles::ParticleEffect;
 use crate::physics::tile_resolver::TileResolver;
+use crate::physics::Position;
+
+const MULTI_COIN_COUNT: u32 = 10;
 
 pub struct TileCollider {
     resolvers: Vec<TileResolver>,
+    platforms: Vec<Rc<RefCell<Entity>>>,
+    /// The platform each entity stands on, by entity id
+    riders: HashMap<String, Rc<RefCell<Entity>>>,
 }
 
 impl TileCollider {
-    pub fn new(tiles: &[Rc<RefCell<Matrix<TileData>>>]) -> Self {
+    /// Only the layers scrolling with the camera collide, the backdrops are left out
+    pub fn new(tiles: &[Rc<RefCell<Matrix<TileData>>>], layers: &[usize]) -> Self {
         let resolvers = tiles
             .iter()
-            .map(|mat| TileResolver::new(mat.clone(), TILE_SIZE))
+            .enumerate()
+            .filter(|(layer, _)| layers.contains(layer))
+            .map(|(layer, mat)| TileResolver::new(layer, mat.clone(), TILE_SIZE))
             .collect();
-        Self { resolvers }
+        let platforms = vec![];
+        let riders = HashMap::new();
+
+        Self {
+            resolvers,
+            platforms,
+            riders,
+        }
+    }
+
+    /// A moving entity as solid as the ground
+    pub fn add_platform(&mut self, platform: Rc<RefCell<Entity>>) {
+        self.platforms.push(platform);
+    }
+
+    /// The entity left the level, as a platform or as a rider
+    pub fn remove_entity(&mut self, id: &str) {
+        self.platforms
+            .retain(|platform| platform.borrow().id() != id);
+        self.riders.remove(id);
+        self.riders
+            .retain(|_, platform| platform.borrow().id() != id);
+    }
+
+    /// The platform collides with the entities
+    #[cfg(test)]
+    pub fn is_platform(&self, platform_id: &str) -> bool {
+        self.platforms
+            .iter()
+            .any(|platform| platform.borrow().id() == platform_id)
+    }
+
+    /// Some entity stands on the platform
+    pub fn is_ridden(&self, platform_id: &str) -> bool {
+        self.riders
+            .values()
+            .any(|platform| platform.borrow().id() == platform_id)
+    }
+
+    /// Move the entity along with the living platform it stands on
+    pub fn carry(&self, entity: Rc<RefCell<Entity>>, dt: f64) {
+        let id = entity.borrow().id();
+        let platform = self
+            .riders
+            .get(&id)
+            .filter(|platform| platform.borrow().living() == Living::Alive);
+        if let Some(platform) = platform {
+            let (dx, dy) = (platform.borrow().dx(), platform.borrow().dy());
+            entity.borrow_mut().translate(dx * dt, dy * dt);
+        }
+    }
+
+    /// The living platforms overlapping the entity
+    fn platforms_under(&self, entity: &Rc<RefCell<Entity>>) -> Vec<Rc<RefCell<Entity>>> {
+        let id = entity.borrow().id();
+        let bbox = entity.borrow().collision_box();
+        self.platforms
+            .iter()
+            .filter(|platform| {
+                let platform = platform.borrow();
+                platform.living() == Living::Alive
+                    && platform.id() != id
+                    && platform.collision_box().overlaps(bbox)
+            })
+            .cloned()
+            .collect()
     }
 
     pub fn check_x(&mut self, entity: Rc<RefCell<Entity>>, event_buffer: Rc<RefCell<EventBuffer>>) {
@@ -47,6 +123,19 @@ impl TileCollider {
                 }
             }
         }
+
+        // The platform ridden is only solid from above
+        let riding = self.riders.get(&entity.borrow().id()).cloned();
+        for platform in self.platforms_under(&entity) {
+            if riding
+                .as_ref()
+                .is_some_and(|ridden| Rc::ptr_eq(ridden, &platform))
+            {
+                continue;
+            }
+            let rect = platform.borrow().collision_box();
+            TileType::handle_solid_x(entity.clone(), rect);
+        }
     }
 
     pub fn check_y(&mut self, entity: Rc<RefCell<Entity>>, event_buffer: Rc<RefCell<EventBuffer>>) {
@@ -73,6 +162,17 @@ impl TileCollider {
                 }
             }
         }
+
+        // Landing on a platform, the entity rides it until it leaves
+        let id = entity.borrow().id();
+        self.riders.remove(&id);
+        for platform in self.platforms_under(&entity) {
+            let rect = platform.borrow().collision_box();
+            TileType::handle_solid_y(entity.clone(), rect);
+            if dy > 0.0 {
+                self.riders.insert(id.clone(), platform);
+            }
+        }
     }
 }
 
@@ -100,9 +200,13 @@ impl TileType {
     ) {
         match self {
             TileType::Ground => TileType::handle_solid_y(entity, tile_data.rectangle()),
-            TileType::Brick => TileType::handle_brick_y(entity, tile_data, resolver),
-            TileType::BrickBroken => TileType::handle_brick_y(entity, tile_data, resolver),
+            TileType::Brick | TileType::BrickBroken => {
+                TileType::handle_brick_y(entity, tile_data, resolver, event_buffer)
+            }
             TileType::Coin => TileType::handle_coin(entity, tile_data, resolver, event_buffer),
+            TileType::Chance => {
+                TileType::handle_chance_y(entity, tile_data, resolver, event_buffer)
+            }
         }
     }
 
@@ -133,6 +237,7 @@ impl TileType {
         entity: Rc<RefCell<Entity>>,
         tile_data: &TileData,
         resolver: &mut TileResolver,
+        event_buffer: Rc<RefCell<EventBuffer>>,
     ) {
         if entity.borrow().living() != Living::Alive {
             return;
@@ -145,16 +250,88 @@ impl TileType {
                 obstruct(entity, ObstructionSide::Bottom, rect);
             }
         } else if dy < 0.0 && bbox.top() < rect.bottom() {
-            obstruct(entity, ObstructionSide::Top, rect);
+            obstruct(entity.clone(), ObstructionSide::Top, rect);
+            let id = entity.borrow().id();
+            let layer = resolver.layer();
+            event_buffer
+                .borrow_mut()
+                .bump(id, layer, tile_data.position());
             if tile_data.sprite() == Sprite::Brick {
                 let td = tile_data.replace_sprite(Sprite::BrickBroken);
                 resolver.update(td);
             } else {
+                let (x, y) = tile_data.position();
+                let position = Position::new((x * TILE_SIZE) as f64, (y * TILE_SIZE) as f64);
+                event_buffer
+                    .borrow_mut()
+                    .particles(ParticleEffect::BrickDebris, position);
                 resolver.remove(tile_data);
             }
         }
     }
 
+    fn handle_chance_y(
+        entity: Rc<RefCell<Entity>>,
+        tile_data: &TileData,
+        resolver: &mut TileResolver,
+        event_buffer: Rc<RefCell<EventBuffer>>,
+    ) {
+        if entity.borrow().living() != Living::Alive {
+            return;
+        }
+        let bbox = entity.borrow().collision_box();
+        let dy = entity.borrow().dy();
+        let rect = tile_data.rectangle();
+        if dy > 0.0 {
+            if bbox.bottom() > rect.top() {
+                obstruct(entity, ObstructionSide::Bottom, rect);
+            }
+        } else if dy < 0.0 && bbox.top() < rect.bottom() {
+            obstruct(entity.clone(), ObstructionSide::Top, rect);
+            let player = entity.borrow().is(EntityFeature::Player);
+            if player && tile_data.sprite() != Sprite::ChanceSpent {
+                TileType::release_chance(entity, tile_data, resolver, event_buffer);
+            }
+        }
+    }
+
+    fn release_chance(
+        entity: Rc<RefCell<Entity>>,
+        tile_data: &TileData,
+        resolver: &mut TileResolver,
+        event_buffer: Rc<RefCell<EventBuffer>>,
+    ) {
+        // Items appear above the tile
+        let content = tile_data.content();
+        if let Some(mob) = content.mob() {
+            let (x, y) = tile_data.position();
+            let x = (x * TILE_SIZE) as f64;
+            let y = (y as f64 - 1.) * TILE_SIZE as f64;
+            event_buffer.borrow_mut().spawn(mob, Position::new(x, y));
+        } else {
+            let (x, y) = tile_data.position();
+            let x = (x * TILE_SIZE) as f64 + 4.;
+            let y = (y as f64 - 1.) * TILE_SIZE as f64;
+            let mut event_buffer = event_buffer.borrow_mut();
+            event_buffer.coin(entity.borrow().id(), 1);
+            event_buffer.particles(ParticleEffect::CoinSparkle, Position::new(x, y));
+        }
+
+        let id = entity.borrow().id();
+        let layer = resolver.layer();
+        event_buffer
+            .borrow_mut()
+            .bump(id, layer, tile_data.position());
+
+        let tile = tile_data.hit();
+        let spent = content != ChanceContent::MultiCoin || tile.hits() >= MULTI_COIN_COUNT;
+        if spent {
+            resolver.update(tile.replace_sprite(Sprite::ChanceSpent));
+        } else {
+            resolver.update(tile);
+        }
+    }
+
     fn handle_coin(
         entity: Rc<RefCell<Entity>>,
         tile_data: &TileData,
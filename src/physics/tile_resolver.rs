@@ -6,13 +6,22 @@ use crate::assets::levels::TileData;
 use crate::physics::matrix::Matrix;
 
 pub struct TileResolver {
+    layer: usize,
     tiles: Rc<RefCell<Matrix<TileData>>>,
     tile_size: u32,
 }
 
 impl TileResolver {
-    pub fn new(tiles: Rc<RefCell<Matrix<TileData>>>, tile_size: u32) -> Self {
-        Self { tiles, tile_size }
+    pub fn new(layer: usize, tiles: Rc<RefCell<Matrix<TileData>>>, tile_size: u32) -> Self {
+        Self {
+            layer,
+            tiles,
+            tile_size,
+        }
+    }
+
+    pub fn layer(&self) -> usize {
+        self.layer
     }
 
     pub fn index(tile_size: u32, value: f64) -> u32 {
@@ -0,0 +1,47 @@
+use std::rc::Rc;
+
+use crate::assets::font::Font;
+use crate::game::GameContext;
+use crate::layers::colors::ColorsLayer;
+use crate::scene::Scene;
+use crate::video::{Color, Renderer};
+
+pub struct GameOverScene {
+    count_down: f64,
+    color: ColorsLayer,
+    font: Rc<Font>,
+}
+
+impl GameOverScene {
+    pub fn new(font: Rc<Font>) -> Self {
+        let count_down = 4.;
+        let color = ColorsLayer::new(Color::BLACK);
+
+        Self {
+            count_down,
+            color,
+            font,
+        }
+    }
+}
+
+impl Scene for GameOverScene {
+    fn update_soft(&self, _context: &GameContext) {}
+
+    fn update(&mut self, context: &GameContext) {
+        if self.count_down < 0. {
+            return;
+        }
+        self.count_down -= context.dt();
+        if self.count_down < 0. {
+            context.emitter().borrow_mut().title();
+        }
+    }
+
+    fn draw(&mut self, renderer: &dyn Renderer) {
+        let size = self.font.size() as f64;
+        self.color.draw(renderer);
+        self.font
+            .print(renderer, "GAME OVER", size * 12., size * 14.);
+    }
+}
@@ -5,13 +5,13 @@ use std::cell::Cell;
 use std::fmt::Debug;
 use std::rc::Rc;
 
-use web_sys::AudioContext;
-
 use crate::assets::audio::MusicController;
-use crate::assets::levels::TriggerDefinition;
+use crate::assets::levels::{
+    EntityDefinition, SpawnDefinition, SpawnKind, TileData, TriggerDefinition, TriggerKind,
+};
 use crate::assets::sprites::SpriteSheet;
 use crate::assets::{Assets, TILE_SIZE};
-use crate::camera::Camera;
+use crate::camera::{Camera, CameraZone};
 use crate::entity::entity_drawable::DrawableEntity;
 use crate::entity::player::PlayerEntity;
 use crate::entity::player_env::PlayerEnv;
@@ -21,46 +21,91 @@ use crate::entity::trigger::TriggerEntity;
 use crate::entity::{create_mobs, finalize, Entity, EntityFeature, EntityToCreate, Living};
 use crate::events::{Event, EventBuffer};
 use crate::game::{GameContext, PlayerInfo};
-use crate::input::Keyboard;
+use crate::input::replay::{Recorder, Replay};
+use crate::input::{ActionHandler, Input};
 use crate::layers::backgrounds::BackgroundsLayer;
 use crate::layers::collision::CollisionLayer;
 use crate::layers::dashboard::Dashboard;
 use crate::layers::entity::EntityLayer;
+use crate::layers::particles::ParticlesLayer;
 use crate::layers::{Compositor, Drawable};
+use crate::physics::bounding_box::BBox;
 use crate::physics::entity_collider::EntityCollider;
+use crate::physics::matrix::Matrix;
+use crate::physics::particles::Particles;
+use crate::physics::tile_bumps::TileBumps;
 use crate::physics::tile_collider::TileCollider;
 use crate::physics::{GravityForce, Position, Size};
+use crate::scene::pause::{PauseEvent, PauseItem, PauseMenu, PauseOverlay};
+use crate::scene::save::SaveState;
 use crate::scene::Scene;
 use crate::utils::log;
+use crate::video::Renderer;
+
+const START_SPAWN: &str = "start";
+
+/// Layers are only created when the level is drawn,
+/// so a level can be simulated without any video
+enum LayerRequest {
+    Backgrounds(usize, Rc<RefCell<Matrix<TileData>>>, String),
+    Entity(Rc<RefCell<dyn DrawableEntity>>, String),
+    Collision(Rc<RefCell<dyn DrawableEntity>>),
+}
 
 pub struct Level {
     name: String,
     assets: Assets,
     size: Size,
     camera: Camera,
-    dashboard: Dashboard,
+    camera_zones: Vec<CameraZone>,
+    dashboard: Option<Dashboard>,
+    pause_overlay: Option<PauseOverlay>,
+    particles_layer: Option<ParticlesLayer>,
     compositor: Compositor,
+    layer_requests: Vec<LayerRequest>,
     entities: Vec<Rc<RefCell<dyn DrawableEntity>>>,
     respawn_entities: Vec<Rc<RefCell<dyn DrawableEntity>>>,
+    mobs: Vec<(String, Position, Rc<RefCell<Entity>>)>,
+    pipes: Vec<TriggerDefinition>,
     tile_collider: Rc<RefCell<TileCollider>>,
+    bumps: Rc<RefCell<TileBumps>>,
+    particles: Rc<RefCell<Particles>>,
     entity_collider: EntityCollider,
-    player_env: Option<Rc<RefCell<PlayerEnv>>>,
+    players: Vec<Rc<RefCell<PlayerEnv>>>,
     gravity: GravityForce,
     distance: Rc<Cell<f64>>,
+    frame: Rc<Cell<u32>>,
+    recording: Option<Rc<RefCell<Replay>>>,
+    recorders: Vec<Rc<RefCell<Recorder>>>,
+    pause_menu: Option<Rc<RefCell<PauseMenu>>>,
+    replay: Option<Replay>,
+    replayed: Cell<usize>,
     next_mob: u32,
     music_controller: Rc<MusicController>,
 }
 
 impl Level {
+    /// Create the level with the tiles as built
     pub fn new(level_name: &str, assets: Assets) -> Self {
+        assets.level(level_name).restore_tiles(&[]);
+        let mobs = assets.level(level_name).entities();
+        Level::with_mobs(level_name, assets, mobs)
+    }
+
+    /// Create the level with the tiles and mobs of the save
+    pub fn restore(save: &SaveState, assets: Assets) -> Self {
+        assets.level(save.level()).restore_tiles(save.tiles());
+        Level::with_mobs(save.level(), assets, save.mobs().to_vec())
+    }
+
+    fn with_mobs(level_name: &str, assets: Assets, mobs: Vec<EntityDefinition>) -> Self {
         let specs = assets.level(level_name);
         let config = assets.configuration();
 
         let name = String::from(level_name);
         let camera_size = config.view * TILE_SIZE;
         let camera = Camera::new(camera_size);
-
-        let dashboard = Dashboard::new(assets.font());
+        let camera_zones = specs.camera();
 
         let matrix = specs.tiles();
         let size = matrix.first().unwrap().borrow().size();
@@ -68,25 +113,39 @@ impl Level {
         let entities = vec![];
         let respawn_entities = vec![];
         let next_mob = 0;
+        let mobs_entities = vec![];
+        let pipes = vec![];
 
-        let tile_collider = Rc::new(RefCell::new(TileCollider::new(&matrix)));
-        let entity_collider = EntityCollider::default();
+        let tile_collider = Rc::new(RefCell::new(TileCollider::new(
+            &matrix,
+            &specs.camera_layers(),
+        )));
+        let bumps = Rc::default();
+        let entity_collider = EntityCollider::new(&config.coop);
         let gravity = GravityForce::new(specs.gravity().unwrap_or(config.gravity));
-        let player_env = None;
+        let particles = Rc::new(RefCell::new(Particles::new(gravity)));
+        let players = vec![];
         let distance = Rc::new(Cell::new(0.));
+        let frame = Rc::new(Cell::new(0));
+        let recording = None;
+        let recorders = vec![];
+        let pause_menu = None;
+        let replay = None;
+        let replayed = Cell::new(0);
 
         // Compositor & layers
-        let mut compositor = Compositor::default();
-        let bg_sprites = specs.bg_sprites();
-        for background_matrix in matrix.iter() {
-            let bg_layer = BackgroundsLayer::new(
-                config.view,
-                background_matrix.clone(),
-                bg_sprites.clone(),
-                distance.clone(),
-            );
-            compositor.add_layer(Rc::new(RefCell::new(bg_layer)));
-        }
+        let dashboard = None;
+        let pause_overlay = None;
+        let particles_layer = None;
+        let compositor = Compositor::default();
+        let bg_sprites = specs.sprite_sheet();
+        let layer_requests = matrix
+            .iter()
+            .enumerate()
+            .map(|(layer, tiles)| {
+                LayerRequest::Backgrounds(layer, tiles.clone(), String::from(bg_sprites))
+            })
+            .collect();
 
         let music_player = assets.music_player(specs.music());
         let music_controller = MusicController::new(music_player);
@@ -95,24 +154,38 @@ impl Level {
         let mut result = Self {
             name,
             camera,
+            camera_zones,
             dashboard,
+            pause_overlay,
+            particles_layer,
             size,
             compositor,
+            layer_requests,
             entities,
             respawn_entities,
+            mobs: mobs_entities,
+            pipes,
             next_mob,
             tile_collider,
+            bumps,
+            particles,
             entity_collider,
             gravity,
-            player_env,
+            players,
             distance,
+            frame,
+            recording,
+            recorders,
+            pause_menu,
+            replay,
+            replayed,
             assets,
             music_controller,
         };
 
         // Entities
-        for entity_def in specs.entities() {
-            result.create_mobs(entity_def.name(), entity_def.position());
+        for entity_def in mobs {
+            result.create_mob_from(&entity_def);
         }
 
         // Triggers
@@ -127,32 +200,239 @@ impl Level {
         self.name.as_str()
     }
 
+    /// The first player, the only one unless playing co-op
     pub fn find_player(&self) -> Option<Rc<RefCell<PlayerEnv>>> {
-        self.player_env.clone()
+        self.players.first().cloned()
+    }
+
+    pub fn find_player_named(&self, name: &str) -> Option<Rc<RefCell<PlayerEnv>>> {
+        self.players
+            .iter()
+            .find(|player| player.borrow().name() == name)
+            .cloned()
+    }
+
+    /// The height of the level, in pixels
+    pub fn height(&self) -> f64 {
+        (self.size.height * TILE_SIZE) as f64
+    }
+
+    pub fn players(&self) -> &[Rc<RefCell<PlayerEnv>>] {
+        &self.players
     }
 
     pub fn current_player(&self) -> PlayerInfo {
-        let player = self
-            .find_player()
-            .unwrap_or_else(|| panic!("No player found"));
-        let name = player.borrow().name();
-        let lives = player.borrow().lives().get();
-        let score = player.borrow().score().get();
-        let coins = player.borrow().coins().get();
+        self.find_player()
+            .unwrap_or_else(|| panic!("No player found"))
+            .borrow()
+            .info()
+    }
 
-        PlayerInfo::new(name.as_str(), lives, score, coins)
+    pub fn players_info(&self) -> Vec<PlayerInfo> {
+        self.players
+            .iter()
+            .map(|player| player.borrow().info())
+            .collect()
     }
 
-    pub fn start_or_resume(&mut self, player_info: &PlayerInfo, input: Rc<RefCell<Keyboard>>) {
-        let position = Position::new(8., 64.);
-        if let Some(player) = self.find_player() {
+    /// Place the player at the spawn point, the start of the level by default
+    pub fn start_or_resume(
+        &mut self,
+        player_info: &PlayerInfo,
+        spawn: Option<&str>,
+        input: Rc<RefCell<Input>>,
+    ) {
+        let (position, kind) = self.spawn_point(spawn);
+        let player = if let Some(player) = self.find_player() {
             log(&format!("Update player {:?}", player));
             player.borrow_mut().update_player(player_info, position);
             self.music_controller.play_theme();
-            input.borrow_mut().register(player);
+            player
         } else {
-            let player = self.create_player(player_info, position);
-            input.borrow_mut().register(player);
+            self.create_player(player_info, position)
+        };
+        if kind == SpawnKind::Pipe {
+            let width = self.pipe_width(position.x(), position.y());
+            player.borrow().exit_pipe(position, width);
+        }
+        self.record(player_info, spawn, player, input);
+    }
+
+    /// The partners play along the first player, each one with its own inputs
+    pub fn join(&mut self, partners: &[PlayerInfo], input: Rc<RefCell<Input>>) {
+        let (Some(first), Some(menu), Some(replay)) = (
+            self.find_player(),
+            self.pause_menu.clone(),
+            self.recording.clone(),
+        ) else {
+            return;
+        };
+        let (x, y) = first.borrow().position();
+        for (index, partner) in partners.iter().enumerate() {
+            let position = Position::new(x + ((index + 1) as u32 * TILE_SIZE) as f64, y);
+            self.create_player(partner, position);
+            replay.borrow_mut().add_partner(partner);
+        }
+
+        for (index, player) in self.players.iter().enumerate().skip(1) {
+            let inputs = PauseMenu::add_player(menu.clone(), player.clone());
+            let inputs = Rc::new(RefCell::new(inputs));
+            let recorder = Recorder::new(inputs, index, self.frame.clone(), replay.clone());
+            self.recorders.push(Rc::new(RefCell::new(recorder)));
+        }
+        let handlers: Vec<Rc<RefCell<dyn ActionHandler>>> = self
+            .recorders
+            .iter()
+            .map(|recorder| recorder.clone() as Rc<RefCell<dyn ActionHandler>>)
+            .collect();
+        input.borrow_mut().register_players(&handlers);
+    }
+
+    fn spawn_point(&self, name: Option<&str>) -> (Position, SpawnKind) {
+        let specs = self.assets.level(self.name());
+        let spawn = name
+            .and_then(|name| {
+                let spawn = specs.spawn(name);
+                if spawn.is_none() {
+                    log(&format!("No spawn point {} in <{}>", name, self.name()));
+                }
+                spawn
+            })
+            .or_else(|| specs.spawn(START_SPAWN));
+        match spawn {
+            Some(spawn) => (spawn.position(), spawn.kind()),
+            None => (
+                self.assets.configuration().player.position,
+                SpawnKind::Start,
+            ),
+        }
+    }
+
+    pub fn next_level(&self) -> Option<String> {
+        self.assets.level(self.name()).next()
+    }
+
+    /// The position of the last checkpoint on the left of x
+    pub fn checkpoint(&self, x: f64) -> Option<SpawnDefinition> {
+        self.assets.level(self.name()).checkpoint(x)
+    }
+
+    /// Move the player where it was saved
+    pub fn restore_player(&self, save: &SaveState) {
+        if let Some(player) = self.find_player() {
+            player
+                .borrow()
+                .update_player(save.player(), save.position());
+            // The saved position is the one of the player in its form
+            player.borrow().move_to(save.position());
+            player.borrow().time().set(save.time());
+        }
+    }
+
+    pub fn save(&self) -> Option<SaveState> {
+        let player = self.find_player()?;
+        let (x, y) = player.borrow().position();
+        let time = player.borrow().time().get();
+        let tiles = self.assets.level(self.name()).tile_changes();
+        let mobs = self
+            .mobs
+            .iter()
+            .filter(|(_, _, entity)| entity.borrow().living() == Living::Alive)
+            .map(|(name, origin, entity)| {
+                let (x, y) = entity.borrow().position();
+                EntityDefinition::new(name, Position::new(x, y)).with_origin(*origin)
+            })
+            .collect();
+
+        let save = SaveState::new(
+            self.name(),
+            &self.current_player(),
+            Position::new(x, y),
+            time,
+            tiles,
+            mobs,
+        );
+        Some(save)
+    }
+
+    /// The inputs are recorded before the pause menu, so a replay pauses too
+    fn record(
+        &mut self,
+        player_info: &PlayerInfo,
+        spawn: Option<&str>,
+        player: Rc<RefCell<PlayerEnv>>,
+        input: Rc<RefCell<Input>>,
+    ) {
+        let replay = Replay::new(self.name(), spawn, player_info);
+        let replay = Rc::new(RefCell::new(replay));
+        let pause_menu = Rc::new(RefCell::new(PauseMenu::new(player)));
+        let recorder = Recorder::new(pause_menu.clone(), 0, self.frame.clone(), replay.clone());
+        let recorder = Rc::new(RefCell::new(recorder));
+
+        self.recording = Some(replay);
+        self.recorders = vec![recorder.clone()];
+        self.pause_menu = Some(pause_menu);
+        input.borrow_mut().register(recorder);
+    }
+
+    fn is_paused(&self) -> bool {
+        self.pause_menu
+            .as_ref()
+            .is_some_and(|menu| menu.borrow().is_paused())
+    }
+
+    fn handle_pause(&mut self, context: &GameContext) {
+        let event = self
+            .pause_menu
+            .as_ref()
+            .and_then(|menu| menu.borrow_mut().take_event());
+        match event {
+            Some(PauseEvent::Paused) => self.music_controller.pause(),
+            Some(PauseEvent::Chosen(PauseItem::Resume)) => {
+                if let Some(menu) = &self.pause_menu {
+                    menu.borrow_mut().resume();
+                }
+                self.music_controller.resume();
+            }
+            Some(PauseEvent::Chosen(PauseItem::Restart)) => {
+                // With the player as it was at the start of the level
+                if let Some(replay) = self.recording() {
+                    let player = replay.player().clone();
+                    let spawn = replay.spawn().map(String::from);
+                    context
+                        .emitter()
+                        .borrow_mut()
+                        .goto(self.name(), player, spawn);
+                }
+            }
+            Some(PauseEvent::Chosen(PauseItem::Quit)) => context.emitter().borrow_mut().quit(),
+            None => {}
+        }
+    }
+
+    /// Number of updates since the level creation
+    pub fn frame(&self) -> u32 {
+        self.frame.get()
+    }
+
+    /// The inputs received by the players so far
+    pub fn recording(&self) -> Option<Replay> {
+        self.recording
+            .as_ref()
+            .map(|replay| replay.borrow().clone())
+    }
+
+    /// Play the inputs of the replay instead of waiting for the devices
+    pub fn play_replay(&mut self, replay: Replay) {
+        self.replay = Some(replay);
+        self.replayed.set(0);
+    }
+
+    /// The inputs go through the recorders, as the inputs of the devices
+    fn replay_inputs(&self) {
+        if let Some(replay) = &self.replay {
+            let next = replay.play(self.replayed.get(), self.frame(), &self.recorders);
+            self.replayed.set(next);
         }
     }
 
@@ -176,29 +456,60 @@ impl Level {
         let env = Rc::new(RefCell::new(player_env));
         self.entities.push(env.clone());
 
-        self.player_env = Some(env.clone());
+        self.players.push(env.clone());
 
         env
     }
 
-    pub fn create_mobs(&mut self, mob: &str, position: Position) -> Rc<RefCell<Entity>> {
-        let audio = self.assets.audio_board(mob);
-        let config = self.assets.configuration();
-        let mobs_default = config
-            .mobs
-            .get(mob)
-            .unwrap_or_else(|| panic!("No mobs configuration found for {}", mob));
+    pub fn create_mobs(&mut self, mob: &str, position: Position) -> Option<Rc<RefCell<Entity>>> {
+        self.create_mob_from(&EntityDefinition::new(mob, position))
+    }
+
+    /// The mob is built where it was created, then moved where it is now,
+    /// so a restored platform keeps its path
+    fn create_mob_from(&mut self, definition: &EntityDefinition) -> Option<Rc<RefCell<Entity>>> {
         self.next_mob += 1;
+        let mob = definition.name();
         let id = format!("{} #{}", mob, self.next_mob);
-        let physics = Physics::new(self.gravity, self.tile_collider.clone());
 
-        let entity = create_mobs(id, mob, mobs_default, position, physics, audio);
+        let origin = definition.origin();
+        let (sheet, entity) = self.build_mob(id, mob, origin)?;
         let result = entity.borrow().entity();
-        self.add_entity(mob, entity.clone());
+        let position = definition.position();
         result
+            .borrow_mut()
+            .translate(position.x() - origin.x(), position.y() - origin.y());
+        self.add_entity(sheet.as_str(), entity);
+        self.mobs.push((String::from(mob), origin, result.clone()));
+        Some(result)
+    }
+
+    /// Build a mob from its configuration, with the sprite sheet used to draw it,
+    /// the levels are checked when loaded so an unknown mob is only logged
+    pub fn build_mob(&self, id: String, mob: &str, position: Position) -> Option<EntityToCreate> {
+        let config = self.assets.configuration();
+        let Some(mobs_default) = config.mobs.get(mob) else {
+            log(&format!("No mobs configuration found for {}", mob));
+            return None;
+        };
+        let sheet = mobs_default
+            .sprites
+            .clone()
+            .unwrap_or_else(|| String::from(mob));
+        let audio = self.assets.audio_board(sheet.as_str());
+        let physics = Physics::new(self.gravity, self.tile_collider.clone());
+
+        let entity = create_mobs(id, mobs_default, position, physics, audio);
+        Some((sheet, entity))
     }
 
     fn create_trigger(&mut self, trigger: TriggerDefinition) {
+        // Pipes are entered by the player, see find_pipe
+        if let TriggerKind::Pipe = trigger.kind() {
+            self.pipes.push(trigger);
+            return;
+        }
+
         let entity = TriggerEntity::new(trigger);
         let entity = Rc::new(RefCell::new(entity));
 
@@ -207,26 +518,85 @@ impl Level {
         self.entities.push(entity);
     }
 
+    /// The warp pipe the bounding box is standing on
+    pub fn find_pipe(&self, bbox: BBox) -> Option<TriggerDefinition> {
+        self.pipes
+            .iter()
+            .find(|pipe| {
+                let (x, y) = pipe.position();
+                let (left, top) = (x as f64, y as f64);
+                (bbox.bottom() - top).abs() < 1.
+                    && bbox.left() >= left
+                    && bbox.right() <= left + self.pipe_width(left, top)
+            })
+            .cloned()
+    }
+
+    /// The width of the pipe whose top left corner is at the position, in pixels
+    fn pipe_width(&self, left: f64, top: f64) -> f64 {
+        let tile = TILE_SIZE as f64;
+        let (x, y) = ((left / tile) as usize, (top / tile) as usize);
+        let span = self.assets.level(self.name()).ground_span(x, y);
+        (span * TILE_SIZE as usize) as f64
+    }
+
     pub fn sprite_sheet(&self, name: &str) -> Rc<SpriteSheet> {
         self.assets.spite_sheet(name)
     }
 
     fn add_entity(&mut self, sheet: &str, entity: Rc<RefCell<dyn DrawableEntity>>) {
-        let sprites = self.sprite_sheet(sheet);
         self.entities.push(entity.clone());
         self.entity_collider.add_entity(entity.borrow().entity());
 
         self.collision_layer(entity.clone());
 
         // Entity
-        let layer = EntityLayer::new(entity, sprites);
-        self.compositor.add_layer(Rc::new(RefCell::new(layer)));
+        let request = LayerRequest::Entity(entity, String::from(sheet));
+        self.layer_requests.push(request);
     }
 
     fn collision_layer(&mut self, entity: Rc<RefCell<dyn DrawableEntity>>) {
         if self.assets.configuration().dev.show_collision {
-            let collision = CollisionLayer::new(entity);
-            self.compositor.add_layer(Rc::new(RefCell::new(collision)));
+            self.layer_requests.push(LayerRequest::Collision(entity));
+        }
+    }
+
+    fn create_layers(&mut self, renderer: &dyn Renderer) {
+        let view = self.assets.configuration().view;
+        for request in self.layer_requests.drain(..) {
+            let layer: Rc<RefCell<dyn Drawable>> = match request {
+                LayerRequest::Backgrounds(layer, tiles, sheet) => {
+                    let sprites = self.assets.spite_sheet(sheet.as_str());
+                    let distance = self.distance.clone();
+                    let bumps = self.bumps.clone();
+                    let parallax = self.assets.level(self.name.as_str()).parallax(layer);
+                    let layer = BackgroundsLayer::new(
+                        renderer, view, layer, tiles, sprites, distance, bumps,
+                    )
+                    .with_parallax(parallax);
+                    Rc::new(RefCell::new(layer))
+                }
+                LayerRequest::Entity(entity, sheet) => {
+                    let sprites = self.assets.spite_sheet(sheet.as_str());
+                    Rc::new(RefCell::new(EntityLayer::new(renderer, entity, sprites)))
+                }
+                LayerRequest::Collision(entity) => {
+                    Rc::new(RefCell::new(CollisionLayer::new(entity)))
+                }
+            };
+            self.compositor.add_layer(layer);
+        }
+
+        if self.particles_layer.is_none() {
+            let sprites = self.assets.spite_sheet("particles");
+            let particles = self.particles.clone();
+            self.particles_layer = Some(ParticlesLayer::new(particles, sprites));
+        }
+        if self.dashboard.is_none() {
+            self.dashboard = Some(Dashboard::new(self.assets.font()));
+        }
+        if self.pause_overlay.is_none() {
+            self.pause_overlay = Some(PauseOverlay::new(self.assets.font()));
         }
     }
 
@@ -235,6 +605,11 @@ impl Level {
         for entity in self.entities.iter() {
             let removed = entity.borrow().living() == Living::NoExistence;
             let respawnable = entity.borrow().is(EntityFeature::Player);
+            if removed {
+                self.tile_collider
+                    .borrow_mut()
+                    .remove_entity(entity.borrow().id().as_str());
+            }
             if removed && respawnable {
                 self.respawn_entities.push(entity.clone());
             } else if removed {
@@ -246,6 +621,8 @@ impl Level {
         // Remove
         self.entities
             .retain(|entity| entity.borrow().living() != Living::NoExistence);
+        self.mobs
+            .retain(|(_, _, entity)| entity.borrow().living() != Living::NoExistence);
     }
 
     fn respwan_entities(&mut self) {
@@ -263,22 +640,51 @@ impl Level {
         })
     }
 
-    fn focus_player(&mut self) {
-        if let Some(player_env) = self.player_env.clone() {
-            let width = self.size.width - 16;
-            let max_x = (width * TILE_SIZE) as f64;
+    fn focus_player(&mut self, dt: f64) {
+        let width = self.size.width - 16;
+        let max_x = (width * TILE_SIZE) as f64;
+        let height = self
+            .size
+            .height
+            .saturating_sub(self.camera.height() / TILE_SIZE);
+        let max_y = (height * TILE_SIZE) as f64;
 
-            let (x, _y) = player_env.borrow().position();
-            let shift = (TILE_SIZE * 6) as f64;
-            let cam_x = (x - shift).max(0.).min(max_x);
-            self.camera.set_x(cam_x);
+        // Players out of the level do not move the camera
+        let playing: Vec<_> = self
+            .players
+            .iter()
+            .filter(|player| player.borrow().living() != Living::NoExistence)
+            .collect();
+        let positions: Vec<_> = playing.iter().map(|p| p.borrow().position()).collect();
+        let mode = self
+            .camera
+            .focus(&self.camera_zones, &positions, (max_x, max_y), dt);
+
+        // In co-op, or when the camera does not follow back, nobody walks out of the view
+        if self.players.len() > 1 || mode.holds_players() {
+            let (left, _) = self.camera.position();
+            let right = left + self.camera.width() as f64;
+            for player in playing {
+                player.borrow().keep_in(left, right);
+            }
         }
     }
 
-    fn handle_level_event(&self, event: &Event) {
+    fn handle_level_event(&mut self, event: &Event) {
         match event {
             Event::TimeOk => self.music_controller.play_theme(),
             Event::Hurry => self.music_controller.play_hurry(),
+            Event::Timeout => {
+                for player in self.players.iter() {
+                    player.borrow().time_out();
+                }
+            }
+            Event::Spawn { mob, position } => {
+                self.create_mobs(mob, *position);
+            }
+            Event::Particles { effect, position } => {
+                self.particles.borrow_mut().emit(*effect, *position);
+            }
             _ => {}
         }
     }
@@ -289,6 +695,42 @@ impl Level {
         }
     }
 
+    /// Entities resting on a bumped tile are killed by the bumper
+    fn tiles_bumps(&self, event_buffer: Rc<RefCell<EventBuffer>>) {
+        let events = event_buffer.borrow_mut().drain_tile();
+        for event in events.iter() {
+            if let Event::Bump {
+                id,
+                layer,
+                position: (x, y),
+            } = event
+            {
+                self.bumps
+                    .borrow_mut()
+                    .add(*layer, *x as usize, *y as usize);
+
+                // In co-op, the partners are knocked only when players can bump each other
+                let players = self.assets.configuration().coop.bump;
+                let tile_size = Size::new(TILE_SIZE, TILE_SIZE);
+                let tile = BBox::new((x * TILE_SIZE) as f64, (y * TILE_SIZE) as f64, tile_size);
+                for entity in self.entities.iter() {
+                    let entity = entity.borrow().entity();
+                    let bbox = entity.borrow().collision_box();
+                    let resting = (bbox.bottom() - tile.top()).abs() < 1.
+                        && bbox.right() > tile.left()
+                        && bbox.left() < tile.right();
+                    let other = entity.borrow().id() != *id;
+                    let knocked = players || !entity.borrow().is(EntityFeature::Player);
+                    if resting && other && knocked && entity.borrow().living() == Living::Alive {
+                        event_buffer
+                            .borrow_mut()
+                            .kill(id.clone(), entity.borrow().id());
+                    }
+                }
+            }
+        }
+    }
+
     fn entities_collision(&self, event_emitter: Rc<RefCell<EventBuffer>>) {
         for entity in self.entities.iter() {
             // log(&format!("Check collision for {:?}", entity.borrow().id()));
@@ -313,13 +755,9 @@ impl Level {
         result
     }
 
-    fn entities_sounds(&self, audio_context: &AudioContext) {
+    fn entities_sounds(&self) {
         for entity in self.entities.iter() {
-            entity
-                .borrow()
-                .entity()
-                .borrow_mut()
-                .play_sounds(audio_context);
+            entity.borrow().entity().borrow_mut().play_sounds();
         }
     }
 }
@@ -332,16 +770,25 @@ impl Debug for Level {
 
 impl Scene for Level {
     fn update_soft(&self, context: &GameContext) {
+        // The replay drives the pause menu too
+        self.replay_inputs();
+        if self.is_paused() {
+            return;
+        }
         self.entities_updates(context);
+        self.tiles_bumps(context.emitter());
         self.entities_collision(context.emitter());
-        self.entities_sounds(context.audio_context());
+        self.entities_sounds();
         self.entities_tasks(context.emitter());
-
-        // Dashboard
-        self.dashboard.draw(context, self);
     }
 
     fn update(&mut self, context: &GameContext) {
+        // Frozen while paused
+        self.handle_pause(context);
+        if self.is_paused() {
+            return;
+        }
+
         // Entity remove / respawn / create
         self.remove_entities();
         self.respwan_entities();
@@ -349,12 +796,17 @@ impl Scene for Level {
             self.add_entity(key.as_str(), created.clone());
         }
 
+        // Tiles
+        self.bumps.borrow_mut().update(context.dt());
+        self.particles.borrow_mut().update(context.dt());
+
         // Level Distance
         let dist = self.distance.get() + 1000. * context.dt();
         self.distance.set(dist);
+        self.frame.set(self.frame.get() + 1);
 
         // Camera
-        self.focus_player();
+        self.focus_player(context.dt());
 
         // Level Events
         for event in context.emitter().borrow_mut().drain_level().iter() {
@@ -362,9 +814,22 @@ impl Scene for Level {
         }
     }
 
-    fn draw(&mut self, context: &GameContext) {
-        self.compositor.draw(context.video_context(), &self.camera);
-        self.dashboard.draw(context, self);
+    fn draw(&mut self, renderer: &dyn Renderer) {
+        self.create_layers(renderer);
+
+        self.compositor.draw(renderer, &self.camera);
+        // Particles above every entity, including the ones created later
+        if let Some(particles) = &mut self.particles_layer {
+            particles.draw(renderer, &self.camera);
+        }
+        if let Some(dashboard) = &self.dashboard {
+            dashboard.draw(renderer, self);
+        }
+        if let (Some(overlay), Some(menu)) = (&self.pause_overlay, &self.pause_menu) {
+            if menu.borrow().is_paused() {
+                overlay.draw(renderer, &menu.borrow());
+            }
+        }
     }
 
     fn pause(&mut self) {
@@ -372,3 +837,960 @@ impl Scene for Level {
         self.music_controller.pause();
     }
 }
+
+#[cfg(test)]
+mod golden;
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+    use crate::assets::config::Configuration;
+    use crate::assets::levels::LevelDefinition;
+    use crate::assets::sprites::Sprite;
+    use crate::entity::traits::power::PlayerForm;
+    use crate::input::{Action, ActionHandler, KeyState};
+    use crate::DELTA_TIME;
+
+    fn tiles(layer: &str, entities: &str) -> String {
+        level_json(layer, entities, "", "")
+    }
+
+    fn level_json(layer: &str, entities: &str, triggers: &str, spawns: &str) -> String {
+        format!(
+            r#"{{
+                "spriteSheet": "overworld",
+                "musicSheet": "overworld",
+                "patternSheet": "overworld-pattern",
+                "layers": [{{ "tiles": [
+                    {{ "name": "ground", "type": "ground", "ranges": [[0, 32, 13, 2]] }}
+                    {layer}
+                ] }}],
+                "entities": [{entities}],
+                "triggers": [{triggers}],
+                "spawns": [{spawns}]
+            }}"#
+        )
+    }
+
+    fn create_assets(json: &str) -> Assets {
+        let config: Configuration =
+            serde_json::from_str(include_str!("../../www/assets/config.json")).unwrap();
+        create_assets_with(json, config)
+    }
+
+    fn create_assets_with(json: &str, config: Configuration) -> Assets {
+        let definition: LevelDefinition = serde_json::from_str(json).unwrap();
+        let mut levels = HashMap::new();
+        levels.insert(String::from("test"), definition.build(&HashMap::new()));
+        Assets::headless(config, levels)
+    }
+
+    fn create_level(json: &str) -> (Level, GameContext) {
+        let assets = create_assets(json);
+        let input = Rc::default();
+        let mut level = Level::new("test", assets);
+        level.start_or_resume(&PlayerInfo::new("mario", 3, 0, 0), None, input);
+
+        let context = GameContext::new(Rc::default(), DELTA_TIME);
+        (level, context)
+    }
+
+    fn step(level: &mut Level, context: &GameContext, frames: usize) {
+        for _ in 0..frames {
+            level.update_soft(context);
+            level.update(context);
+            context.emitter().borrow_mut().clear();
+        }
+    }
+
+    /// Step and keep the system events
+    fn step_events(level: &mut Level, context: &GameContext, frames: usize) -> Vec<Event> {
+        let mut events = vec![];
+        for _ in 0..frames {
+            level.update_soft(context);
+            level.update(context);
+            events.append(&mut context.emitter().borrow_mut().drain_system());
+            context.emitter().borrow_mut().clear();
+        }
+        events
+    }
+
+    fn walk_under(level: &mut Level, context: &GameContext, x: f64) {
+        let player = level.find_player().unwrap();
+        player
+            .borrow_mut()
+            .handle(Action::MoveRight, KeyState::Pressed);
+        while player.borrow().position().0 < x {
+            step(level, context, 1);
+        }
+        player
+            .borrow_mut()
+            .handle(Action::MoveRight, KeyState::Released);
+        step(level, context, 60);
+    }
+
+    fn jump(level: &mut Level, context: &GameContext) {
+        let player = level.find_player().unwrap();
+        player.borrow_mut().handle(Action::Jump, KeyState::Pressed);
+        step(level, context, 60);
+        player.borrow_mut().handle(Action::Jump, KeyState::Released);
+    }
+
+    fn tile(level: &Level, x: usize, y: usize) -> Option<TileData> {
+        let spec = level.assets.level(level.name());
+        let tiles = spec.tiles();
+        let tile = tiles[0].borrow().get(x, y).copied();
+        tile
+    }
+
+    #[test]
+    fn should_collect_coin() {
+        let coin = r#", { "name": "coin", "type": "coin", "ranges": [[1, 12]] }"#;
+        let (mut level, context) = create_level(tiles(coin, "").as_str());
+
+        step(&mut level, &context, 60);
+
+        let player = level.current_player();
+        assert_eq!(player.coins(), 1);
+        assert!(tile(&level, 1, 12).is_none());
+    }
+
+    #[test]
+    fn should_break_brick() {
+        let brick = r#", { "name": "bricks", "type": "brick", "ranges": [[3, 10]] }"#;
+        let (mut level, context) = create_level(tiles(brick, "").as_str());
+
+        // Hit the brick from below
+        walk_under(&mut level, &context, 48.);
+        jump(&mut level, &context);
+        assert_eq!(tile(&level, 3, 10).unwrap().sprite(), Sprite::BrickBroken);
+
+        // Second hit remove the brick
+        jump(&mut level, &context);
+        assert!(tile(&level, 3, 10).is_none());
+
+        // Debris are still falling
+        assert_eq!(level.particles.borrow().alive().count(), 4);
+    }
+
+    #[test]
+    fn should_kill_mob_on_bumped_tile() {
+        let platform = r#", { "name": "bricks", "type": "brick", "ranges": [[4, 3, 10]] },
+            { "name": "ground", "type": "ground", "ranges": [[3, 9], [7, 9]] }"#;
+        let goomba = r#"{ "name": "goomba", "pos": { "x": 72, "y": 144 } }"#;
+        let (mut level, context) = create_level(tiles(platform, goomba).as_str());
+
+        walk_under(&mut level, &context, 80.);
+        jump(&mut level, &context);
+
+        let (_, _, goomba) = level
+            .mobs
+            .iter()
+            .find(|(mob, _, _)| mob == "goomba")
+            .unwrap();
+        assert_ne!(goomba.borrow().living(), Living::Alive);
+        assert_eq!(level.current_player().score(), 100);
+        assert!(level.bumps.borrow().is_empty());
+    }
+
+    #[test]
+    fn should_knock_partner_on_bumped_tile_when_players_bump() {
+        let platform = r#", { "name": "bricks", "type": "brick", "ranges": [[4, 3, 10]] },
+            { "name": "ground", "type": "ground", "ranges": [[3, 9], [7, 9]] }"#;
+        let json = tiles(platform, "");
+        for bump in [false, true] {
+            let mut config: Configuration =
+                serde_json::from_str(include_str!("../../www/assets/config.json")).unwrap();
+            config.coop.bump = bump;
+            let mut level = Level::new("test", create_assets_with(json.as_str(), config));
+            let mario = PlayerInfo::new("mario", 3, 0, 0);
+            level.start_or_resume(&mario, None, Rc::default());
+            level.join(&[PlayerInfo::new("luigi", 2, 0, 0)], Rc::default());
+            let context = GameContext::new(Rc::default(), DELTA_TIME);
+
+            // Luigi stands on the bricks, Mario hits them from below
+            let luigi = level.find_player_named("luigi").unwrap();
+            luigi.borrow().move_to(Position::new(72., 144.));
+            walk_under(&mut level, &context, 80.);
+            jump(&mut level, &context);
+
+            let alive = luigi.borrow().living() == Living::Alive;
+            assert_eq!(alive, !bump);
+            assert_eq!(luigi.borrow().lives().get() == 2, !bump);
+        }
+    }
+
+    #[test]
+    fn should_release_chance_content() {
+        let chance = r#", { "name": "chance", "type": "chance", "content": "mushroom",
+            "animation": "chance", "ranges": [[3, 10]] }"#;
+        let (mut level, context) = create_level(tiles(chance, "").as_str());
+
+        walk_under(&mut level, &context, 48.);
+        jump(&mut level, &context);
+        let spent = tile(&level, 3, 10).unwrap();
+        assert_eq!(spent.sprite(), Sprite::ChanceSpent);
+        assert_eq!(spent.animation(), None);
+        assert_eq!(level.mobs.len(), 1);
+        assert_eq!(level.mobs[0].0, "mushroom");
+
+        // A spent tile is only solid
+        jump(&mut level, &context);
+        assert_eq!(level.mobs.len(), 1);
+        assert_eq!(level.current_player().coins(), 0);
+    }
+
+    #[test]
+    fn should_release_chance_only_for_players() {
+        let chance = r#", { "name": "chance", "type": "chance", "content": "coin",
+            "ranges": [[3, 8, 11, 1]] }"#;
+        let mobs = r#"{ "name": "fire-flower", "pos": { "x": 8, "y": 192 } },
+            { "name": "fire-flower", "pos": { "x": 8, "y": 192 } }"#;
+        let (mut level, context) = create_level(tiles(chance, mobs).as_str());
+        step(&mut level, &context, 60);
+
+        // The fireball bounces under the tiles
+        let player = level.find_player().unwrap();
+        player.borrow_mut().handle(Action::Fire, KeyState::Pressed);
+        step(&mut level, &context, 90);
+
+        let spec = level.assets.level(level.name());
+        let tiles = spec.tiles();
+        for x in 3..11 {
+            let tile = tiles[0].borrow().get(x, 11).copied().unwrap();
+            assert_eq!(tile.sprite(), Sprite::Chance);
+        }
+        assert_eq!(level.current_player().coins(), 0);
+    }
+
+    #[test]
+    fn should_release_multiple_coins() {
+        let chance = r#", { "name": "chance", "type": "chance", "content": "multi-coin",
+            "ranges": [[3, 10]] }"#;
+        let (mut level, context) = create_level(tiles(chance, "").as_str());
+
+        walk_under(&mut level, &context, 48.);
+        jump(&mut level, &context);
+        jump(&mut level, &context);
+
+        assert_eq!(level.current_player().coins(), 2);
+        assert_eq!(tile(&level, 3, 10).unwrap().sprite(), Sprite::Chance);
+    }
+
+    #[test]
+    fn should_warp_through_pipe() {
+        let pipe = r#", { "name": "ground", "type": "ground", "ranges": [[0, 2, 11, 2]] }"#;
+        let trigger = r#"{ "type": "pipe", "name": "1-2", "pos": [0, 176], "spawn": "exit" }"#;
+        let spawn = r#"{ "name": "exit", "type": "pipe", "pos": { "x": 0, "y": 176 } }"#;
+        let (mut level, context) = create_level(level_json(pipe, "", trigger, spawn).as_str());
+        step(&mut level, &context, 60);
+
+        // Slide into the pipe
+        let player = level.find_player().unwrap();
+        player.borrow_mut().handle(Action::Down, KeyState::Pressed);
+        step(&mut level, &context, 20);
+        let (_, y) = player.borrow().position();
+        assert!(y > 160. && y < 176.);
+
+        let events = step_events(&mut level, &context, 60);
+        let spawn = events.into_iter().find_map(|event| match event {
+            Event::GotoLevel { level, spawn, .. } => {
+                assert_eq!(level, "1-2");
+                spawn
+            }
+            _ => None,
+        });
+        assert_eq!(spawn.as_deref(), Some("exit"));
+
+        // Come out of the pipe at the spawn point
+        let input = Rc::default();
+        level.start_or_resume(&level.current_player(), Some("exit"), input);
+        step(&mut level, &context, 60);
+        let (x, y) = player.borrow().position();
+        assert_eq!((x, y), (9., 160.));
+        player
+            .borrow_mut()
+            .handle(Action::MoveRight, KeyState::Pressed);
+        step(&mut level, &context, 10);
+        assert!(player.borrow().position().0 > x);
+    }
+
+    #[test]
+    fn should_warp_partner_through_wide_pipe() {
+        let pipe = r#", { "name": "ground", "type": "ground", "ranges": [[4, 3, 11, 2]] }"#;
+        let trigger = r#"{ "type": "pipe", "name": "1-2", "pos": [64, 176], "spawn": "exit" }"#;
+        let spawns = r#"{ "name": "start", "pos": { "x": 80, "y": 144 } },
+            { "name": "exit", "type": "pipe", "pos": { "x": 64, "y": 176 } }"#;
+        let (mut level, context) = create_level(level_json(pipe, "", trigger, spawns).as_str());
+        level.join(&[PlayerInfo::new("luigi", 2, 0, 0)], Rc::default());
+        step(&mut level, &context, 60);
+
+        // Luigi goes down the pipe with his own info
+        let luigi = level.find_player_named("luigi").unwrap();
+        luigi.borrow_mut().handle(Action::Down, KeyState::Pressed);
+        let events = step_events(&mut level, &context, 90);
+        let player = events.iter().find_map(|event| match event {
+            Event::GotoLevel { player, .. } => Some(player),
+            _ => None,
+        });
+        assert_eq!(player.unwrap().name(), "luigi");
+
+        // Out of the middle of the pipe
+        let input = Rc::default();
+        level.start_or_resume(&level.current_player(), Some("exit"), input);
+        step(&mut level, &context, 60);
+        let (x, _) = level.find_player().unwrap().borrow().position();
+        assert_eq!(x, 64. + (48. - 14.) / 2.);
+    }
+
+    #[test]
+    fn should_complete_level_at_flagpole() {
+        let flagpole = r#"{ "name": "flagpole", "pos": { "x": 200, "y": 48 } }"#;
+        let (mut level, context) = create_level(level_json("", flagpole, "", "").as_str());
+        step(&mut level, &context, 60);
+
+        let player = level.find_player().unwrap();
+        player
+            .borrow_mut()
+            .handle(Action::MoveRight, KeyState::Pressed);
+        let events = step_events(&mut level, &context, 600);
+        assert!(events.iter().any(|event| matches!(event, Event::Title)));
+
+        // Grabbed at the bottom, then the remaining time is converted
+        let time = player.borrow().time().get();
+        let score = player.borrow().score().get();
+        assert_eq!(time, 0.);
+        assert!(score > 100);
+        assert_eq!((score - 100) % 50, 0);
+        assert!(player.borrow().position().0 >= 304.);
+    }
+
+    #[test]
+    fn should_go_next_level_with_player_grabbing_pole() {
+        // A wall stops the player on the way to the castle
+        let wall = r#", { "name": "ground", "type": "ground", "ranges": [[16, 1, 11, 2]] }"#;
+        let flagpole = r#"{ "name": "flagpole", "pos": { "x": 200, "y": 48 } }"#;
+        let json = level_json(wall, flagpole, "", "")
+            .replace(r#""spriteSheet""#, r#""next": "1-2", "spriteSheet""#);
+        let (mut level, context) = create_level(json.as_str());
+        level.join(&[PlayerInfo::new("luigi", 2, 0, 0)], Rc::default());
+        step(&mut level, &context, 60);
+
+        // Luigi goes alone to the flagpole
+        let luigi = level.find_player_named("luigi").unwrap();
+        luigi
+            .borrow_mut()
+            .handle(Action::MoveRight, KeyState::Pressed);
+        let events = step_events(&mut level, &context, 900);
+        let player = events.iter().find_map(|event| match event {
+            Event::GotoLevel { level, player, .. } if level == "1-2" => Some(player),
+            _ => None,
+        });
+        assert_eq!(player.unwrap().name(), "luigi");
+        assert!(luigi.borrow().position().0 < 256.);
+    }
+
+    #[test]
+    fn should_freeze_while_paused() {
+        let (mut level, context) = create_level(tiles("", "").as_str());
+        let menu = level.pause_menu.clone().unwrap();
+        step(&mut level, &context, 10);
+
+        menu.borrow_mut()
+            .handle(Action::MoveRight, KeyState::Pressed);
+        menu.borrow_mut().handle(Action::Pause, KeyState::Pressed);
+        step(&mut level, &context, 30);
+        let position = level.find_player().unwrap().borrow().position();
+        assert_eq!(level.frame(), 10);
+
+        // Resume, the player was stopped by the pause
+        menu.borrow_mut().handle(Action::Pause, KeyState::Pressed);
+        step(&mut level, &context, 30);
+        assert!(level.frame() > 10);
+        assert!(level.find_player().unwrap().borrow().position().0 <= position.0 + 1.);
+
+        // Restart from the pause menu
+        menu.borrow_mut().handle(Action::Pause, KeyState::Pressed);
+        menu.borrow_mut().handle(Action::Down, KeyState::Pressed);
+        menu.borrow_mut().handle(Action::Jump, KeyState::Pressed);
+        let events = step_events(&mut level, &context, 1);
+        assert!(events
+            .iter()
+            .any(|event| matches!(event, Event::GotoLevel { level, .. } if level == "test")));
+
+        // Quit from the pause menu, the game can be continued
+        menu.borrow_mut().handle(Action::Down, KeyState::Pressed);
+        menu.borrow_mut().handle(Action::Jump, KeyState::Pressed);
+        let events = step_events(&mut level, &context, 1);
+        assert!(events.iter().any(|event| matches!(event, Event::Quit)));
+        assert!(level.save().is_some());
+    }
+
+    #[test]
+    fn should_respawn_at_checkpoint() {
+        let spawns = r#"{ "name": "start", "pos": { "x": 24, "y": 160 } },
+            { "name": "middle", "type": "checkpoint", "pos": { "x": 160, "y": 160 } }"#;
+        let (mut level, context) = create_level(level_json("", "", "", spawns).as_str());
+        let player = level
+            .entities
+            .iter()
+            .find(|entity| entity.borrow().is(EntityFeature::Player))
+            .unwrap()
+            .clone();
+        assert_eq!(player.borrow().position(), (24., 160.));
+
+        walk_under(&mut level, &context, 200.);
+        context
+            .emitter()
+            .borrow_mut()
+            .kill(String::from("test"), player.borrow().id());
+        step(&mut level, &context, 1);
+        assert_eq!(player.borrow().living(), Living::Dead);
+
+        // Restart the level at the checkpoint with a life less
+        let events = step_events(&mut level, &context, 240);
+        let restart = events.into_iter().find_map(|event| match event {
+            Event::PlayerDied {
+                level,
+                player,
+                spawn,
+            } => Some((level, player, spawn)),
+            _ => None,
+        });
+        let (name, player_info, spawn) = restart.unwrap();
+        assert_eq!(name, "test");
+        assert_eq!(player_info.lives(), 2);
+        assert_eq!(spawn.as_deref(), Some("middle"));
+
+        let input = Rc::default();
+        level.start_or_resume(&player_info, spawn.as_deref(), input);
+        step(&mut level, &context, 1);
+        assert_eq!(player.borrow().living(), Living::Alive);
+        assert_eq!(player.borrow().position(), (160., 160.));
+    }
+
+    #[test]
+    fn should_die_when_time_is_out() {
+        let (mut level, context) = create_level(tiles("", "").as_str());
+        let player = level.find_player().unwrap();
+        player.borrow().time().set(0.5);
+
+        step(&mut level, &context, 30);
+        let entity = level
+            .entities
+            .iter()
+            .find(|entity| entity.borrow().is(EntityFeature::Player))
+            .unwrap();
+        assert_eq!(entity.borrow().living(), Living::Dead);
+        assert_eq!(player.borrow().time().get(), 0.);
+        assert_eq!(player.borrow().lives().get(), 2);
+    }
+
+    #[test]
+    fn should_die_without_lives() {
+        let (mut level, context) = create_level(tiles("", "").as_str());
+        let input = Rc::default();
+        level.start_or_resume(&PlayerInfo::new("mario", 1, 0, 0), None, input);
+        let player = level.find_player().unwrap();
+        let id = player.borrow().name();
+
+        context
+            .emitter()
+            .borrow_mut()
+            .kill(String::from("test"), id);
+        let events = step_events(&mut level, &context, 240);
+        let died = events.iter().find_map(|event| match event {
+            Event::PlayerDied { player, .. } => Some(player.lives()),
+            _ => None,
+        });
+        assert_eq!(died, Some(0));
+        assert_eq!(player.borrow().lives().get(), 0);
+    }
+
+    #[test]
+    fn should_play_coop() {
+        let (mut level, context) = create_level(tiles("", "").as_str());
+        let input = Rc::default();
+        level.join(&[PlayerInfo::new("luigi", 2, 0, 0)], input);
+        step(&mut level, &context, 60);
+
+        let mario = level.find_player().unwrap();
+        let luigi = level.find_player_named("luigi").unwrap();
+        let luigi_x = luigi.borrow().position().0;
+        assert!(mario.borrow().position().0 < luigi_x);
+
+        // Mario bumps into Luigi
+        mario
+            .borrow_mut()
+            .handle(Action::MoveRight, KeyState::Pressed);
+        step(&mut level, &context, 60);
+        assert!(luigi.borrow().position().0 > luigi_x);
+        assert!(mario.borrow().position().0 < luigi.borrow().position().0);
+
+        // Luigi comes back next to Mario with a life less
+        let id = luigi.borrow().name();
+        context
+            .emitter()
+            .borrow_mut()
+            .kill(String::from("test"), id);
+        let events = step_events(&mut level, &context, 240);
+        assert!(!events
+            .iter()
+            .any(|event| matches!(event, Event::PlayerDied { .. })));
+        assert_eq!(luigi.borrow().living(), Living::Alive);
+        assert_eq!(luigi.borrow().lives().get(), 1);
+    }
+
+    #[test]
+    fn should_stomp_goomba() {
+        let goomba = r#"{ "name": "goomba", "pos": { "x": 8, "y": 192 } }"#;
+        let (mut level, context) = create_level(tiles("", goomba).as_str());
+
+        step(&mut level, &context, 60);
+
+        let goomba = level
+            .entities
+            .iter()
+            .find(|entity| entity.borrow().id().starts_with("goomba"))
+            .unwrap();
+        assert_ne!(goomba.borrow().living(), Living::Alive);
+
+        let player = level.current_player();
+        assert!(player.score() > 0);
+        assert_eq!(player.lives(), 3);
+    }
+
+    #[test]
+    fn should_create_mob_variant_from_configuration() {
+        let goombas = r#"{ "name": "goomba", "pos": { "x": 400, "y": 192 } },
+            { "name": "fast-goomba", "pos": { "x": 400, "y": 160 } }"#;
+        let (mut level, context) = create_level(tiles("", goombas).as_str());
+
+        step(&mut level, &context, 60);
+
+        let (sheet, _) = level
+            .build_mob(String::from("test"), "fast-goomba", Position::default())
+            .unwrap();
+        assert_eq!(sheet, "goomba");
+        let distance = |name: &str| {
+            let (_, _, entity) = level.mobs.iter().find(|(mob, _, _)| mob == name).unwrap();
+            let (x, _) = entity.borrow().position();
+            (x - 400.).abs()
+        };
+        assert!(distance("goomba") > 0.);
+        assert!(distance("fast-goomba") > 1.5 * distance("goomba"));
+    }
+
+    fn player_height(level: &Level) -> u32 {
+        let player = level
+            .entities
+            .iter()
+            .find(|entity| entity.borrow().id() == "mario")
+            .unwrap();
+        let height = player.borrow().size().height;
+        height
+    }
+
+    #[test]
+    fn should_shrink_when_hurt_while_big() {
+        let mobs = r#"{ "name": "fire-flower", "pos": { "x": 8, "y": 192 } },
+            { "name": "goomba", "pos": { "x": 80, "y": 192 } }"#;
+        let (mut level, context) = create_level(tiles("", mobs).as_str());
+
+        step(&mut level, &context, 60);
+        assert_eq!(player_height(&level), 32);
+        assert_eq!(level.current_player().score(), 1000);
+
+        // Run into the goomba
+        let player = level.find_player().unwrap();
+        player
+            .borrow_mut()
+            .handle(Action::MoveRight, KeyState::Pressed);
+        step(&mut level, &context, 120);
+
+        assert_eq!(player_height(&level), 16);
+        assert_eq!(level.current_player().lives(), 3);
+    }
+
+    #[test]
+    fn should_keep_power_up_in_save_and_next_level() {
+        let pipe = r#", { "name": "ground", "type": "ground", "ranges": [[0, 2, 11, 2]] }"#;
+        let trigger = r#"{ "type": "pipe", "name": "1-2", "pos": [0, 176], "spawn": "exit" }"#;
+        let spawn = r#"{ "name": "exit", "type": "pipe", "pos": { "x": 0, "y": 176 } }"#;
+        let flower = r#"{ "name": "fire-flower", "pos": { "x": 0, "y": 120 } }"#;
+        let (mut level, context) = create_level(level_json(pipe, flower, trigger, spawn).as_str());
+        step(&mut level, &context, 60);
+        assert_eq!(player_height(&level), 32);
+
+        // Restored big, at the same place
+        let save = level.save().unwrap();
+        assert_eq!(save.player().form(), PlayerForm::Big);
+        let json = level_json(pipe, "", trigger, spawn);
+        let mut restored = Level::restore(&save, create_assets(json.as_str()));
+        restored.start_or_resume(save.player(), None, Rc::default());
+        restored.restore_player(&save);
+        assert_eq!(player_height(&restored), 32);
+        let (x, y) = restored.find_player().unwrap().borrow().position();
+        assert_eq!(Position::new(x, y), save.position());
+
+        // Through the pipe, to the next level
+        let player = level.find_player().unwrap();
+        player.borrow_mut().handle(Action::Down, KeyState::Pressed);
+        let events = step_events(&mut level, &context, 120);
+        let player = events.into_iter().find_map(|event| match event {
+            Event::GotoLevel { player, .. } => Some(player),
+            _ => None,
+        });
+        let player = player.unwrap();
+        assert_eq!(player.form(), PlayerForm::Big);
+
+        let mut next = Level::new("test", create_assets(json.as_str()));
+        next.start_or_resume(&player, Some("exit"), Rc::default());
+        step(&mut next, &context, 60);
+        assert_eq!(player_height(&next), 32);
+        assert_eq!(next.current_player().form(), PlayerForm::Big);
+    }
+
+    #[test]
+    fn should_respawn_small_in_coop() {
+        let flower = r#"{ "name": "fire-flower", "pos": { "x": 8, "y": 192 } }"#;
+        let (mut level, context) = create_level(tiles("", flower).as_str());
+        level.join(&[PlayerInfo::new("luigi", 2, 0, 0)], Rc::default());
+        step(&mut level, &context, 60);
+        assert_eq!(player_height(&level), 32);
+
+        // Mario comes back next to Luigi, without the power up
+        let mario = level.find_player().unwrap();
+        mario.borrow().time_out();
+        step(&mut level, &context, 240);
+        assert_eq!(mario.borrow().living(), Living::Alive);
+        assert_eq!(mario.borrow().lives().get(), 2);
+        assert_eq!(player_height(&level), 16);
+        assert_eq!(mario.borrow().info().form(), PlayerForm::Small);
+    }
+
+    #[test]
+    fn should_throw_fireball() {
+        let mobs = r#"{ "name": "fire-flower", "pos": { "x": 8, "y": 192 } },
+            { "name": "fire-flower", "pos": { "x": 8, "y": 192 } },
+            { "name": "goomba", "pos": { "x": 120, "y": 192 } }"#;
+        let (mut level, context) = create_level(tiles("", mobs).as_str());
+        step(&mut level, &context, 60);
+
+        let player = level.find_player().unwrap();
+        player.borrow_mut().handle(Action::Fire, KeyState::Pressed);
+        step(&mut level, &context, 90);
+
+        let (_, _, goomba) = level
+            .mobs
+            .iter()
+            .find(|(mob, _, _)| mob == "goomba")
+            .unwrap();
+        assert_ne!(goomba.borrow().living(), Living::Alive);
+        assert_eq!(player_height(&level), 32);
+    }
+
+    #[test]
+    fn should_replay_recorded_inputs() {
+        let coins = r#", { "name": "coin", "type": "coin", "ranges": [[6, 3, 11, 1]] }"#;
+        let goomba = r#"{ "name": "goomba", "pos": { "x": 96, "y": 192 } }"#;
+        let json = tiles(coins, goomba);
+
+        // Record
+        let (mut level, context) = create_level(json.as_str());
+        let recorder = level.recorders[0].clone();
+        let inputs = [
+            (10, Action::MoveRight, KeyState::Pressed),
+            (40, Action::Jump, KeyState::Pressed),
+            (70, Action::Jump, KeyState::Released),
+            (90, Action::Run, KeyState::Pressed),
+            (150, Action::MoveRight, KeyState::Released),
+        ];
+        for (frame, action, state) in inputs {
+            let frames = frame - level.frame() as usize;
+            step(&mut level, &context, frames);
+            recorder.borrow_mut().handle(action, state);
+        }
+        step(&mut level, &context, 60);
+        let json = level.recording().unwrap().to_json();
+
+        // Replay
+        let replay = Replay::from_json(json.as_str()).unwrap();
+        assert_eq!(replay.inputs().len(), inputs.len());
+        let (mut replayed, context) = create_level(tiles(coins, goomba).as_str());
+        replayed.play_replay(replay);
+        step(&mut replayed, &context, level.frame() as usize);
+
+        let expected = level.current_player();
+        let actual = replayed.current_player();
+        assert!(expected.coins() > 0);
+        assert_eq!(actual.score(), expected.score());
+        assert_eq!(actual.coins(), expected.coins());
+        assert_eq!(actual.lives(), expected.lives());
+        assert_eq!(
+            replayed.find_player().unwrap().borrow().position(),
+            level.find_player().unwrap().borrow().position()
+        );
+    }
+
+    #[test]
+    fn should_replay_partners_and_pause() {
+        let (mut level, context) = create_level(tiles("", "").as_str());
+        level.join(&[PlayerInfo::new("luigi", 2, 0, 0)], Rc::default());
+        let inputs = [
+            (10, 1, Action::MoveRight, KeyState::Pressed),
+            (20, 0, Action::Pause, KeyState::Pressed),
+            (20, 0, Action::Pause, KeyState::Pressed),
+            (40, 0, Action::MoveRight, KeyState::Pressed),
+            (60, 1, Action::Jump, KeyState::Pressed),
+        ];
+        for (frame, player, action, state) in inputs {
+            let frames = frame - level.frame() as usize;
+            step(&mut level, &context, frames);
+            level.recorders[player].borrow_mut().handle(action, state);
+        }
+        step(&mut level, &context, 60);
+        let replay = level.recording().unwrap();
+        assert_eq!(replay.partners().len(), 1);
+
+        // Replay
+        let (mut replayed, context) = create_level(tiles("", "").as_str());
+        replayed.join(replay.partners(), Rc::default());
+        replayed.play_replay(replay);
+        step(&mut replayed, &context, level.frame() as usize);
+
+        for (expected, actual) in level.players().iter().zip(replayed.players()) {
+            assert_eq!(expected.borrow().position(), actual.borrow().position());
+        }
+        assert_eq!(replayed.frame(), level.frame());
+    }
+
+    #[test]
+    fn should_restore_save() {
+        let coin = r#", { "name": "coin", "type": "coin", "ranges": [[1, 12]] }"#;
+        let goomba = r#"{ "name": "goomba", "pos": { "x": 160, "y": 192 } }"#;
+        let json = tiles(coin, goomba);
+        let (mut level, context) = create_level(json.as_str());
+        step(&mut level, &context, 60);
+        let json_save = level.save().unwrap().to_json();
+
+        // Restore on fresh assets
+        let save = SaveState::from_json(json_save.as_str()).unwrap();
+        let assets = create_assets(json.as_str());
+        let input = Rc::default();
+        let mut restored = Level::restore(&save, assets);
+        restored.start_or_resume(save.player(), None, input);
+        restored.restore_player(&save);
+
+        assert!(tile(&restored, 1, 12).is_none());
+        assert_eq!(restored.current_player().coins(), 1);
+        let player = restored.find_player().unwrap();
+        let (x, y) = player.borrow().position();
+        assert_eq!(Position::new(x, y), save.position());
+        assert_eq!(player.borrow().time().get(), save.time());
+        let mob = |level: &Level| level.mobs[0].2.borrow().position();
+        assert_eq!(restored.mobs.len(), 1);
+        assert_eq!(mob(&restored), mob(&level));
+        assert_eq!(restored.save().unwrap().to_json(), json_save);
+    }
+
+    #[test]
+    fn should_reject_invalid_save() {
+        let assets = create_assets(tiles("", "").as_str());
+        let player = PlayerInfo::new("mario", 3, 0, 0);
+        let position = Position::new(8., 64.);
+        let change =
+            |layer, x| format!(r#"{{ "layer": {layer}, "x": {x}, "y": 12, "sprite": null }}"#);
+        let save = |level: &str, tiles: &str, mobs: &str| {
+            let json = SaveState::new(level, &player, position, 300., vec![], vec![]).to_json();
+            let json = json
+                .replace(r#""tiles":[]"#, &format!(r#""tiles":[{tiles}]"#))
+                .replace(r#""mobs":[]"#, &format!(r#""mobs":[{mobs}]"#));
+            SaveState::from_json(json.as_str()).unwrap()
+        };
+        let goomba = r#"{ "name": "goomba", "pos": { "x": 160, "y": 192 } }"#;
+        assert_eq!(save("test", &change(0, 1), goomba).check(&assets), Ok(()));
+
+        let unknown = r#"{ "name": "bowser", "pos": { "x": 160, "y": 192 } }"#;
+        assert!(save("test", &change(1, 1), "").check(&assets).is_err());
+        assert!(save("test", &change(0, 999), "").check(&assets).is_err());
+        assert!(save("8-4", "", "").check(&assets).is_err());
+        assert!(save("test", "", unknown).check(&assets).is_err());
+    }
+
+    #[test]
+    fn should_migrate_save_before_backdrops() {
+        // A backdrop added in front of the layer of the save
+        let json = r#"{
+            "spriteSheet": "overworld",
+            "musicSheet": "overworld",
+            "patternSheet": "overworld-pattern",
+            "layers": [
+                { "scroll": 0.5, "repeat": 16, "tiles": [
+                    { "name": "ground", "ranges": [[1, 12]] }
+                ] },
+                { "tiles": [
+                    { "name": "ground", "type": "ground", "ranges": [[0, 32, 13, 2]] },
+                    { "name": "coin", "type": "coin", "ranges": [[1, 12]] }
+                ] }
+            ],
+            "entities": [],
+            "triggers": [],
+            "spawns": []
+        }"#;
+        let assets = create_assets(json);
+        let player = PlayerInfo::new("mario", 3, 1, 100);
+        let save = |tiles: &str| {
+            let json = SaveState::new(
+                "test",
+                &player,
+                Position::new(8., 64.),
+                300.,
+                vec![],
+                vec![],
+            )
+            .to_json()
+            .replace(r#""version":2"#, r#""version":1"#)
+            .replace(r#""tiles":[]"#, &format!(r#""tiles":[{tiles}]"#));
+            SaveState::from_json(json.as_str()).unwrap()
+        };
+
+        // The coin collected on the first layer of the version 1
+        let coin = r#"{ "layer": 0, "x": 1, "y": 12, "sprite": null }"#;
+        let migrated = save(coin).migrate(&assets).unwrap();
+        assert_eq!(migrated.check(&assets), Ok(()));
+        assert_eq!(migrated.tiles()[0].layer(), 1);
+        assert!(migrated.to_json().contains(r#""version":2"#));
+
+        let mut restored = Level::restore(&migrated, assets.clone());
+        restored.start_or_resume(migrated.player(), None, Rc::default());
+        let tiles = restored.assets.level("test").tiles();
+        assert!(tiles[0].borrow().get(1, 12).is_some());
+        assert!(tiles[1].borrow().get(1, 12).is_none());
+
+        // Only one layer in the version 1
+        let unknown = r#"{ "layer": 1, "x": 1, "y": 12, "sprite": null }"#;
+        assert!(save(unknown).migrate(&assets).is_err());
+    }
+
+    #[test]
+    fn should_not_collide_with_backdrop() {
+        let json = r#"{
+            "spriteSheet": "overworld",
+            "musicSheet": "overworld",
+            "patternSheet": "overworld-pattern",
+            "layers": [
+                { "scroll": 0.5, "tiles": [
+                    { "name": "ground", "type": "ground", "ranges": [[0, 32, 10, 1]] }
+                ] },
+                { "tiles": [
+                    { "name": "ground", "type": "ground", "ranges": [[0, 32, 13, 2]] }
+                ] }
+            ],
+            "entities": [],
+            "triggers": [],
+            "spawns": []
+        }"#;
+        let (mut level, context) = create_level(json);
+        step(&mut level, &context, 60);
+        let (_, y) = level.find_player().unwrap().borrow().position();
+        assert_eq!(y, 192.);
+    }
+
+    #[test]
+    fn should_scroll_down_tall_level() {
+        let json = r#"{
+            "spriteSheet": "overworld",
+            "musicSheet": "overworld",
+            "patternSheet": "overworld-pattern",
+            "layers": [{ "tiles": [
+                { "name": "ground", "type": "ground", "ranges": [[0, 4, 13, 2], [0, 32, 28, 2]] }
+            ] }],
+            "entities": [],
+            "triggers": [],
+            "spawns": []
+        }"#;
+        let (mut level, context) = create_level(json);
+        step(&mut level, &context, 60);
+        assert_eq!(level.camera.position(), (0., 32.));
+
+        // Down the ledge, at the bottom of the level
+        walk_under(&mut level, &context, 100.);
+        let (_, y) = level.find_player().unwrap().borrow().position();
+        assert_eq!(y, 432.);
+        assert_eq!(level.camera.position().1, 240.);
+    }
+
+    #[test]
+    fn should_carry_player_on_platform() {
+        let platform = r#"{ "name": "platform-horizontal", "pos": { "x": 0, "y": 120 } }"#;
+        let (mut level, context) = create_level(tiles("", platform).as_str());
+        let platform = |level: &Level| level.mobs[0].2.borrow().position();
+        let player = |level: &Level| level.find_player().unwrap().borrow().position();
+        step(&mut level, &context, 30);
+        let offset = player(&level).0 - platform(&level).0;
+
+        step(&mut level, &context, 30);
+        let (platform_x, platform_y) = platform(&level);
+        let (x, y) = player(&level);
+        assert!(platform_x > 20.);
+        assert!((y + 16. - platform_y).abs() < 1.);
+        assert!((x - platform_x - offset).abs() < 1.);
+    }
+
+    #[test]
+    fn should_fall_when_stepped_on() {
+        let platform = r#"{ "name": "platform-falling", "pos": { "x": 0, "y": 120 } }"#;
+        let (mut level, context) = create_level(tiles("", platform).as_str());
+        step(&mut level, &context, 10);
+        assert_eq!(level.mobs[0].2.borrow().position(), (0., 120.));
+
+        step(&mut level, &context, 30);
+        let (_, platform_y) = level.mobs[0].2.borrow().position();
+        let (_, y) = level.find_player().unwrap().borrow().position();
+        assert!(platform_y > 130.);
+        assert!((y + 16. - platform_y).abs() < 1.);
+
+        // Removed below the level, the player stays on the ground
+        let id = level.mobs[0].2.borrow().id();
+        let tile_collider = level.tile_collider.clone();
+        assert!(tile_collider.borrow().is_platform(&id));
+        assert!(tile_collider.borrow().is_ridden(&id));
+        step(&mut level, &context, 120);
+        assert!(level.mobs.is_empty());
+        assert!(!tile_collider.borrow().is_platform(&id));
+        assert!(!tile_collider.borrow().is_ridden(&id));
+        let (_, y) = level.find_player().unwrap().borrow().position();
+        assert_eq!(y, 192.);
+    }
+
+    #[test]
+    fn should_restore_platform_on_its_path() {
+        let platform = r#"{ "name": "platform-horizontal", "pos": { "x": 32, "y": 120 } }"#;
+        let json = tiles("", platform);
+        let (mut level, context) = create_level(json.as_str());
+        step(&mut level, &context, 60);
+        let save = level.save().unwrap();
+        assert_eq!(save.mobs()[0].origin(), Position::new(32., 120.));
+
+        let assets = create_assets(json.as_str());
+        let mut restored = Level::restore(&save, assets);
+        restored.start_or_resume(save.player(), None, Rc::default());
+        assert_eq!(
+            restored.mobs[0].2.borrow().position(),
+            level.mobs[0].2.borrow().position()
+        );
+
+        // Back and forth from the origin of the level
+        let (mut min_x, mut max_x) = (f64::MAX, f64::MIN);
+        for _ in 0..300 {
+            step(&mut restored, &context, 1);
+            let (x, _) = restored.mobs[0].2.borrow().position();
+            (min_x, max_x) = (min_x.min(x), max_x.max(x));
+        }
+        assert!((min_x - 32.).abs() < 1.);
+        assert!((max_x - 96.).abs() < 1.);
+    }
+}
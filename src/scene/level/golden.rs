@@ -0,0 +1,177 @@
+//! Golden-frame regression harness.
+//!
+//! Each `tests/golden/<name>.replay.json` is replayed on its level, and the state of the
+//! level is compared against `tests/golden/<name>.txt`.
+//! Run with `UPDATE_GOLDEN=1` to accept the new gameplay,
+//! and with `SCREENSHOTS=1` to keep the rendered frames in `target/screenshots`.
+use std::fmt::Write;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use super::*;
+use crate::input::replay::Replay;
+use crate::video::framebuffer::{Framebuffer, FramebufferRenderer};
+use crate::DELTA_TIME;
+
+/// Snapshot every N frames
+const SNAPSHOT_PERIOD: u32 = 30;
+/// Frames simulated after the last input
+const TRAILING_FRAMES: u32 = 120;
+
+fn root() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).to_path_buf()
+}
+
+fn load_level(replay: &Replay) -> (Level, GameContext) {
+    let assets = Assets::load_dir(root().join("www").as_path()).unwrap();
+    let mut level = Level::new(replay.level(), assets);
+    let input = Rc::default();
+    level.start_or_resume(replay.player(), replay.spawn(), input);
+    level.play_replay(replay.clone());
+
+    let context = GameContext::new(Rc::default(), DELTA_TIME);
+    (level, context)
+}
+
+fn snapshot(level: &Level, out: &mut String) {
+    let versions = level
+        .assets
+        .level(level.name())
+        .tiles()
+        .iter()
+        .map(|tiles| tiles.borrow().version().to_string())
+        .collect::<Vec<_>>()
+        .join(" ");
+    writeln!(out, "# frame {}", level.frame()).unwrap();
+    writeln!(out, "tiles {}", versions).unwrap();
+    for entity in level.entities.iter() {
+        let entity = entity.borrow();
+        let (x, y) = entity.position();
+        writeln!(
+            out,
+            "{:<16} {:>10.3} {:>10.3} {:?}",
+            entity.id(),
+            x,
+            y,
+            entity.living()
+        )
+        .unwrap();
+    }
+    let player = level.current_player();
+    writeln!(
+        out,
+        "player lives={} score={} coins={}",
+        player.lives(),
+        player.score(),
+        player.coins()
+    )
+    .unwrap();
+}
+
+fn run(replay: &Replay) -> String {
+    let (mut level, context) = load_level(replay);
+    let last_frame = replay.inputs().iter().map(|input| input.frame()).max();
+    let frames = last_frame.unwrap_or(0) + TRAILING_FRAMES;
+
+    let mut result = String::new();
+    while level.frame() <= frames {
+        if level.frame() % SNAPSHOT_PERIOD == 0 {
+            snapshot(&level, &mut result);
+        }
+        level.update_soft(&context);
+        level.update(&context);
+        context.emitter().borrow_mut().clear();
+    }
+    result
+}
+
+fn check(replay_path: &Path) -> Result<(), String> {
+    let json = fs::read_to_string(replay_path).unwrap();
+    let replay = Replay::from_json(json.as_str()).unwrap();
+    let actual = run(&replay);
+
+    let name = replay_path.file_name().unwrap().to_string_lossy();
+    let golden_path = replay_path.with_file_name(name.replace(".replay.json", ".txt"));
+    if std::env::var_os("UPDATE_GOLDEN").is_some() {
+        fs::write(&golden_path, actual).unwrap();
+        return Ok(());
+    }
+    if !golden_path.exists() {
+        return Err(format!(
+            "{} missing golden, run with UPDATE_GOLDEN=1",
+            golden_path.display()
+        ));
+    }
+
+    let expected = fs::read_to_string(&golden_path).unwrap();
+    let mismatch = expected
+        .lines()
+        .zip(actual.lines())
+        .enumerate()
+        .find(|(_, (expected, actual))| expected != actual);
+    match mismatch {
+        Some((index, (expected, actual))) => Err(format!(
+            "{} line {}\n  expected: {}\n    actual: {}",
+            golden_path.display(),
+            index + 1,
+            expected,
+            actual
+        )),
+        None if expected.lines().count() != actual.lines().count() => {
+            Err(format!("{} length differs", golden_path.display()))
+        }
+        None => Ok(()),
+    }
+}
+
+#[test]
+fn should_match_golden_frames() {
+    let mut replays = fs::read_dir(root().join("tests/golden"))
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.to_string_lossy().ends_with(".replay.json"))
+        .collect::<Vec<_>>();
+    replays.sort();
+    assert!(!replays.is_empty(), "No golden replay found");
+
+    let errors = replays
+        .iter()
+        .filter_map(|path| check(path).err())
+        .collect::<Vec<_>>();
+    assert!(
+        errors.is_empty(),
+        "Gameplay changed, run with UPDATE_GOLDEN=1 if expected:\n{}",
+        errors.join("\n")
+    );
+}
+
+#[test]
+fn should_render_frame_without_browser() {
+    let replay = Replay::new("1-1", None, &PlayerInfo::new("mario", 3, 0, 0));
+    let (mut level, context) = load_level(&replay);
+    level.update_soft(&context);
+    level.update(&context);
+
+    let view = level.assets.configuration().view * TILE_SIZE;
+    let renderer = FramebufferRenderer::new(view);
+    level.draw(&renderer);
+    let frame = renderer.framebuffer();
+
+    // Keep the screenshot for a visual check
+    let png = frame.encode_png().unwrap();
+    if std::env::var_os("SCREENSHOTS").is_some() {
+        let dir = root().join("target/screenshots");
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("1-1.png"), &png).unwrap();
+    }
+    assert_eq!(Framebuffer::decode_png(png.as_slice()).unwrap(), frame);
+
+    // Sky, ground and font are drawn
+    let sky = frame.pixel(0, 0);
+    let ground = frame.pixel(0, 14 * TILE_SIZE as i32);
+    assert_eq!(sky.a, 255);
+    assert_eq!(ground.a, 255);
+    assert_ne!(sky, ground);
+    let text = (8..16).flat_map(|y| (16..56).map(move |x| (x, y)));
+    assert!(text.into_iter().any(|(x, y)| frame.pixel(x, y) != sky));
+}
@@ -3,18 +3,32 @@ use std::rc::Rc;
 
 use crate::assets::Assets;
 use crate::game::{GameContext, PlayerInfo};
-use crate::input::Keyboard;
+use crate::input::keyboard::Keyboard;
+use crate::input::replay::Replay;
+use crate::input::Input;
+use crate::scene::game_over::GameOverScene;
 use crate::scene::level::Level;
+use crate::scene::options::OptionsScene;
+use crate::scene::save::SaveState;
+use crate::scene::title::{TitleScene, CHARACTERS};
+use crate::scene::turns::Turns;
 use crate::scene::wait_scene::WaitScene;
 use crate::utils::log;
+use crate::video::Renderer;
 
+pub mod game_over;
 pub mod level;
+pub mod options;
+pub mod pause;
+pub mod save;
+pub mod title;
+pub mod turns;
 pub mod wait_scene;
 
 pub trait Scene {
     fn update_soft(&self, context: &GameContext);
     fn update(&mut self, context: &GameContext);
-    fn draw(&mut self, context: &GameContext);
+    fn draw(&mut self, renderer: &dyn Renderer);
 
     fn pause(&mut self) {
         log("Pause");
@@ -23,26 +37,43 @@ pub trait Scene {
 
 pub struct SceneRunner {
     assets: Assets,
-    input: Rc<RefCell<Keyboard>>,
+    input: Rc<RefCell<Input>>,
+    keyboard: Rc<RefCell<Keyboard>>,
     current_index: Option<usize>,
     scenes: Vec<Rc<RefCell<dyn Scene>>>,
+    level: Option<Rc<RefCell<Level>>>,
+    turns: Turns,
+    coop: bool,
+    recording: Option<Replay>,
 }
 
 impl SceneRunner {
-    pub fn new(assets: Assets, input: Rc<RefCell<Keyboard>>) -> Self {
+    pub fn new(assets: Assets, input: Rc<RefCell<Input>>, keyboard: Rc<RefCell<Keyboard>>) -> Self {
         let current_index = None;
         let scenes = vec![];
+        let level = None;
+        let turns = Turns::default();
+        let coop = false;
+        let recording = None;
 
         Self {
             assets,
             input,
+            keyboard,
             current_index,
             scenes,
+            level,
+            turns,
+            coop,
+            recording,
         }
     }
 
-    fn create_level(&self, level: &str) -> Rc<RefCell<Level>> {
-        let level = Level::new(level, self.assets.clone());
+    fn create_level(&self, level: &str, save: Option<&SaveState>) -> Rc<RefCell<Level>> {
+        let level = match save {
+            Some(save) => Level::restore(save, self.assets.clone()),
+            None => Level::new(level, self.assets.clone()),
+        };
 
         Rc::new(RefCell::new(level))
     }
@@ -51,30 +82,211 @@ impl SceneRunner {
         self.current_index.and_then(|i| self.scenes.get(i)).cloned()
     }
 
-    pub fn run_level(&mut self, level_name: &str, player_info: &PlayerInfo) {
+    fn run_level(
+        &mut self,
+        level_name: &str,
+        player_info: &PlayerInfo,
+        spawn: Option<&str>,
+        save: Option<&SaveState>,
+    ) {
         // Pause current
         if let Some(current) = self.current() {
             current.borrow_mut().pause();
         }
+        self.keep_recording();
         self.scenes.clear();
         self.current_index = None;
 
         // Level
-        let level = self.create_level(level_name);
+        let level = self.create_level(level_name, save);
 
         // Progress
-        let progress = WaitScene::new(self.assets.font(), level.clone());
+        let show_turn = self.turns.count() > 1 && !self.coop;
+        let progress = WaitScene::new(self.assets.font(), level.clone(), show_turn);
         self.scenes.push(Rc::new(RefCell::new(progress)));
         self.scenes.push(level.clone());
+        self.level = Some(level.clone());
 
         // Player
         level
             .borrow_mut()
-            .start_or_resume(player_info, self.input.clone());
+            .start_or_resume(player_info, spawn, self.input.clone());
+        if let Some(save) = save {
+            level.borrow().restore_player(save);
+        }
+        if self.coop {
+            let partners = self.turns.partners();
+            level.borrow_mut().join(&partners, self.input.clone());
+        }
 
         self.run_next();
     }
 
+    /// Replace the scenes by a single one, not a level
+    fn run_scene(&mut self, scene: Rc<RefCell<dyn Scene>>) {
+        if let Some(current) = self.current() {
+            current.borrow_mut().pause();
+        }
+        self.keep_recording();
+        self.scenes.clear();
+        self.level = None;
+
+        self.scenes.push(scene);
+        self.current_index = Some(0);
+    }
+
+    /// The main menu, continue is offered when a game was saved
+    pub fn title(&mut self, can_continue: bool) {
+        let sprites = CHARACTERS
+            .iter()
+            .map(|name| self.assets.spite_sheet(name))
+            .collect();
+        let title = TitleScene::new(
+            self.assets.font(),
+            sprites,
+            self.input.clone(),
+            can_continue,
+        );
+        self.run_scene(Rc::new(RefCell::new(title)));
+    }
+
+    pub fn options(&mut self) {
+        let options = OptionsScene::new(
+            self.assets.font(),
+            self.keyboard.clone(),
+            self.input.clone(),
+        );
+        self.run_scene(Rc::new(RefCell::new(options)));
+    }
+
+    /// No more lives, the game over screen replaces the level
+    pub fn game_over(&mut self) {
+        let game_over = GameOverScene::new(self.assets.font());
+        self.run_scene(Rc::new(RefCell::new(game_over)));
+    }
+
+    pub fn new_game(&mut self, players: &[String], coop: bool) {
+        self.turns = Turns::new(players);
+        self.coop = coop;
+        if let Some(turn) = self.turns.current().cloned() {
+            self.run_level(turn.level(), turn.player(), turn.spawn(), None);
+        }
+    }
+
+    /// Play the level of the replay, the replay drives the players instead of the devices
+    pub fn replay(&mut self, replay: &Replay) -> Result<(), String> {
+        if self.assets.find_level(replay.level()).is_none() {
+            return Err(format!("Unknown level {}", replay.level()));
+        }
+        let mut players = vec![replay.player().clone()];
+        players.extend_from_slice(replay.partners());
+        if let Some(player) = players.iter().find(|p| !CHARACTERS.contains(&p.name())) {
+            return Err(format!("Unknown player {}", player.name()));
+        }
+        self.turns = Turns::replay(&players, replay.level(), replay.spawn());
+        self.coop = players.len() > 1;
+        self.run_level(replay.level(), replay.player(), replay.spawn(), None);
+        if let Some(level) = &self.level {
+            level.borrow_mut().play_replay(replay.clone());
+        }
+        self.input.borrow_mut().register_players(&[]);
+        Ok(())
+    }
+
+    /// Restore the level of the save, unless it does not match the assets
+    pub fn resume(&mut self, save: &SaveState) -> Result<(), String> {
+        let save = save.migrate(&self.assets)?;
+        save.check(&self.assets)?;
+        self.turns = Turns::resume(&save.turns(), save.player());
+        self.coop = save.coop();
+        self.run_level(save.level(), save.player(), None, Some(&save));
+        Ok(())
+    }
+
+    /// The current player goes to another level, along the partners in co-op
+    pub fn goto(&mut self, level_name: &str, player_info: &PlayerInfo, spawn: Option<&str>) {
+        if self.coop {
+            self.play_together(level_name, spawn);
+            return;
+        }
+        self.turns
+            .reach(level_name, player_info, spawn.map(String::from));
+        self.run_level(level_name, player_info, spawn, None);
+    }
+
+    /// The next player with lives left plays, the game is over without any
+    pub fn player_died(&mut self, level_name: &str, player_info: &PlayerInfo, spawn: Option<&str>) {
+        if self.coop {
+            self.play_together(level_name, spawn);
+            return;
+        }
+        let next = self
+            .turns
+            .died(level_name, player_info, spawn.map(String::from))
+            .cloned();
+        match next {
+            Some(turn) => self.run_level(turn.level(), turn.player(), turn.spawn(), None),
+            None => self.game_over(),
+        }
+    }
+
+    /// The players of the current level play the next one together
+    fn play_together(&mut self, level_name: &str, spawn: Option<&str>) {
+        let players = self
+            .level
+            .as_ref()
+            .map(|level| level.borrow().players_info())
+            .unwrap_or_default();
+        let next = self
+            .turns
+            .together(level_name, &players, spawn.map(String::from))
+            .cloned();
+        match next {
+            Some(turn) => self.run_level(turn.level(), turn.player(), turn.spawn(), None),
+            None => self.game_over(),
+        }
+    }
+
+    /// The level being played, with every player of the game
+    pub fn save(&self) -> Option<SaveState> {
+        let level = self.level.as_ref()?.borrow();
+        let save = level.save()?;
+        let players = if self.coop {
+            level.players_info()
+        } else {
+            vec![save.player().clone()]
+        };
+        let turns = self.turns.save(save.level(), &players);
+        Some(save.with_turns(turns, self.coop))
+    }
+
+    /// The inputs of the current level
+    pub fn recording(&self) -> Option<Replay> {
+        self.level
+            .as_ref()
+            .and_then(|level| level.borrow().recording())
+    }
+
+    /// The inputs of the last level left, once
+    pub fn take_recording(&mut self) -> Option<Replay> {
+        self.recording.take()
+    }
+
+    /// Keep the inputs of the level left, logged in dev
+    fn keep_recording(&mut self) {
+        let Some(replay) = self.recording() else {
+            return;
+        };
+        if self.assets.configuration().dev.record_inputs {
+            log(&format!(
+                "Replay of <{}>: {}",
+                replay.level(),
+                replay.to_json()
+            ));
+        }
+        self.recording = Some(replay);
+    }
+
     pub fn run_next(&mut self) {
         self.current_index = if let Some(i) = self.current_index {
             Some(i + 1)
@@ -87,7 +299,12 @@ impl SceneRunner {
         if let Some(scene) = self.current() {
             scene.borrow().update_soft(context);
             scene.borrow_mut().update(context);
-            scene.borrow_mut().draw(context);
+        }
+    }
+
+    pub fn draw(&self, renderer: &dyn Renderer) {
+        if let Some(scene) = self.current() {
+            scene.borrow_mut().draw(renderer);
         }
     }
 }
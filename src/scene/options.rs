@@ -0,0 +1,234 @@
+use core::cell::RefCell;
+use std::rc::Rc;
+
+use crate::assets::font::Font;
+use crate::game::GameContext;
+use crate::input::keyboard::{KeyCapture, Keyboard};
+use crate::input::{Action, ActionHandler, Input, KeyState};
+use crate::layers::colors::ColorsLayer;
+use crate::scene::Scene;
+use crate::video::{Color, Renderer};
+
+const ACTIONS: [Action; 9] = [
+    Action::MoveLeft,
+    Action::MoveRight,
+    Action::Up,
+    Action::Down,
+    Action::Jump,
+    Action::Run,
+    Action::Fire,
+    Action::Start,
+    Action::Pause,
+];
+/// The entry after the actions
+const BACK: usize = ACTIONS.len();
+
+/// Choose an action to rebind, or go back to the title.
+/// Left and right choose the player
+#[derive(Default)]
+struct OptionsMenu {
+    selected: usize,
+    chosen: Option<usize>,
+    player: usize,
+    players: usize,
+}
+
+impl OptionsMenu {
+    fn new(players: usize) -> Self {
+        Self {
+            players,
+            ..Self::default()
+        }
+    }
+
+    fn take_chosen(&mut self) -> Option<usize> {
+        self.chosen.take()
+    }
+}
+
+impl ActionHandler for OptionsMenu {
+    fn name(&self) -> String {
+        String::from("options")
+    }
+
+    fn handle(&mut self, action: Action, state: KeyState) {
+        if state != KeyState::Pressed {
+            return;
+        }
+        let count = BACK + 1;
+        match action {
+            Action::Up => self.selected = (self.selected + count - 1) % count,
+            Action::Down => self.selected = (self.selected + 1) % count,
+            Action::MoveLeft => self.player = (self.player + self.players - 1) % self.players,
+            Action::MoveRight => self.player = (self.player + 1) % self.players,
+            Action::Jump | Action::Start => self.chosen = Some(self.selected),
+            _ => {}
+        }
+    }
+}
+
+/// The keys of each action of a player, the next key pressed is bound to the chosen action
+pub struct OptionsScene {
+    font: Rc<Font>,
+    color: ColorsLayer,
+    keyboard: Rc<RefCell<Keyboard>>,
+    capture: Rc<RefCell<KeyCapture>>,
+    menu: Rc<RefCell<OptionsMenu>>,
+    rebinding: Option<(usize, Action)>,
+    message: Option<String>,
+}
+
+impl OptionsScene {
+    pub fn new(font: Rc<Font>, keyboard: Rc<RefCell<Keyboard>>, input: Rc<RefCell<Input>>) -> Self {
+        let color = ColorsLayer::new(Color::BLACK);
+        let capture = keyboard.borrow().key_capture();
+        let players = keyboard.borrow().players();
+        let menu = Rc::new(RefCell::new(OptionsMenu::new(players)));
+        input.borrow_mut().register(menu.clone());
+
+        Self {
+            font,
+            color,
+            keyboard,
+            capture,
+            menu,
+            rebinding: None,
+            message: None,
+        }
+    }
+
+    fn keys(&self, player: usize, action: Action) -> String {
+        let keymap = self.keyboard.borrow().keymap(player);
+        let mut keys: Vec<&str> = keymap
+            .iter()
+            .filter(|(_, a)| **a == action)
+            .map(|(key, _)| key.as_str())
+            .collect();
+        keys.sort();
+        keys.join(" ")
+    }
+
+    fn rebind(&mut self, player: usize, action: Action, key: &str, context: &GameContext) {
+        let bound = self.keyboard.borrow_mut().bind(player, action, key);
+        match bound {
+            Ok(()) => {
+                self.message = None;
+                let bindings = self.keyboard.borrow().bindings();
+                context.emitter().borrow_mut().keys_changed(bindings);
+            }
+            Err((other_player, other)) => {
+                let name = format!("{:?}", other).to_uppercase();
+                let message = if other_player == player {
+                    format!("{} USED BY {}", key, name)
+                } else {
+                    format!("{} USED BY P{} {}", key, other_player + 1, name)
+                };
+                self.message = Some(message);
+            }
+        }
+    }
+}
+
+impl Scene for OptionsScene {
+    fn update_soft(&self, _context: &GameContext) {}
+
+    fn update(&mut self, context: &GameContext) {
+        let chosen = self.menu.borrow_mut().take_chosen();
+
+        // Waiting for the key of the action
+        if let Some((player, action)) = self.rebinding {
+            let key = self.capture.borrow_mut().take();
+            if let Some(key) = key {
+                self.rebinding = None;
+                self.rebind(player, action, key.as_str(), context);
+            }
+            return;
+        }
+
+        match chosen {
+            Some(BACK) => context.emitter().borrow_mut().title(),
+            Some(index) => {
+                let player = self.menu.borrow().player;
+                self.rebinding = Some((player, ACTIONS[index]));
+                self.message = None;
+                self.capture.borrow_mut().start();
+            }
+            None => {}
+        }
+    }
+
+    fn draw(&mut self, renderer: &dyn Renderer) {
+        let size = self.font.size() as f64;
+        let (selected, player, players) = {
+            let menu = self.menu.borrow();
+            (menu.selected, menu.player, menu.players)
+        };
+        self.color.draw(renderer);
+
+        self.font.print(renderer, "CONTROLS", size * 12., size * 3.);
+        if players > 1 {
+            let title = format!("< PLAYER {} >", player + 1);
+            self.font
+                .print(renderer, title.as_str(), size * 10., size * 4.);
+        }
+        for (index, action) in ACTIONS.iter().enumerate() {
+            let y = size * (6 + 2 * index) as f64;
+            if index == selected {
+                self.font.print(renderer, ">", size, y);
+            }
+            let name = format!("{:?}", action).to_uppercase();
+            self.font.print(renderer, name.as_str(), size * 2., y);
+            let keys = match self.rebinding {
+                Some((_, rebinding)) if rebinding == *action => String::from("PRESS A KEY"),
+                _ => self.keys(player, *action),
+            };
+            self.font.print(renderer, keys.as_str(), size * 12., y);
+        }
+
+        let y = size * (6 + 2 * BACK) as f64;
+        if selected == BACK {
+            self.font.print(renderer, ">", size, y);
+        }
+        self.font.print(renderer, "BACK", size * 2., y);
+
+        if let Some(message) = &self.message {
+            self.font
+                .print(renderer, message.as_str(), size * 2., size * 27.);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_choose_action_or_back() {
+        let mut menu = OptionsMenu::new(1);
+        menu.handle(Action::Down, KeyState::Pressed);
+        menu.handle(Action::Jump, KeyState::Pressed);
+        assert_eq!(menu.take_chosen(), Some(1));
+        assert_eq!(ACTIONS[1], Action::MoveRight);
+
+        menu.handle(Action::Up, KeyState::Pressed);
+        menu.handle(Action::Up, KeyState::Pressed);
+        menu.handle(Action::Start, KeyState::Pressed);
+        assert_eq!(menu.take_chosen(), Some(BACK));
+    }
+
+    #[test]
+    fn should_choose_player() {
+        let mut menu = OptionsMenu::new(2);
+        menu.handle(Action::MoveRight, KeyState::Pressed);
+        assert_eq!(menu.player, 1);
+        menu.handle(Action::MoveRight, KeyState::Pressed);
+        assert_eq!(menu.player, 0);
+        menu.handle(Action::MoveLeft, KeyState::Pressed);
+        assert_eq!(menu.player, 1);
+
+        // Alone, always the first player
+        let mut menu = OptionsMenu::new(1);
+        menu.handle(Action::MoveLeft, KeyState::Pressed);
+        assert_eq!(menu.player, 0);
+    }
+}
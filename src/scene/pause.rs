@@ -0,0 +1,221 @@
+use core::cell::RefCell;
+use std::collections::HashSet;
+use std::rc::Rc;
+
+use crate::assets::font::Font;
+use crate::input::{Action, ActionHandler, KeyState};
+use crate::physics::Size;
+use crate::video::{Color, Renderer};
+
+const ITEMS: [PauseItem; 3] = [PauseItem::Resume, PauseItem::Restart, PauseItem::Quit];
+const SHADE: Color = Color::rgba(0, 0, 0, 160);
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum PauseItem {
+    Resume,
+    Restart,
+    Quit,
+}
+
+impl PauseItem {
+    fn label(&self) -> &str {
+        match self {
+            PauseItem::Resume => "RESUME",
+            PauseItem::Restart => "RESTART LEVEL",
+            PauseItem::Quit => "QUIT",
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum PauseEvent {
+    Paused,
+    Chosen(PauseItem),
+}
+
+/// Send the inputs to the player handlers, unless the game is paused,
+/// then the inputs drive the pause menu
+pub struct PauseMenu {
+    handlers: Vec<Rc<RefCell<dyn ActionHandler>>>,
+    paused: bool,
+    selected: usize,
+    pressed: HashSet<(usize, Action)>,
+    event: Option<PauseEvent>,
+}
+
+impl PauseMenu {
+    pub fn new(handler: Rc<RefCell<dyn ActionHandler>>) -> Self {
+        Self {
+            handlers: vec![handler],
+            paused: false,
+            selected: 0,
+            pressed: HashSet::new(),
+            event: None,
+        }
+    }
+
+    /// Another player in the level, its inputs go through the returned handler
+    pub fn add_player(
+        menu: Rc<RefCell<PauseMenu>>,
+        handler: Rc<RefCell<dyn ActionHandler>>,
+    ) -> PlayerInputs {
+        let index = menu.borrow().handlers.len();
+        menu.borrow_mut().handlers.push(handler);
+        PlayerInputs { menu, index }
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused
+    }
+
+    pub fn selected(&self) -> PauseItem {
+        ITEMS[self.selected]
+    }
+
+    pub fn take_event(&mut self) -> Option<PauseEvent> {
+        self.event.take()
+    }
+
+    pub fn resume(&mut self) {
+        self.paused = false;
+    }
+
+    fn pause(&mut self) {
+        // Keys held are released, the players do not move on resume
+        for (index, action) in self.pressed.drain() {
+            self.handlers[index]
+                .borrow_mut()
+                .handle(action, KeyState::Released);
+        }
+        self.paused = true;
+        self.selected = 0;
+        self.event = Some(PauseEvent::Paused);
+    }
+
+    fn choose(&mut self, item: PauseItem) {
+        self.event = Some(PauseEvent::Chosen(item));
+    }
+
+    fn handle_player(&mut self, index: usize, action: Action, state: KeyState) {
+        match (self.paused, action, state) {
+            (false, Action::Pause, KeyState::Pressed) => self.pause(),
+            (true, Action::Pause, KeyState::Pressed) => self.choose(PauseItem::Resume),
+            (true, Action::Up, KeyState::Pressed) => {
+                self.selected = (self.selected + ITEMS.len() - 1) % ITEMS.len();
+            }
+            (true, Action::Down, KeyState::Pressed) => {
+                self.selected = (self.selected + 1) % ITEMS.len();
+            }
+            (true, Action::Jump | Action::Start, KeyState::Pressed) => {
+                self.choose(self.selected());
+            }
+            (false, _, _) => {
+                match state {
+                    KeyState::Pressed => self.pressed.insert((index, action)),
+                    KeyState::Released => self.pressed.remove(&(index, action)),
+                };
+                self.handlers[index].borrow_mut().handle(action, state);
+            }
+            _ => {}
+        }
+    }
+}
+
+impl ActionHandler for PauseMenu {
+    fn name(&self) -> String {
+        self.handlers[0].borrow().name()
+    }
+
+    fn handle(&mut self, action: Action, state: KeyState) {
+        self.handle_player(0, action, state);
+    }
+}
+
+/// The inputs of another player, through the pause menu
+pub struct PlayerInputs {
+    menu: Rc<RefCell<PauseMenu>>,
+    index: usize,
+}
+
+impl ActionHandler for PlayerInputs {
+    fn name(&self) -> String {
+        self.menu.borrow().handlers[self.index].borrow().name()
+    }
+
+    fn handle(&mut self, action: Action, state: KeyState) {
+        self.menu
+            .borrow_mut()
+            .handle_player(self.index, action, state);
+    }
+}
+
+/// The pause menu drawn over the frozen level
+pub struct PauseOverlay {
+    font: Rc<Font>,
+}
+
+impl PauseOverlay {
+    pub fn new(font: Rc<Font>) -> Self {
+        Self { font }
+    }
+
+    pub fn draw(&self, renderer: &dyn Renderer, menu: &PauseMenu) {
+        let size = self.font.size() as f64;
+        let Size { width, height } = renderer.size();
+        renderer.fill_rect(SHADE, 0., 0., width as f64, height as f64);
+
+        self.font.print(renderer, "PAUSE", size * 13., size * 9.);
+        for (index, item) in ITEMS.iter().enumerate() {
+            let y = size * (13 + 2 * index) as f64;
+            if *item == menu.selected() {
+                self.font.print(renderer, ">", size * 8., y);
+            }
+            self.font.print(renderer, item.label(), size * 10., y);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::input::tests::Inputs;
+
+    #[test]
+    fn should_hold_inputs_while_paused() {
+        let inputs = Rc::new(RefCell::new(Inputs::default()));
+        let mut menu = PauseMenu::new(inputs.clone());
+
+        menu.handle(Action::MoveRight, KeyState::Pressed);
+        menu.handle(Action::Pause, KeyState::Pressed);
+        assert!(menu.is_paused());
+        assert_eq!(menu.take_event(), Some(PauseEvent::Paused));
+
+        // The held key is released, the menu inputs are not forwarded
+        menu.handle(Action::Down, KeyState::Pressed);
+        menu.handle(Action::Jump, KeyState::Pressed);
+        assert_eq!(inputs.borrow().pressed(), vec![Action::MoveRight]);
+        assert_eq!(inputs.borrow().released(), vec![Action::MoveRight]);
+        assert_eq!(
+            menu.take_event(),
+            Some(PauseEvent::Chosen(PauseItem::Restart))
+        );
+    }
+
+    #[test]
+    fn should_resume_with_pause_action() {
+        let inputs = Rc::new(RefCell::new(Inputs::default()));
+        let mut menu = PauseMenu::new(inputs.clone());
+
+        menu.handle(Action::Pause, KeyState::Pressed);
+        menu.handle(Action::Pause, KeyState::Pressed);
+        assert_eq!(
+            menu.take_event(),
+            Some(PauseEvent::Chosen(PauseItem::Resume))
+        );
+        menu.resume();
+
+        menu.handle(Action::Jump, KeyState::Pressed);
+        assert_eq!(inputs.borrow().pressed(), vec![Action::Jump]);
+        assert!(inputs.borrow().released().is_empty());
+    }
+}
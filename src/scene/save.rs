@@ -0,0 +1,189 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::assets::levels::{EntityDefinition, TileChange};
+use crate::assets::Assets;
+use crate::game::PlayerInfo;
+use crate::physics::Position;
+use crate::scene::title::CHARACTERS;
+use crate::scene::turns::Turn;
+
+/// Version of the save format, older saves are migrated, newer ones are rejected
+const SAVE_VERSION: u64 = 2;
+
+/// The state of a running level, to resume it after a reload
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct SaveState {
+    version: u64,
+    level: String,
+    player: PlayerInfo,
+    position: Position,
+    time: f64,
+    tiles: Vec<TileChange>,
+    mobs: Vec<EntityDefinition>,
+    /// Every player of the game, the older saves only have the current one
+    #[serde(default)]
+    turns: Vec<Turn>,
+    #[serde(default)]
+    coop: bool,
+}
+
+impl SaveState {
+    pub fn new(
+        level: &str,
+        player: &PlayerInfo,
+        position: Position,
+        time: f64,
+        tiles: Vec<TileChange>,
+        mobs: Vec<EntityDefinition>,
+    ) -> Self {
+        let level = String::from(level);
+        let player = player.clone();
+
+        Self {
+            version: SAVE_VERSION,
+            level,
+            player,
+            position,
+            time,
+            tiles,
+            mobs,
+            turns: vec![],
+            coop: false,
+        }
+    }
+
+    /// The save with all the players of the game
+    pub fn with_turns(self, turns: Vec<Turn>, coop: bool) -> Self {
+        Self {
+            turns,
+            coop,
+            ..self
+        }
+    }
+
+    pub fn from_json(json: &str) -> Result<Self, String> {
+        let value: Value = serde_json::from_str(json).map_err(|err| err.to_string())?;
+        match value.get("version").and_then(Value::as_u64) {
+            Some(1..=SAVE_VERSION) => serde_json::from_value(value).map_err(|err| err.to_string()),
+            Some(version) => Err(format!("Unsupported save version {}", version)),
+            None => Err(String::from("Missing save version")),
+        }
+    }
+
+    /// The save in the current version
+    pub fn migrate(&self, assets: &Assets) -> Result<Self, String> {
+        let mut result = self.clone();
+        if self.version < 2 {
+            // The version 1 predates the backdrops, its layers are the ones scrolling with the camera
+            let level = assets
+                .find_level(self.level())
+                .ok_or_else(|| format!("Unknown level {}", self.level))?;
+            let layers = level.camera_layers();
+            result.tiles = self
+                .tiles
+                .iter()
+                .map(|change| match layers.get(change.layer()) {
+                    Some(layer) => Ok(change.on_layer(*layer)),
+                    None => Err(format!("Invalid tile {:?}", change)),
+                })
+                .collect::<Result<_, _>>()?;
+        }
+        result.version = SAVE_VERSION;
+        Ok(result)
+    }
+
+    /// The save comes from the browser storage, check it against the assets before restoring
+    pub fn check(&self, assets: &Assets) -> Result<(), String> {
+        let level = assets
+            .find_level(self.level())
+            .ok_or_else(|| format!("Unknown level {}", self.level))?;
+        if !CHARACTERS.contains(&self.player.name()) {
+            return Err(format!("Unknown player {}", self.player.name()));
+        }
+        for turn in self.turns.iter() {
+            if !CHARACTERS.contains(&turn.player().name()) {
+                return Err(format!("Unknown player {}", turn.player().name()));
+            }
+            if assets.find_level(turn.level()).is_none() {
+                return Err(format!("Unknown level {}", turn.level()));
+            }
+        }
+        if let Some(change) = self.tiles.iter().find(|change| !level.contains(change)) {
+            return Err(format!("Invalid tile {:?}", change));
+        }
+        let mobs = assets.configuration().mobs;
+        if let Some(mob) = self.mobs.iter().find(|mob| !mobs.contains_key(mob.name())) {
+            return Err(format!("Unknown mob {}", mob.name()));
+        }
+        Ok(())
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).expect("Save should be serializable")
+    }
+
+    pub fn level(&self) -> &str {
+        self.level.as_str()
+    }
+    pub fn player(&self) -> &PlayerInfo {
+        &self.player
+    }
+    pub fn position(&self) -> Position {
+        self.position
+    }
+    pub fn time(&self) -> f64 {
+        self.time
+    }
+    pub fn tiles(&self) -> &[TileChange] {
+        self.tiles.as_slice()
+    }
+    pub fn mobs(&self) -> &[EntityDefinition] {
+        self.mobs.as_slice()
+    }
+    pub fn turns(&self) -> Vec<Turn> {
+        if self.turns.is_empty() {
+            vec![Turn::new(self.player.clone(), self.level(), None)]
+        } else {
+            self.turns.clone()
+        }
+    }
+    pub fn coop(&self) -> bool {
+        self.coop
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_reject_unknown_version() {
+        let player = PlayerInfo::new("mario", 3, 0, 0);
+        let save = SaveState::new("1-1", &player, Position::new(8., 64.), 300., vec![], vec![]);
+        let json = save.to_json();
+        assert_eq!(SaveState::from_json(json.as_str()).unwrap().level(), "1-1");
+
+        let json = json.replace("\"version\":2", "\"version\":42");
+        assert!(SaveState::from_json(json.as_str()).is_err());
+        assert!(SaveState::from_json("{}").is_err());
+    }
+
+    #[test]
+    fn should_keep_every_player() {
+        let mario = PlayerInfo::new("mario", 3, 0, 0);
+        let save = SaveState::new("1-2", &mario, Position::new(8., 64.), 300., vec![], vec![]);
+        // The older saves only have the current player
+        let turns = save.turns();
+        assert_eq!(turns.len(), 1);
+        assert_eq!(turns[0].level(), "1-2");
+
+        let luigi = Turn::new(PlayerInfo::new("luigi", 1, 0, 0), "1-1", None);
+        let turns = vec![Turn::new(mario, "1-2", None), luigi];
+        let json = save.with_turns(turns, true).to_json();
+        let save = SaveState::from_json(json.as_str()).unwrap();
+        assert!(save.coop());
+        assert_eq!(save.turns()[1].player().name(), "luigi");
+        assert_eq!(save.turns()[1].level(), "1-1");
+    }
+}
@@ -0,0 +1,213 @@
+use core::cell::RefCell;
+use std::rc::Rc;
+
+use crate::assets::font::Font;
+use crate::assets::sprites::{Sprite, SpriteSheet};
+use crate::entity::entity_display::EntityDisplay;
+use crate::game::GameContext;
+use crate::input::{Action, ActionHandler, Input, KeyState};
+use crate::layers::colors::ColorsLayer;
+use crate::scene::Scene;
+use crate::video::{Color, Renderer};
+
+pub const CHARACTERS: [&str; 2] = ["mario", "luigi"];
+const SKY: Color = Color::rgb(92, 148, 252);
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum MenuItem {
+    OnePlayer,
+    TwoPlayers,
+    CoOp,
+    Continue,
+    Options,
+}
+
+impl MenuItem {
+    fn label(&self) -> &str {
+        match self {
+            MenuItem::OnePlayer => "1 PLAYER GAME",
+            MenuItem::TwoPlayers => "2 PLAYER GAME",
+            MenuItem::CoOp => "2 PLAYER CO-OP",
+            MenuItem::Continue => "CONTINUE",
+            MenuItem::Options => "OPTIONS",
+        }
+    }
+}
+
+/// The entries of the title screen, driven by the keymap
+pub struct Menu {
+    items: Vec<MenuItem>,
+    selected: usize,
+    character: usize,
+    chosen: Option<MenuItem>,
+}
+
+impl Menu {
+    /// Continue is only offered when a game was saved
+    pub fn new(can_continue: bool) -> Self {
+        let mut items = vec![MenuItem::OnePlayer, MenuItem::TwoPlayers, MenuItem::CoOp];
+        if can_continue {
+            items.push(MenuItem::Continue);
+        }
+        items.push(MenuItem::Options);
+
+        Self {
+            items,
+            selected: 0,
+            character: 0,
+            chosen: None,
+        }
+    }
+
+    pub fn selected(&self) -> MenuItem {
+        self.items[self.selected]
+    }
+
+    /// The character of the first player
+    pub fn character(&self) -> &'static str {
+        CHARACTERS[self.character]
+    }
+
+    /// The players of a new game, the first one is the chosen character
+    pub fn players(&self, count: usize) -> Vec<String> {
+        (0..count)
+            .map(|i| CHARACTERS[(self.character + i) % CHARACTERS.len()])
+            .map(String::from)
+            .collect()
+    }
+
+    pub fn take_chosen(&mut self) -> Option<MenuItem> {
+        self.chosen.take()
+    }
+}
+
+impl ActionHandler for Menu {
+    fn name(&self) -> String {
+        String::from("menu")
+    }
+
+    fn handle(&mut self, action: Action, state: KeyState) {
+        if state != KeyState::Pressed {
+            return;
+        }
+        let count = self.items.len();
+        match action {
+            Action::Up => self.selected = (self.selected + count - 1) % count,
+            Action::Down => self.selected = (self.selected + 1) % count,
+            Action::MoveLeft | Action::MoveRight => {
+                self.character = (self.character + 1) % CHARACTERS.len();
+            }
+            Action::Jump | Action::Start => self.chosen = Some(self.selected()),
+            _ => {}
+        }
+    }
+}
+
+pub struct TitleScene {
+    font: Rc<Font>,
+    color: ColorsLayer,
+    menu: Rc<RefCell<Menu>>,
+    sprites: Vec<Rc<SpriteSheet>>,
+}
+
+impl TitleScene {
+    pub fn new(
+        font: Rc<Font>,
+        sprites: Vec<Rc<SpriteSheet>>,
+        input: Rc<RefCell<Input>>,
+        can_continue: bool,
+    ) -> Self {
+        let color = ColorsLayer::new(SKY);
+        let menu = Rc::new(RefCell::new(Menu::new(can_continue)));
+        input.borrow_mut().register(menu.clone());
+
+        Self {
+            font,
+            color,
+            menu,
+            sprites,
+        }
+    }
+}
+
+impl Scene for TitleScene {
+    fn update_soft(&self, _context: &GameContext) {}
+
+    fn update(&mut self, context: &GameContext) {
+        let mut menu = self.menu.borrow_mut();
+        if let Some(item) = menu.take_chosen() {
+            let emitter = context.emitter();
+            let mut emitter = emitter.borrow_mut();
+            match item {
+                MenuItem::OnePlayer => emitter.new_game(menu.players(1), false),
+                MenuItem::TwoPlayers => emitter.new_game(menu.players(2), false),
+                MenuItem::CoOp => emitter.new_game(menu.players(2), true),
+                MenuItem::Continue => emitter.resume(),
+                MenuItem::Options => emitter.options(),
+            }
+        }
+    }
+
+    fn draw(&mut self, renderer: &dyn Renderer) {
+        let size = self.font.size() as f64;
+        let menu = self.menu.borrow();
+        self.color.draw(renderer);
+
+        self.font
+            .print(renderer, "PLOP MARIO", size * 11., size * 6.);
+
+        for (index, item) in menu.items.iter().enumerate() {
+            let y = size * (12 + 2 * index) as f64;
+            if index == menu.selected {
+                self.font.print(renderer, ">", size * 8., y);
+            }
+            self.font.print(renderer, item.label(), size * 10., y);
+        }
+
+        // Chosen character
+        let character = format!("< {} >", menu.character().to_uppercase());
+        self.font
+            .print(renderer, character.as_str(), size * 11., size * 23.);
+        if let Some(sprites) = self.sprites.get(menu.character) {
+            EntityDisplay::sprite(Sprite::Idle).draw(renderer, size * 15., size * 25., sprites);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_navigate_menu() {
+        let mut menu = Menu::new(false);
+        assert_eq!(menu.selected(), MenuItem::OnePlayer);
+
+        menu.handle(Action::Up, KeyState::Pressed);
+        assert_eq!(menu.selected(), MenuItem::Options);
+        menu.handle(Action::Down, KeyState::Pressed);
+        menu.handle(Action::Down, KeyState::Pressed);
+        assert_eq!(menu.selected(), MenuItem::TwoPlayers);
+
+        // Released keys are ignored
+        menu.handle(Action::Down, KeyState::Released);
+        assert_eq!(menu.selected(), MenuItem::TwoPlayers);
+
+        menu.handle(Action::MoveRight, KeyState::Pressed);
+        assert_eq!(menu.character(), "luigi");
+        assert_eq!(menu.players(2), vec!["luigi", "mario"]);
+
+        menu.handle(Action::Start, KeyState::Pressed);
+        assert_eq!(menu.take_chosen(), Some(MenuItem::TwoPlayers));
+        assert_eq!(menu.take_chosen(), None);
+    }
+
+    #[test]
+    fn should_continue_only_with_save() {
+        let mut menu = Menu::new(true);
+        menu.handle(Action::Down, KeyState::Pressed);
+        menu.handle(Action::Down, KeyState::Pressed);
+        menu.handle(Action::Down, KeyState::Pressed);
+        assert_eq!(menu.selected(), MenuItem::Continue);
+    }
+}
@@ -0,0 +1,236 @@
+use serde::{Deserialize, Serialize};
+
+use crate::game::PlayerInfo;
+
+const START_LEVEL: &str = "1-1";
+const START_LIVES: u32 = 3;
+
+/// A player of the game, with the level reached
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Turn {
+    player: PlayerInfo,
+    level: String,
+    spawn: Option<String>,
+}
+
+impl Turn {
+    pub fn new(player: PlayerInfo, level: &str, spawn: Option<String>) -> Self {
+        let level = String::from(level);
+        Self {
+            player,
+            level,
+            spawn,
+        }
+    }
+
+    pub fn player(&self) -> &PlayerInfo {
+        &self.player
+    }
+    pub fn level(&self) -> &str {
+        self.level.as_str()
+    }
+    pub fn spawn(&self) -> Option<&str> {
+        self.spawn.as_deref()
+    }
+}
+
+/// The players take turns, the next one plays when the current one dies
+#[derive(Default)]
+pub struct Turns {
+    turns: Vec<Turn>,
+    current: usize,
+}
+
+impl Turns {
+    /// A new game, all the players start at the first level
+    pub fn new(names: &[String]) -> Self {
+        let turns = names
+            .iter()
+            .map(|name| PlayerInfo::new(name, START_LIVES, 0, 0))
+            .map(|player| Turn::new(player, START_LEVEL, None))
+            .collect();
+
+        Self { turns, current: 0 }
+    }
+
+    /// A game resumed from a save, the saved player plays
+    pub fn resume(turns: &[Turn], player: &PlayerInfo) -> Self {
+        let turns = turns.to_vec();
+        let current = turns
+            .iter()
+            .position(|turn| turn.player.name() == player.name())
+            .unwrap_or_default();
+        Self { turns, current }
+    }
+
+    /// The players of a replay, from the level where it was recorded
+    pub fn replay(players: &[PlayerInfo], level: &str, spawn: Option<&str>) -> Self {
+        let turns = players
+            .iter()
+            .map(|player| Turn::new(player.clone(), level, spawn.map(String::from)))
+            .collect();
+        Self { turns, current: 0 }
+    }
+
+    pub fn current(&self) -> Option<&Turn> {
+        self.turns.get(self.current)
+    }
+
+    pub fn count(&self) -> usize {
+        self.turns.len()
+    }
+
+    /// The current player goes to another level
+    pub fn reach(&mut self, level: &str, player: &PlayerInfo, spawn: Option<String>) {
+        if let Some(turn) = self.turns.get_mut(self.current) {
+            *turn = Turn::new(player.clone(), level, spawn);
+        }
+    }
+
+    /// The players playing together reached a level, the first one with lives left leads.
+    /// None when the game is over
+    pub fn together(
+        &mut self,
+        level: &str,
+        players: &[PlayerInfo],
+        spawn: Option<String>,
+    ) -> Option<&Turn> {
+        for turn in self.turns.iter_mut() {
+            if let Some(player) = players.iter().find(|p| p.name() == turn.player.name()) {
+                *turn = Turn::new(player.clone(), level, spawn.clone());
+            }
+        }
+
+        self.current = self.turns.iter().position(|t| t.player.lives() > 0)?;
+        self.current()
+    }
+
+    /// The turns to save, with the players of the level as they are now
+    pub fn save(&self, level: &str, players: &[PlayerInfo]) -> Vec<Turn> {
+        self.turns
+            .iter()
+            .map(
+                |turn| match players.iter().find(|p| p.name() == turn.player.name()) {
+                    Some(player) => Turn::new(player.clone(), level, None),
+                    None => turn.clone(),
+                },
+            )
+            .collect()
+    }
+
+    /// The players along the current one, with lives left
+    pub fn partners(&self) -> Vec<PlayerInfo> {
+        self.turns
+            .iter()
+            .enumerate()
+            .filter(|(i, turn)| *i != self.current && turn.player.lives() > 0)
+            .map(|(_, turn)| turn.player.clone())
+            .collect()
+    }
+
+    /// The current player died, the turn goes to the next player with lives left.
+    /// None when the game is over
+    pub fn died(
+        &mut self,
+        level: &str,
+        player: &PlayerInfo,
+        spawn: Option<String>,
+    ) -> Option<&Turn> {
+        self.reach(level, player, spawn);
+
+        let count = self.turns.len();
+        let next = (1..=count)
+            .map(|i| (self.current + i) % count)
+            .find(|&i| self.turns[i].player.lives() > 0)?;
+        self.current = next;
+        self.current()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn players() -> Vec<String> {
+        vec![String::from("mario"), String::from("luigi")]
+    }
+
+    #[test]
+    fn should_alternate_on_death() {
+        let mut turns = Turns::new(&players());
+        assert_eq!(turns.current().unwrap().player().name(), "mario");
+
+        // Mario reached 1-2, then died
+        let mario = PlayerInfo::new("mario", 3, 500, 10);
+        turns.reach("1-2", &mario, None);
+        let mario = PlayerInfo::new("mario", 2, 800, 12);
+        let next = turns.died("1-2", &mario, Some(String::from("pipe")));
+        let next = next.unwrap();
+        assert_eq!(next.player().name(), "luigi");
+        assert_eq!(next.level(), "1-1");
+        assert_eq!(next.player().lives(), 3);
+
+        // Back to Mario where he died
+        let luigi = PlayerInfo::new("luigi", 2, 100, 1);
+        let next = turns.died("1-1", &luigi, None).unwrap();
+        assert_eq!(next.player().name(), "mario");
+        assert_eq!(next.player().score(), 800);
+        assert_eq!(next.level(), "1-2");
+        assert_eq!(next.spawn(), Some("pipe"));
+    }
+
+    #[test]
+    fn should_skip_players_without_lives() {
+        let mut turns = Turns::new(&players());
+
+        let mario = PlayerInfo::new("mario", 0, 0, 0);
+        let next = turns.died("1-1", &mario, None).unwrap();
+        assert_eq!(next.player().name(), "luigi");
+
+        let luigi = PlayerInfo::new("luigi", 2, 0, 0);
+        let next = turns.died("1-1", &luigi, None).unwrap();
+        assert_eq!(next.player().name(), "luigi");
+
+        let luigi = PlayerInfo::new("luigi", 0, 0, 0);
+        assert!(turns.died("1-1", &luigi, None).is_none());
+    }
+
+    #[test]
+    fn should_play_together() {
+        let mut turns = Turns::new(&players());
+        assert_eq!(turns.partners()[0].name(), "luigi");
+
+        // Mario is out of lives, Luigi leads alone
+        let players = [
+            PlayerInfo::new("mario", 0, 300, 2),
+            PlayerInfo::new("luigi", 1, 200, 1),
+        ];
+        let next = turns.together("1-2", &players, None).unwrap();
+        assert_eq!(next.player().name(), "luigi");
+        assert_eq!(next.level(), "1-2");
+        assert!(turns.partners().is_empty());
+
+        let players = [PlayerInfo::new("luigi", 0, 200, 1)];
+        assert!(turns.together("1-2", &players, None).is_none());
+    }
+
+    #[test]
+    fn should_resume_saved_turns() {
+        let mut turns = Turns::new(&players());
+        let luigi = PlayerInfo::new("luigi", 2, 0, 0);
+        turns.died("1-1", &PlayerInfo::new("mario", 2, 400, 4), None);
+        turns.died("1-1", &luigi, Some(String::from("pipe")));
+
+        // Mario plays 1-2, Luigi waits in 1-1
+        let players = [PlayerInfo::new("mario", 2, 900, 9)];
+        let saved = turns.save("1-2", &players);
+        let mut resumed = Turns::resume(&saved, &players[0]);
+        assert_eq!(resumed.current().unwrap().level(), "1-2");
+        assert_eq!(resumed.current().unwrap().player().score(), 900);
+
+        let next = resumed.died("1-2", &players[0], None).unwrap();
+        assert_eq!(next.player().name(), "luigi");
+        assert_eq!(next.level(), "1-1");
+        assert_eq!(next.spawn(), Some("pipe"));
+    }
+}
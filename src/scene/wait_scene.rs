@@ -8,6 +8,7 @@ use crate::layers::dashboard::Dashboard;
 use crate::layers::player_progress::PlayerProgress;
 use crate::scene::level::Level;
 use crate::scene::Scene;
+use crate::video::{Color, Renderer};
 
 pub struct WaitScene {
     count_down: f64,
@@ -18,13 +19,14 @@ pub struct WaitScene {
 }
 
 impl WaitScene {
-    pub fn new(font: Rc<Font>, level: Rc<RefCell<Level>>) -> Self {
+    /// With several players, the player whose turn it is is shown
+    pub fn new(font: Rc<Font>, level: Rc<RefCell<Level>>, show_turn: bool) -> Self {
         let count_down = 2.;
 
         // Layers
-        let color = ColorsLayer::new("#000");
+        let color = ColorsLayer::new(Color::BLACK);
         let dashboard = Dashboard::new(font.clone());
-        let progress = PlayerProgress::new(font, level.clone());
+        let progress = PlayerProgress::new(font, level.clone(), show_turn);
 
         Self {
             level,
@@ -46,14 +48,12 @@ impl Scene for WaitScene {
         }
     }
 
-    fn draw(&mut self, context: &GameContext) {
-        let context = context.video_context();
-
+    fn draw(&mut self, renderer: &dyn Renderer) {
         if let Some(player) = self.level.borrow().find_player() {
-            self.color.draw(context.clone());
+            self.color.draw(renderer);
             self.dashboard
-                .draw_info(context.clone(), self.level.borrow().name(), player);
-            self.progress.draw(context);
+                .draw_info(renderer, self.level.borrow().name(), player);
+            self.progress.draw(renderer);
         }
     }
 }
@@ -1,65 +1,222 @@
 use std::cell::RefCell;
 use std::rc::Rc;
 
-use web_sys::{AudioContext, CanvasRenderingContext2d};
+use wasm_bindgen::prelude::*;
+use wasm_bindgen::JsCast;
+use web_sys::HtmlCanvasElement;
 
 use crate::assets::Assets;
+use crate::camera::Camera;
 use crate::events::{Event, EventBuffer};
-use crate::game::{GameContext, PlayerInfo};
-use crate::input::Keyboard;
+use crate::game::GameContext;
+use crate::input::bindings::KeyBindings;
+use crate::input::gamepad::Gamepads;
+use crate::input::keyboard::Keyboard;
+use crate::input::replay::Replay;
+use crate::input::touch::TouchControls;
+use crate::input::{Input, InputSource};
+use crate::layers::touch::TouchLayer;
+use crate::layers::{Compositor, Drawable};
+use crate::scene::save::SaveState;
+use crate::scene::title::CHARACTERS;
 use crate::scene::SceneRunner;
-use crate::utils::log;
+use crate::utils::{local_storage, log, window};
+use crate::video::Renderer;
+
+const SAVE_KEY: &str = "plop-mario-save";
+const KEYMAP_KEY: &str = "plop-mario-keymap";
+/// The inputs of the last level played
+const RECORDING_KEY: &str = "plop-mario-recording";
+/// A recording copied here is played once on the next start, to reproduce a bug
+const REPLAY_KEY: &str = "plop-mario-replay";
 
 pub struct System {
-    audio_context: Rc<AudioContext>,
-    video_context: Rc<CanvasRenderingContext2d>,
+    renderer: Box<dyn Renderer>,
     event_buffer: Rc<RefCell<EventBuffer>>,
     scene_runner: Rc<RefCell<SceneRunner>>,
+    input: Rc<RefCell<Input>>,
+    overlays: Compositor,
+    screen: Camera,
 }
 
 impl System {
-    pub fn new(assets: Assets, video_context: Rc<CanvasRenderingContext2d>) -> Self {
+    pub fn new(assets: Assets, renderer: Box<dyn Renderer>, canvas: &HtmlCanvasElement) -> Self {
         // Events
         let event_buffer: Rc<RefCell<EventBuffer>> = Rc::default();
 
-        // Keyboard
-        let mut input = Keyboard::new(&assets.configuration());
-        input.listen();
-        let input = Rc::new(RefCell::new(input));
+        // Inputs
+        let config = assets.configuration();
+        let mut keyboard = Keyboard::new(&config);
+        if let Some(bindings) = System::load_bindings() {
+            keyboard.set_bindings(bindings);
+        }
+        keyboard.listen();
+        let keyboard = Rc::new(RefCell::new(keyboard));
+        let gamepads = Gamepads::new(&config.gamepad);
+        let touch = Rc::new(RefCell::new(TouchControls::new(renderer.size())));
+        TouchControls::listen(touch.clone(), canvas);
+        let sources: Vec<Rc<RefCell<dyn InputSource>>> = vec![
+            keyboard.clone(),
+            Rc::new(RefCell::new(gamepads)),
+            touch.clone(),
+        ];
+        let input = Rc::new(RefCell::new(Input::new(sources)));
+
+        // Overlays, drawn over the scenes
+        let mut overlays = Compositor::default();
+        let touch_layer = TouchLayer::new(touch, assets.font());
+        overlays.add_layer(Rc::new(RefCell::new(touch_layer)));
+        let screen = Camera::new(renderer.size());
 
         // Scenes
-        let scene_runner = SceneRunner::new(assets, input);
+        let scene_runner = SceneRunner::new(assets, input.clone(), keyboard);
         let scene_runner = Rc::new(RefCell::new(scene_runner));
-
-        // Audio
-        let audio_context = AudioContext::new().unwrap();
-        let audio_context = Rc::new(audio_context);
+        System::save_on_leave(scene_runner.clone());
 
         Self {
-            audio_context,
-            video_context,
+            renderer,
             event_buffer,
             scene_runner,
+            input,
+            overlays,
+            screen,
+        }
+    }
+
+    pub fn start(&mut self) {
+        match System::load_replay() {
+            Some(replay) => {
+                log(&format!(
+                    "Replay <{}> with {} inputs",
+                    replay.level(),
+                    replay.inputs().len()
+                ));
+                let played = self.scene_runner.borrow_mut().replay(&replay);
+                if let Err(err) = played {
+                    log(&format!("Cannot replay: {}", err));
+                    System::title(&self.scene_runner);
+                }
+            }
+            None => System::title(&self.scene_runner),
+        }
+    }
+
+    fn title(scene_runner: &Rc<RefCell<SceneRunner>>) {
+        let can_continue = System::load_save().is_some();
+        scene_runner.borrow_mut().title(can_continue);
+    }
+
+    /// A save that cannot be restored is discarded, a new game starts instead
+    fn resume(scene_runner: &Rc<RefCell<SceneRunner>>) {
+        if let Some(save) = System::load_save() {
+            log(&format!("Resume <{}>", save.level()));
+            let resumed = scene_runner.borrow_mut().resume(&save);
+            if let Err(err) = resumed {
+                log(&format!("Cannot resume: {}", err));
+                System::clear_save();
+                let players = [String::from(CHARACTERS[0])];
+                scene_runner.borrow_mut().new_game(&players, false);
+            }
         }
     }
 
-    pub fn start(&mut self, player_name: &str) {
-        let player_info = PlayerInfo::new(player_name, 3, 0, 0);
-        self.scene_runner
-            .borrow_mut()
-            .run_level("1-1", &player_info);
+    fn load_save() -> Option<SaveState> {
+        let json = local_storage()?.get_item(SAVE_KEY).ok()??;
+        SaveState::from_json(json.as_str())
+            .inspect_err(|err| log(&format!("Cannot resume: {}", err)))
+            .ok()
+    }
+
+    fn clear_save() {
+        if let Some(storage) = local_storage() {
+            let _ = storage.remove_item(SAVE_KEY);
+        }
+    }
+
+    /// The keys chosen by the players, merged over the keymaps of the configuration
+    fn load_bindings() -> Option<Vec<KeyBindings>> {
+        let json = local_storage()?.get_item(KEYMAP_KEY).ok()??;
+        KeyBindings::players_from_json(json.as_str())
+            .inspect_err(|err| log(&format!("Cannot load the keys: {}", err)))
+            .ok()
+    }
+
+    fn save_bindings(bindings: &[KeyBindings]) {
+        if let Some(storage) = local_storage() {
+            let json = KeyBindings::players_to_json(bindings);
+            storage
+                .set_item(KEYMAP_KEY, json.as_str())
+                .unwrap_or_else(|_| log("Cannot save the keys"));
+        }
+    }
+
+    /// The replay is removed once loaded, the next start shows the title
+    fn load_replay() -> Option<Replay> {
+        let storage = local_storage()?;
+        let json = storage.get_item(REPLAY_KEY).ok()??;
+        let _ = storage.remove_item(REPLAY_KEY);
+        Replay::from_json(json.as_str())
+            .inspect_err(|err| log(&format!("Cannot replay: {}", err)))
+            .ok()
+    }
+
+    fn save_recording(replay: &Replay) {
+        if let Some(storage) = local_storage() {
+            storage
+                .set_item(RECORDING_KEY, replay.to_json().as_str())
+                .unwrap_or_else(|_| log("Cannot save the recording"));
+        }
+    }
+
+    /// Keep the level being played, or forget the save without a level to continue
+    fn write_save(scene_runner: &Rc<RefCell<SceneRunner>>) {
+        let save = scene_runner.borrow().save();
+        match save {
+            Some(save) => System::store_save(&save),
+            None => System::clear_save(),
+        }
+    }
+
+    fn store_save(save: &SaveState) {
+        if let Some(storage) = local_storage() {
+            storage
+                .set_item(SAVE_KEY, save.to_json().as_str())
+                .unwrap_or_else(|_| log("Cannot save the game"));
+        }
+    }
+
+    fn save_on_leave(scene_runner: Rc<RefCell<SceneRunner>>) {
+        let closure = Closure::wrap(Box::new(move || {
+            if let Some(save) = scene_runner.borrow().save() {
+                System::store_save(&save);
+            }
+            if let Some(replay) = scene_runner.borrow().recording() {
+                System::save_recording(&replay);
+            }
+        }) as Box<dyn FnMut()>);
+
+        window()
+            .add_event_listener_with_callback("pagehide", closure.as_ref().unchecked_ref())
+            .expect("Cannot listen the event");
+        closure.forget();
     }
 
     pub fn update(&mut self, dt: f64) {
-        let context = GameContext::new(
-            self.audio_context.clone(),
-            self.video_context.clone(),
-            self.event_buffer.clone(),
-            dt,
-        );
+        let context = GameContext::new(self.event_buffer.clone(), dt);
+
+        // Gamepads
+        self.input.borrow().poll();
+
+        // The inputs of the level left
+        let recording = self.scene_runner.borrow_mut().take_recording();
+        if let Some(replay) = recording {
+            System::save_recording(&replay);
+        }
 
         // Update scene
         self.scene_runner.borrow().update(&context);
+        self.scene_runner.borrow().draw(self.renderer.as_ref());
+        self.overlays.draw(self.renderer.as_ref(), &self.screen);
 
         // Process events
         let sr = self.scene_runner.clone();
@@ -68,9 +225,48 @@ impl System {
         for event in system_events.iter() {
             match event {
                 Event::SceneComplete => sr.borrow_mut().run_next(),
-                Event::GotoLevel { level, player } => {
-                    log(&format!("Goto <{level}> with {player:?}"));
-                    sr.borrow_mut().run_level(level, player);
+                Event::GotoLevel {
+                    level,
+                    player,
+                    spawn,
+                } => {
+                    log(&format!("Goto <{level}> at {spawn:?} with {player:?}"));
+                    sr.borrow_mut().goto(level, player, spawn.as_deref());
+                    return;
+                }
+                Event::PlayerDied {
+                    level,
+                    player,
+                    spawn,
+                } => {
+                    log(&format!("{} died in <{level}>", player.name()));
+                    sr.borrow_mut().player_died(level, player, spawn.as_deref());
+                    if sr.borrow().save().is_none() {
+                        System::clear_save();
+                    }
+                    return;
+                }
+                Event::Title => {
+                    System::title(&sr);
+                    return;
+                }
+                Event::Quit => {
+                    System::write_save(&sr);
+                    System::title(&sr);
+                    return;
+                }
+                Event::NewGame { players, coop } => {
+                    log(&format!("New game with {:?}", players));
+                    sr.borrow_mut().new_game(players, *coop);
+                    return;
+                }
+                Event::Resume => {
+                    System::resume(&sr);
+                    return;
+                }
+                Event::KeysChanged(bindings) => System::save_bindings(bindings),
+                Event::Options => {
+                    sr.borrow_mut().options();
                     return;
                 }
                 _ => {} // Skip other events
@@ -78,9 +274,3 @@ impl System {
         }
     }
 }
-
-impl Drop for System {
-    fn drop(&mut self) {
-        let _ = self.audio_context.close();
-    }
-}
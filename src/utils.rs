@@ -1,19 +1,26 @@
 use std::f64;
-use std::rc::Rc;
 
 use wasm_bindgen::prelude::*;
 use wasm_bindgen::JsCast;
-use web_sys::{CanvasRenderingContext2d, Document, HtmlCanvasElement, HtmlImageElement, Window};
+use web_sys::{
+    CanvasRenderingContext2d, Document, HtmlCanvasElement, HtmlImageElement, Storage, Window,
+};
 
 use crate::assets::sprites::Rectangle;
 use crate::physics::Size;
 
+#[cfg(target_arch = "wasm32")]
 #[wasm_bindgen]
 extern "C" {
     #[wasm_bindgen(js_namespace = console)]
     pub fn log(a: &str);
 }
 
+#[cfg(not(target_arch = "wasm32"))]
+pub fn log(a: &str) {
+    println!("{}", a);
+}
+
 #[macro_export]
 macro_rules! console_log {
     ($($t:tt)*) => (log(&format_args!($($t)*).to_string()))
@@ -35,6 +42,10 @@ pub fn document() -> Document {
         .expect("should have a document on window")
 }
 
+pub fn local_storage() -> Option<Storage> {
+    window().local_storage().ok().flatten()
+}
+
 pub fn request_animation_frame(update: &Closure<dyn FnMut()>) {
     window()
         .request_animation_frame(update.as_ref().unchecked_ref())
@@ -83,8 +94,18 @@ where
     buffer
 }
 
+/// Copy an image into a canvas, to be used as a bitmap
+pub fn canvas_image(image: &HtmlImageElement) -> HtmlCanvasElement {
+    let size = Size::new(image.width(), image.height());
+    create_buffer(size, |context| {
+        context
+            .draw_image_with_html_image_element(image, 0., 0.)
+            .unwrap()
+    })
+}
+
 pub fn create_image_buffer(
-    image: Rc<HtmlImageElement>,
+    image: &HtmlCanvasElement,
     rect: &Rectangle,
     mirror: bool,
 ) -> HtmlCanvasElement {
@@ -101,8 +122,8 @@ pub fn create_image_buffer(
             context.translate(-(width as f64), 0.).unwrap();
         }
         context
-            .draw_image_with_html_image_element_and_sw_and_sh_and_dx_and_dy_and_dw_and_dh(
-                &image.clone(),
+            .draw_image_with_html_canvas_element_and_sw_and_sh_and_dx_and_dy_and_dw_and_dh(
+                image,
                 x as f64,
                 y as f64,
                 width as f64,
@@ -0,0 +1,83 @@
+use std::rc::Rc;
+
+use web_sys::CanvasRenderingContext2d;
+
+use crate::assets::sprites::Rectangle;
+use crate::physics::Size;
+use crate::utils::{canvas, context_2d};
+use crate::video::{Bitmap, Color, Renderer};
+
+pub struct CanvasRenderer {
+    context: CanvasRenderingContext2d,
+}
+
+impl CanvasRenderer {
+    pub fn new(context: CanvasRenderingContext2d) -> Self {
+        Self { context }
+    }
+
+    fn canvas(bitmap: &Bitmap) -> &Rc<web_sys::HtmlCanvasElement> {
+        match bitmap {
+            Bitmap::Canvas(canvas) => canvas,
+            Bitmap::Pixels(_) => panic!("Cannot blit a framebuffer into a canvas"),
+        }
+    }
+}
+
+impl Renderer for CanvasRenderer {
+    fn size(&self) -> Size {
+        let canvas = self.context.canvas().unwrap();
+        Size::new(canvas.width(), canvas.height())
+    }
+
+    fn clear(&self) {
+        let Size { width, height } = self.size();
+        self.context.clear_rect(0., 0., width as f64, height as f64);
+    }
+
+    fn fill_rect(&self, color: Color, x: f64, y: f64, width: f64, height: f64) {
+        self.context.set_fill_style_str(color.css().as_str());
+        self.context.fill_rect(x, y, width, height);
+    }
+
+    fn stroke_rect(&self, color: Color, x: f64, y: f64, width: f64, height: f64) {
+        self.context.set_stroke_style_str(color.css().as_str());
+        self.context.set_line_width(0.5);
+        self.context.stroke_rect(x, y, width, height);
+    }
+
+    fn blit(&self, bitmap: &Bitmap, x: f64, y: f64) {
+        let canvas = CanvasRenderer::canvas(bitmap);
+        self.context
+            .draw_image_with_html_canvas_element(canvas, x.floor(), y.floor())
+            .unwrap();
+    }
+
+    fn blit_part(&self, bitmap: &Bitmap, source: &Rectangle, x: f64, y: f64) {
+        let canvas = CanvasRenderer::canvas(bitmap);
+        let width = source.width as f64;
+        let height = source.height as f64;
+        self.context
+            .draw_image_with_html_canvas_element_and_sw_and_sh_and_dx_and_dy_and_dw_and_dh(
+                canvas,
+                source.x as f64,
+                source.y as f64,
+                width,
+                height,
+                x.floor(),
+                y.floor(),
+                width,
+                height,
+            )
+            .unwrap();
+    }
+
+    fn bitmap(&self) -> Bitmap {
+        Bitmap::Canvas(Rc::new(self.context.canvas().unwrap()))
+    }
+
+    fn create_buffer(&self, size: Size) -> Box<dyn Renderer> {
+        let buffer = canvas(size);
+        Box::new(CanvasRenderer::new(context_2d(&buffer)))
+    }
+}
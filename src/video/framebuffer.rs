@@ -0,0 +1,346 @@
+use std::cell::RefCell;
+#[cfg(not(target_arch = "wasm32"))]
+use std::io::Cursor;
+use std::rc::Rc;
+
+use crate::assets::sprites::Rectangle;
+use crate::physics::Size;
+use crate::video::{Bitmap, Color, Renderer};
+
+/// An in-memory RGBA image
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Framebuffer {
+    size: Size,
+    pixels: Vec<u8>,
+}
+
+impl Framebuffer {
+    pub fn new(size: Size) -> Self {
+        let pixels = vec![0; (size.width * size.height * 4) as usize];
+        Self { size, pixels }
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn decode_png(bytes: &[u8]) -> Result<Self, String> {
+        let mut decoder = png::Decoder::new(Cursor::new(bytes));
+        decoder.set_transformations(png::Transformations::normalize_to_color8());
+        let mut reader = decoder.read_info().map_err(|err| err.to_string())?;
+        let mut data = vec![0; reader.output_buffer_size().unwrap_or_default()];
+        let info = reader
+            .next_frame(&mut data)
+            .map_err(|err| err.to_string())?;
+
+        let channels = info.color_type.samples();
+        let size = Size::new(info.width, info.height);
+        let mut result = Framebuffer::new(size);
+        for (index, pixel) in result.pixels.chunks_exact_mut(4).enumerate() {
+            let sample = &data[index * channels..(index + 1) * channels];
+            let rgba = match *sample {
+                [l] => [l, l, l, 255],
+                [l, a] => [l, l, l, a],
+                [r, g, b] => [r, g, b, 255],
+                [r, g, b, a] => [r, g, b, a],
+                _ => return Err(format!("Unsupported PNG color {:?}", info.color_type)),
+            };
+            pixel.copy_from_slice(&rgba);
+        }
+        Ok(result)
+    }
+
+    /// Decode the first frame of a GIF
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn decode_gif(bytes: &[u8]) -> Result<Self, String> {
+        let mut options = gif::DecodeOptions::new();
+        options.set_color_output(gif::ColorOutput::RGBA);
+        let mut decoder = options
+            .read_info(Cursor::new(bytes))
+            .map_err(|err| err.to_string())?;
+        let size = Size::new(decoder.width() as u32, decoder.height() as u32);
+        let mut result = Framebuffer::new(size);
+
+        let frame = decoder
+            .read_next_frame()
+            .map_err(|err| err.to_string())?
+            .ok_or("Empty GIF")?;
+        let frame_width = frame.width as usize;
+        for (index, pixel) in frame.buffer.chunks_exact(4).enumerate() {
+            let x = frame.left as i32 + (index % frame_width) as i32;
+            let y = frame.top as i32 + (index / frame_width) as i32;
+            if let Some(offset) = result.offset(x, y) {
+                result.pixels[offset..offset + 4].copy_from_slice(pixel);
+            }
+        }
+        Ok(result)
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn encode_png(&self) -> Result<Vec<u8>, String> {
+        let mut result = vec![];
+        let mut encoder = png::Encoder::new(&mut result, self.size.width, self.size.height);
+        encoder.set_color(png::ColorType::Rgba);
+        encoder.set_depth(png::BitDepth::Eight);
+        let mut writer = encoder.write_header().map_err(|err| err.to_string())?;
+        writer
+            .write_image_data(self.pixels.as_slice())
+            .map_err(|err| err.to_string())?;
+        writer.finish().map_err(|err| err.to_string())?;
+        Ok(result)
+    }
+
+    pub fn size(&self) -> Size {
+        self.size
+    }
+
+    fn offset(&self, x: i32, y: i32) -> Option<usize> {
+        let Size { width, height } = self.size;
+        if x < 0 || y < 0 || x >= width as i32 || y >= height as i32 {
+            None
+        } else {
+            Some((y as usize * width as usize + x as usize) * 4)
+        }
+    }
+
+    pub fn pixel(&self, x: i32, y: i32) -> Color {
+        self.offset(x, y)
+            .map(|offset| {
+                let p = &self.pixels[offset..offset + 4];
+                Color::rgba(p[0], p[1], p[2], p[3])
+            })
+            .unwrap_or(Color::TRANSPARENT)
+    }
+
+    /// Draw the color over the pixel, with alpha blending
+    pub fn blend(&mut self, x: i32, y: i32, color: Color) {
+        if color.a == 0 {
+            return;
+        }
+        if let Some(offset) = self.offset(x, y) {
+            let pixel = &mut self.pixels[offset..offset + 4];
+            let alpha = color.a as u32;
+            let inverse = (255 - alpha) * pixel[3] as u32 / 255;
+            let out_alpha = alpha + inverse;
+            let mix = |src: u8, dst: u8| {
+                ((src as u32 * alpha + dst as u32 * inverse) / out_alpha.max(1)) as u8
+            };
+            pixel[0] = mix(color.r, pixel[0]);
+            pixel[1] = mix(color.g, pixel[1]);
+            pixel[2] = mix(color.b, pixel[2]);
+            pixel[3] = out_alpha as u8;
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.pixels.fill(0);
+    }
+
+    pub fn crop(&self, rect: &Rectangle, mirror: bool) -> Framebuffer {
+        let mut result = Framebuffer::new(rect.size());
+        for dy in 0..rect.height as i32 {
+            for dx in 0..rect.width as i32 {
+                let sx = if mirror {
+                    rect.x as i32 + rect.width as i32 - 1 - dx
+                } else {
+                    rect.x as i32 + dx
+                };
+                let color = self.pixel(sx, rect.y as i32 + dy);
+                if let Some(offset) = result.offset(dx, dy) {
+                    result.pixels[offset..offset + 4]
+                        .copy_from_slice(&[color.r, color.g, color.b, color.a]);
+                }
+            }
+        }
+        result
+    }
+
+    pub fn fill_rect(&mut self, color: Color, x: i32, y: i32, width: i32, height: i32) {
+        for py in y..y + height {
+            for px in x..x + width {
+                self.blend(px, py, color);
+            }
+        }
+    }
+
+    pub fn stroke_rect(&mut self, color: Color, x: i32, y: i32, width: i32, height: i32) {
+        if width <= 0 || height <= 0 {
+            return;
+        }
+        let right = x + width - 1;
+        let bottom = y + height - 1;
+        for px in x..=right {
+            self.blend(px, y, color);
+            if bottom != y {
+                self.blend(px, bottom, color);
+            }
+        }
+        for py in y + 1..bottom {
+            self.blend(x, py, color);
+            if right != x {
+                self.blend(right, py, color);
+            }
+        }
+    }
+
+    pub fn blit(&mut self, source: &Framebuffer, rect: &Rectangle, x: i32, y: i32) {
+        for dy in 0..rect.height as i32 {
+            for dx in 0..rect.width as i32 {
+                let color = source.pixel(rect.x as i32 + dx, rect.y as i32 + dy);
+                self.blend(x + dx, y + dy, color);
+            }
+        }
+    }
+}
+
+/// Render into a framebuffer, without any browser
+pub struct FramebufferRenderer {
+    buffer: Rc<RefCell<Framebuffer>>,
+}
+
+impl FramebufferRenderer {
+    pub fn new(size: Size) -> Self {
+        let buffer = Rc::new(RefCell::new(Framebuffer::new(size)));
+        Self { buffer }
+    }
+
+    pub fn framebuffer(&self) -> Framebuffer {
+        self.buffer.borrow().clone()
+    }
+
+    fn pixels(bitmap: &Bitmap) -> &Rc<RefCell<Framebuffer>> {
+        match bitmap {
+            Bitmap::Pixels(pixels) => pixels,
+            Bitmap::Canvas(_) => panic!("Cannot blit a canvas into a framebuffer"),
+        }
+    }
+}
+
+impl Renderer for FramebufferRenderer {
+    fn size(&self) -> Size {
+        self.buffer.borrow().size()
+    }
+
+    fn clear(&self) {
+        self.buffer.borrow_mut().clear();
+    }
+
+    fn fill_rect(&self, color: Color, x: f64, y: f64, width: f64, height: f64) {
+        self.buffer.borrow_mut().fill_rect(
+            color,
+            x.floor() as i32,
+            y.floor() as i32,
+            width.round() as i32,
+            height.round() as i32,
+        );
+    }
+
+    fn stroke_rect(&self, color: Color, x: f64, y: f64, width: f64, height: f64) {
+        self.buffer.borrow_mut().stroke_rect(
+            color,
+            x.floor() as i32,
+            y.floor() as i32,
+            width.round() as i32,
+            height.round() as i32,
+        );
+    }
+
+    fn blit(&self, bitmap: &Bitmap, x: f64, y: f64) {
+        let Size { width, height } = bitmap.size();
+        let source = Rectangle {
+            x: 0,
+            y: 0,
+            width,
+            height,
+        };
+        self.blit_part(bitmap, &source, x, y);
+    }
+
+    fn blit_part(&self, bitmap: &Bitmap, source: &Rectangle, x: f64, y: f64) {
+        let pixels = FramebufferRenderer::pixels(bitmap);
+        let (x, y) = (x.floor() as i32, y.floor() as i32);
+        if Rc::ptr_eq(pixels, &self.buffer) {
+            let copy = pixels.borrow().clone();
+            self.buffer.borrow_mut().blit(&copy, source, x, y);
+        } else {
+            self.buffer
+                .borrow_mut()
+                .blit(&pixels.borrow(), source, x, y);
+        }
+    }
+
+    fn bitmap(&self) -> Bitmap {
+        Bitmap::Pixels(self.buffer.clone())
+    }
+
+    fn create_buffer(&self, size: Size) -> Box<dyn Renderer> {
+        Box::new(FramebufferRenderer::new(size))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const GREEN: Color = Color::rgb(0, 255, 0);
+
+    #[test]
+    fn should_fill_and_stroke() {
+        let renderer = FramebufferRenderer::new(Size::new(4, 4));
+        renderer.fill_rect(GREEN, 1., 1., 2., 2.);
+        renderer.stroke_rect(Color::RED, 0., 0., 4., 4.);
+
+        let framebuffer = renderer.framebuffer();
+        assert_eq!(framebuffer.pixel(0, 0), Color::RED);
+        assert_eq!(framebuffer.pixel(3, 2), Color::RED);
+        assert_eq!(framebuffer.pixel(1, 1), GREEN);
+        assert_eq!(framebuffer.pixel(2, 2), GREEN);
+
+        renderer.clear();
+        assert_eq!(renderer.framebuffer().pixel(1, 1), Color::TRANSPARENT);
+    }
+
+    #[test]
+    fn should_blend_alpha() {
+        let mut framebuffer = Framebuffer::new(Size::new(1, 1));
+        framebuffer.blend(0, 0, Color::BLUE);
+        framebuffer.blend(0, 0, Color::rgba(255, 0, 0, 0));
+        assert_eq!(framebuffer.pixel(0, 0), Color::BLUE);
+
+        framebuffer.blend(0, 0, Color::rgba(255, 0, 0, 128));
+        assert_eq!(framebuffer.pixel(0, 0), Color::rgba(128, 0, 127, 255));
+    }
+
+    #[test]
+    fn should_blit_mirrored_crop() {
+        let mut image = Framebuffer::new(Size::new(3, 1));
+        image.blend(0, 0, Color::RED);
+        image.blend(1, 0, GREEN);
+        image.blend(2, 0, Color::BLUE);
+        let rect = Rectangle {
+            x: 0,
+            y: 0,
+            width: 2,
+            height: 1,
+        };
+        let bitmap = Bitmap::from_pixels(image).crop(&rect, true);
+
+        let renderer = FramebufferRenderer::new(Size::new(4, 1));
+        renderer.blit(&bitmap, 1.5, 0.);
+        let framebuffer = renderer.framebuffer();
+        assert_eq!(framebuffer.pixel(0, 0), Color::TRANSPARENT);
+        assert_eq!(framebuffer.pixel(1, 0), GREEN);
+        assert_eq!(framebuffer.pixel(2, 0), Color::RED);
+        assert_eq!(framebuffer.pixel(3, 0), Color::TRANSPARENT);
+    }
+
+    #[test]
+    fn should_encode_png() {
+        let renderer = FramebufferRenderer::new(Size::new(2, 2));
+        renderer.fill_rect(Color::RED, 0., 0., 1., 2.);
+        let framebuffer = renderer.framebuffer();
+
+        let png = framebuffer.encode_png().unwrap();
+        assert_eq!(
+            Framebuffer::decode_png(png.as_slice()).unwrap(),
+            framebuffer
+        );
+    }
+}
@@ -0,0 +1,96 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use web_sys::{HtmlCanvasElement, HtmlImageElement};
+
+use crate::assets::sprites::Rectangle;
+use crate::physics::Size;
+use crate::utils::{canvas_image, create_image_buffer};
+use crate::video::framebuffer::Framebuffer;
+
+pub mod canvas;
+pub mod framebuffer;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Color {
+    pub r: u8,
+    pub g: u8,
+    pub b: u8,
+    pub a: u8,
+}
+
+impl Color {
+    pub const BLACK: Color = Color::rgb(0, 0, 0);
+    pub const RED: Color = Color::rgb(255, 0, 0);
+    pub const BLUE: Color = Color::rgb(0, 0, 255);
+    pub const TRANSPARENT: Color = Color::rgba(0, 0, 0, 0);
+
+    pub const fn rgb(r: u8, g: u8, b: u8) -> Self {
+        Self::rgba(r, g, b, 255)
+    }
+
+    pub const fn rgba(r: u8, g: u8, b: u8, a: u8) -> Self {
+        Self { r, g, b, a }
+    }
+
+    pub fn css(&self) -> String {
+        format!(
+            "rgba({},{},{},{})",
+            self.r,
+            self.g,
+            self.b,
+            self.a as f64 / 255.
+        )
+    }
+}
+
+/// An image that can be blitted by a renderer of the same backend
+#[derive(Clone)]
+pub enum Bitmap {
+    Canvas(Rc<HtmlCanvasElement>),
+    Pixels(Rc<RefCell<Framebuffer>>),
+}
+
+impl Bitmap {
+    pub fn from_image(image: &HtmlImageElement) -> Self {
+        Bitmap::Canvas(Rc::new(canvas_image(image)))
+    }
+
+    pub fn from_pixels(framebuffer: Framebuffer) -> Self {
+        Bitmap::Pixels(Rc::new(RefCell::new(framebuffer)))
+    }
+
+    pub fn size(&self) -> Size {
+        match self {
+            Bitmap::Canvas(canvas) => Size::new(canvas.width(), canvas.height()),
+            Bitmap::Pixels(pixels) => pixels.borrow().size(),
+        }
+    }
+
+    /// Copy a part of the bitmap, flipped horizontally if mirror
+    pub fn crop(&self, rect: &Rectangle, mirror: bool) -> Bitmap {
+        match self {
+            Bitmap::Canvas(canvas) => {
+                Bitmap::Canvas(Rc::new(create_image_buffer(canvas, rect, mirror)))
+            }
+            Bitmap::Pixels(pixels) => Bitmap::from_pixels(pixels.borrow().crop(rect, mirror)),
+        }
+    }
+}
+
+/// Drawing operations, implemented by the canvas and the framebuffer backends
+pub trait Renderer {
+    fn size(&self) -> Size;
+
+    fn clear(&self);
+    fn fill_rect(&self, color: Color, x: f64, y: f64, width: f64, height: f64);
+    fn stroke_rect(&self, color: Color, x: f64, y: f64, width: f64, height: f64);
+
+    fn blit(&self, bitmap: &Bitmap, x: f64, y: f64);
+    fn blit_part(&self, bitmap: &Bitmap, source: &Rectangle, x: f64, y: f64);
+
+    /// The drawn image, to be blitted into another renderer
+    fn bitmap(&self) -> Bitmap;
+    /// An offscreen renderer using the same backend
+    fn create_buffer(&self, size: Size) -> Box<dyn Renderer>;
+}